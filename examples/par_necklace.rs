@@ -0,0 +1,42 @@
+/*
+Counts necklaces in parallel, see the `necklace` example for the algorithm.
+
+Run with `cargo run --release --features rayon --example par_necklace <n> <base>`.
+*/
+
+extern crate discrete;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use discrete::*;
+use discrete::par::par_for_each_chunk;
+
+fn main() {
+    let x: DimensionN = Construct::new();
+    let n = std::env::args_os().nth(1)
+        .and_then(|s| s.into_string().ok())
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(4);
+    let base = std::env::args_os().nth(2)
+        .and_then(|s| s.into_string().ok())
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(3);
+    println!("n {}, base {}", n, base);
+    let ref dim = vec![base; n];
+    let counter = AtomicU64::new(0);
+    par_for_each_chunk(&x, dim, 4096, |&start: &usize, chunk: &[Vec<usize>]| {
+        let mut b = vec![0; n];
+        let mut found = 0;
+        'i: for (i, a) in chunk.iter().enumerate() {
+            for k in 1..n {
+                for m in 0..n {
+                    b[m] = a[(m + k) % n];
+                }
+                if x.to_index(dim, &b) < start + i { continue 'i; }
+            }
+            found += 1;
+        }
+        counter.fetch_add(found, Ordering::Relaxed);
+    });
+    println!("necklaces {}", counter.into_inner());
+}
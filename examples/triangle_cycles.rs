@@ -18,9 +18,10 @@ fn main() {
     // Exploits that the extra 3 pairs are permuted
     // right to left, so one can skip to every 6th solution.
     let scale = 6;
-    for i in 0..count/scale {
-        let i = i * scale;
-        s.to_pos(&dim, i, &mut pos);
+    let every: Stride<Permutation<Of<Pair>>> = Construct::new();
+    let every_dim = (dim, scale);
+    for i in 0..every.count(&every_dim) {
+        every.to_pos(&every_dim, i, &mut pos);
         let triangle = connected(pos[0], pos[1]) &&
             connected(pos[1], pos[2]) &&
             connected(pos[2], pos[0]);
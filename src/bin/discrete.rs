@@ -0,0 +1,352 @@
+//! Command line tool for counting, ranking and enumerating discrete spaces.
+//!
+//! ```text
+//! discrete count "Pair<Of<Pair>>" 4
+//! discrete rank Pair 4 "(0, 3)"
+//! discrete unrank "Permutation<Of<Pair>>" 100 12345678901234567890
+//! discrete enumerate "(Pair, PowerSet)" "(3, 2)" --from 2 --to 8 --step 2
+//! discrete sample Homotopy "(2, 3)" -n 5 --seed 1 --json
+//! ```
+
+extern crate discrete;
+
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use discrete::{BigUint, DynSpace, DynDim, DynPos, ParseDynError};
+
+const USAGE: &str = "\
+Usage: discrete <command> <space> <dim> [options]
+
+Commands:
+    count                                  Counts the positions of the space
+    rank <pos>                             Converts a position to its index
+    unrank <index>                         Converts an index to its position
+    enumerate [--from <i>] [--to <j>] [--step <k>]
+                                           Lists positions from index `i` up to `j`
+    sample -n <count> [--seed <seed>]      Picks random positions
+
+Options:
+    --json                                 Writes one JSON object per line
+    -h, --help                             Prints this message
+
+The space is a type expression, e.g. `PowerSet<Of<Pair>>`, `Slice<Lex<Pair>>` or `(Pair, Either<Context, DimensionN>)`.
+The dim and pos are literals, e.g. `4`, `[2, 3]`, `(2, [3, 4])` or `Snd(Path(0, 1))`.
+";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Count,
+    Rank(DynPos),
+    Unrank(BigUint),
+    Enumerate {from: BigUint, to: Option<BigUint>, step: BigUint},
+    Sample {n: usize, seed: Option<u64>},
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    command: Command,
+    space: DynSpace,
+    dim: DynDim,
+    json: bool,
+}
+
+/// Parses a value, pointing at the error in the text.
+fn parse<T: FromStr<Err = ParseDynError>>(what: &str, s: &str) -> Result<T, String> {
+    s.parse().map_err(|e: ParseDynError| {
+        let col = s[..e.offset].chars().count();
+        format!("{} in {}\n  {}\n  {}^", e.message, what, s, " ".repeat(col))
+    })
+}
+
+fn number<T: FromStr>(what: &str, s: &str) -> Result<T, String> {
+    s.trim().parse().map_err(|_| format!("Expected number for {}, found `{}`", what, s))
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut json = false;
+    let mut free = vec![];
+    let mut flags: Vec<(&str, &str)> = vec![];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--from" | "--to" | "--step" | "-n" | "--seed" => {
+                let value = iter.next().ok_or_else(|| format!("Expected value after `{}`", arg))?;
+                flags.push((arg, value));
+            }
+            x if x.starts_with('-') && x.len() > 1 && !x[1..].starts_with(char::is_numeric) =>
+                return Err(format!("Unknown option `{}`", x)),
+            _ => free.push(arg.as_str()),
+        }
+    }
+    let (&command, rest) = free.split_first().ok_or("Expected command")?;
+    let expected_args = match command {
+        "count" | "enumerate" | "sample" => 2,
+        "rank" | "unrank" => 3,
+        _ => return Err(format!("Unknown command `{}`", command)),
+    };
+    if rest.len() != expected_args {
+        return Err(format!("`{}` expects {} arguments, found {}", command, expected_args, rest.len()))
+    }
+    let allowed: &[&str] = match command {
+        "enumerate" => &["--from", "--to", "--step"],
+        "sample" => &["-n", "--seed"],
+        _ => &[],
+    };
+    if let Some((flag, _)) = flags.iter().find(|(flag, _)| !allowed.contains(flag)) {
+        return Err(format!("`{}` does not take `{}`", command, flag))
+    }
+    let flag = |name: &str| flags.iter().rev().find(|(f, _)| *f == name).map(|(_, v)| *v);
+    let command = match command {
+        "count" => Command::Count,
+        "rank" => Command::Rank(parse("position", rest[2])?),
+        "unrank" => Command::Unrank(number("index", rest[2])?),
+        "enumerate" => {
+            let from = flag("--from").map(|s| number("`--from`", s)).transpose()?;
+            let to = flag("--to").map(|s| number("`--to`", s)).transpose()?;
+            let step = flag("--step").map(|s| number("`--step`", s)).transpose()?;
+            let step = step.unwrap_or_else(|| 1usize.into());
+            if step == 0usize.into() {return Err("`--step` must be positive".into())}
+            Command::Enumerate {from: from.unwrap_or_default(), to, step}
+        }
+        "sample" => {
+            let n = flag("-n").ok_or("`sample` expects `-n <count>`")?;
+            let seed = flag("--seed").map(|s| number("`--seed`", s)).transpose()?;
+            Command::Sample {n: number("`-n`", n)?, seed}
+        }
+        _ => unreachable!(),
+    };
+    Ok(Options {
+        command,
+        space: parse("space", rest[0])?,
+        dim: parse("dimension", rest[1])?,
+        json,
+    })
+}
+
+/// Writes a position as JSON.
+///
+/// Lists and tuples become arrays, paths become `{"path": [a, b]}`
+/// and selections become `{"fst": a}` or `{"snd": b}`.
+fn json(pos: &DynPos) -> String {
+    fn list(v: &[DynPos]) -> String {
+        let items: Vec<String> = v.iter().map(json).collect();
+        format!("[{}]", items.join(","))
+    }
+    match pos {
+        DynPos::Nat(n) => n.to_string(),
+        DynPos::List(v) | DynPos::Tuple(v) => list(v),
+        DynPos::Path(ab) => format!("{{\"path\":[{},{}]}}", json(&ab.0), json(&ab.1)),
+        DynPos::Fst(x) => format!("{{\"fst\":{}}}", json(x)),
+        DynPos::Snd(x) => format!("{{\"snd\":{}}}", json(x)),
+    }
+}
+
+/// A small random generator (SplitMix64), so that samples can be repeated with a seed.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Picks a number below `n`, which must be positive.
+    fn below(&mut self, n: &BigUint) -> BigUint {
+        let bits = n.bits();
+        let words = bits.div_ceil(32) as usize;
+        loop {
+            let mut digits: Vec<u32> = (0..words).map(|_| self.next() as u32).collect();
+            if !bits.is_multiple_of(32) {
+                digits[words - 1] &= (1 << (bits % 32)) - 1;
+            }
+            let x = BigUint::new(digits);
+            if &x < n {return x}
+        }
+    }
+}
+
+fn run(opts: &Options, w: &mut impl Write) -> Result<(), String> {
+    let Options {space, dim, json: as_json, ..} = opts;
+    let io = |e: io::Error| e.to_string();
+    let count = space.try_count(dim)?;
+    let mut pos = space.try_zero(dim)?;
+    let write_pos = |w: &mut dyn Write, index: &BigUint, pos: &DynPos| {
+        if *as_json {writeln!(w, "{{\"index\":{},\"pos\":{}}}", index, json(pos))}
+        else {writeln!(w, "{}: {}", index, pos)}
+    };
+    match &opts.command {
+        Command::Count => {
+            if *as_json {writeln!(w, "{{\"count\":{}}}", count)}
+            else {writeln!(w, "{}", count)}
+        }.map_err(io)?,
+        Command::Rank(p) => {
+            let index = space.try_to_index(dim, p)?;
+            if *as_json {writeln!(w, "{{\"index\":{}}}", index)}
+            else {writeln!(w, "{}", index)}.map_err(io)?
+        }
+        Command::Unrank(index) => {
+            space.try_to_pos(dim, index.clone(), &mut pos)?;
+            if *as_json {write_pos(w, index, &pos)}
+            else {writeln!(w, "{}", pos)}.map_err(io)?
+        }
+        Command::Enumerate {from, to, step} => {
+            let to = match to {
+                Some(to) if *to < count => to,
+                _ => &count,
+            };
+            let mut index = from.clone();
+            while index < *to {
+                space.try_to_pos(dim, index.clone(), &mut pos)?;
+                write_pos(w, &index, &pos).map_err(io)?;
+                index += step;
+            }
+        }
+        Command::Sample {n, seed} => {
+            if count == 0usize.into() {return Err("The space is empty".into())}
+            let seed = seed.unwrap_or_else(|| {
+                SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+            });
+            let mut random = Random(seed);
+            for _ in 0..*n {
+                let index = random.below(&count);
+                space.try_to_pos(dim, index.clone(), &mut pos)?;
+                write_pos(w, &index, &pos).map_err(io)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        print!("{}", USAGE);
+        return
+    }
+    let res = parse_args(&args).and_then(|opts| {
+        let stdout = io::stdout();
+        let mut w = io::BufWriter::new(stdout.lock());
+        // Flushes what was written before an error.
+        let res = run(&opts, &mut w);
+        w.flush().map_err(|e| e.to_string())?;
+        res
+    });
+    if let Err(e) = res {
+        eprintln!("error: {}", e);
+        std::process::exit(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(args: &str) -> Result<String, String> {
+        let args: Vec<String> = shell_split(args);
+        let opts = parse_args(&args)?;
+        let mut w = vec![];
+        run(&opts, &mut w)?;
+        Ok(String::from_utf8(w).unwrap())
+    }
+
+    /// Splits on spaces outside quotes.
+    fn shell_split(s: &str) -> Vec<String> {
+        let mut v = vec![];
+        let mut cur = String::new();
+        let mut quoted = false;
+        for c in s.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                ' ' if !quoted => {
+                    if !cur.is_empty() {v.push(std::mem::take(&mut cur))}
+                }
+                c => cur.push(c),
+            }
+        }
+        if !cur.is_empty() {v.push(cur)}
+        v
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(output("count \"Pair<Of<Pair>>\" 4").unwrap(), "15\n");
+        assert_eq!(output("count Pair 4 --json").unwrap(), "{\"count\":6}\n");
+        assert_eq!(output("rank Pair 4 \"(1, 2)\"").unwrap(), "2\n");
+        assert_eq!(output("unrank Pair 4 3").unwrap(), "(0, 3)\n");
+        assert_eq!(output("unrank Pair 4 3 --json").unwrap(), "{\"index\":3,\"pos\":[0,3]}\n");
+        assert_eq!(output("enumerate Pair 4 --from 1 --to 6 --step 2").unwrap(),
+            "1: (0, 2)\n3: (0, 3)\n5: (2, 3)\n");
+        assert_eq!(output("enumerate \"Either<Pair, Dimension>\" \"(2, 1)\" --json").unwrap(),
+            "{\"index\":0,\"pos\":{\"fst\":[0,1]}}\n{\"index\":1,\"pos\":{\"snd\":0}}\n");
+    }
+
+    #[test]
+    fn orders_and_views() {
+        assert_eq!(output("count \"Lex<Pair>\" 4").unwrap(), "6\n");
+        assert_eq!(output("enumerate \"Lex<Pair>\" 3").unwrap(), "0: (0, 1)\n1: (0, 2)\n2: (1, 2)\n");
+        assert_eq!(output("enumerate \"Colex<PowerSet>\" 2").unwrap(), "0: []\n1: [0]\n2: [1]\n3: [0, 1]\n");
+        assert_eq!(output("enumerate \"Slice<Pair>\" \"(4, (2, 4))\"").unwrap(), "0: (1, 2)\n1: (0, 3)\n");
+        assert_eq!(output("rank \"Stride<Pair>\" \"(4, 2)\" \"(1, 2)\"").unwrap(), "1\n");
+    }
+
+    #[test]
+    fn big() {
+        let count = output("count \"Permutation<Of<Pair>>\" 100").unwrap();
+        let last = format!("{}", count.trim().parse::<BigUint>().unwrap() - 1usize);
+        let pos = output(&format!("unrank \"Permutation<Of<Pair>>\" 100 {}", last)).unwrap();
+        let index = output(&format!("rank \"Permutation<Of<Pair>>\" 100 \"{}\"", pos.trim())).unwrap();
+        assert_eq!(index.trim(), last);
+    }
+
+    #[test]
+    fn sample() {
+        let a = output("sample Homotopy \"(1, 3)\" -n 20 --seed 7").unwrap();
+        let b = output("sample Homotopy \"(1, 3)\" -n 20 --seed 7").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.lines().count(), 20);
+        assert!(a.lines().all(|line| line.split(':').next().unwrap().parse::<usize>().unwrap() < 6));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(output("count Pair<Of<Foo>> 4").unwrap_err(),
+            "Unknown space `Foo` in space\n  Pair<Of<Foo>>\n          ^");
+        assert_eq!(output("rank Pair 4 \"(2, 1)\"").unwrap_err(),
+            "Position `(2, 1)` is not in `Pair`");
+        assert_eq!(output("unrank Pair 4 6").unwrap_err(),
+            "Index 6 is out of range, the count is 6");
+        assert_eq!(output("frobnicate Pair 4").unwrap_err(), "Unknown command `frobnicate`");
+        assert_eq!(output("count Pair").unwrap_err(), "`count` expects 2 arguments, found 1");
+        assert_eq!(output("count Pair 4 --step 2").unwrap_err(), "`count` does not take `--step`");
+        assert_eq!(output("sample Pair 4").unwrap_err(), "`sample` expects `-n <count>`");
+        assert_eq!(output("enumerate Pair 4 --step 0").unwrap_err(), "`--step` must be positive");
+        assert_eq!(output("rank Permutation 3 \"[0, 0, 5]\"").unwrap_err(),
+            "Position `[0, 0, 5]` is not in `Permutation`");
+        assert_eq!(output("rank Pair 4 \"[0, 1]\"").unwrap_err(), "Expected tuple of 2 positions");
+        assert_eq!(output("count Pair \"[4]\"").unwrap_err(), "Expected number");
+        assert_eq!(output("count \"Lex<Dimension>\" \"[2]\"").unwrap_err(),
+            "`Lex` supports `Pair`, `EqPair`, `NeqPair`, `SqPair`, `Permutation` and `PowerSet` \
+            in space\n  Lex<Dimension>\n      ^");
+        assert_eq!(output("count \"Slice<Pair>\" \"(4, (0, 7))\"").unwrap_err(),
+            "Slice range ends after the space");
+        assert_eq!(output("rank \"Stride<Pair>\" \"(4, 2)\" \"(0, 2)\"").unwrap_err(),
+            "Position `(0, 2)` is not in `Stride<Pair>`");
+    }
+
+    #[test]
+    fn random_below() {
+        let mut random = Random(1);
+        let n: BigUint = "340282366920938463463374607431768211457".parse().unwrap();
+        for _ in 0..100 {
+            assert!(random.below(&n) < n);
+        }
+        let one: BigUint = 1usize.into();
+        assert_eq!(random.below(&one), 0usize.into());
+    }
+}
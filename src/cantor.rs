@@ -0,0 +1,136 @@
+use std::marker::PhantomData;
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Of,
+    pair::{biguint_triangular_root, triangular, triangular_root},
+    unbounded::Unbounded,
+};
+
+/// An unbounded space of all pairs of natural numbers,
+/// using the Cantor pairing function.
+///
+/// Position is `(x, y)`, index is `(x + y) * (x + y + 1) / 2 + y`.
+/// Pairs are enumerated along the diagonals where `x + y` is constant.
+pub struct Cantor<T = Data>(PhantomData<T>);
+
+impl<T> Construct for Cantor<T> {
+    fn new() -> Self { Cantor(PhantomData) }
+}
+
+impl Unbounded<usize> for Cantor<Data> {
+    type Pos = (usize, usize);
+    fn zero(&self) -> (usize, usize) { (0, 0) }
+    fn to_index(&self, &(x, y): &(usize, usize)) -> usize {
+        triangular(x + y) + y
+    }
+    fn to_pos(&self, index: usize, pos: &mut (usize, usize)) {
+        let (w, t) = triangular_root(index);
+        let y = index - t;
+        *pos = (w - y, y);
+    }
+}
+
+impl Unbounded<BigUint> for Cantor<Data> {
+    type Pos = (BigUint, BigUint);
+    fn zero(&self) -> Self::Pos { (0usize.into(), 0usize.into()) }
+    fn to_index(&self, (x, y): &Self::Pos) -> BigUint {
+        let w = x + y;
+        &w * (&w + 1usize) / 2usize + y
+    }
+    fn to_pos(&self, index: BigUint, pos: &mut Self::Pos) {
+        let (w, t) = biguint_triangular_root(&index);
+        let y = index - t;
+        *pos = (w - &y, y);
+    }
+}
+
+impl<N, T> Unbounded<N> for Cantor<Of<T>>
+    where T: Unbounded<N>,
+          Cantor<Data>: Unbounded<N, Pos = (N, N)>,
+{
+    type Pos = (T::Pos, T::Pos);
+    fn zero(&self) -> Self::Pos {
+        let of: T = Construct::new();
+        (of.zero(), of.zero())
+    }
+    fn to_index(&self, (x, y): &Self::Pos) -> N {
+        let of: T = Construct::new();
+        let data: Cantor<Data> = Construct::new();
+        data.to_index(&(of.to_index(x), of.to_index(y)))
+    }
+    fn to_pos(&self, index: N, (x, y): &mut Self::Pos) {
+        let of: T = Construct::new();
+        let data: Cantor<Data> = Construct::new();
+        let mut pair = data.zero();
+        data.to_pos(index, &mut pair);
+        of.to_pos(pair.0, x);
+        of.to_pos(pair.1, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn data() {
+        let x: Cantor = Construct::new();
+        let index = |pos: &(usize, usize)| -> usize { x.to_index(pos) };
+        assert_eq!(index(&(0, 0)), 0);
+        assert_eq!(index(&(1, 0)), 1);
+        assert_eq!(index(&(0, 1)), 2);
+        assert_eq!(index(&(2, 0)), 3);
+        assert_eq!(index(&(1, 1)), 4);
+        assert_eq!(index(&(0, 2)), 5);
+        let mut pos = (0, 0);
+        for i in 0..1000 {
+            x.to_pos(i, &mut pos);
+            assert_eq!(index(&pos), i);
+        }
+        x.to_pos(4usize, &mut pos);
+        assert_eq!(pos, (1, 1));
+    }
+
+    #[test]
+    fn data_big() {
+        let x: Cantor = Construct::new();
+        let index = |pos: &(BigUint, BigUint)| -> BigUint { x.to_index(pos) };
+        let mut pos = (0usize.into(), 0usize.into());
+        for i in 0usize..1000 {
+            x.to_pos(BigUint::from(i), &mut pos);
+            assert_eq!(index(&pos), i.into());
+        }
+        let big = BigUint::from(3usize).pow(100u32);
+        x.to_pos(big.clone(), &mut pos);
+        assert_eq!(index(&pos), big);
+    }
+
+    #[test]
+    fn of() {
+        let x: Cantor<Of<Cantor>> = Construct::new();
+        let index = |pos: &((usize, usize), (usize, usize))| -> usize { x.to_index(pos) };
+        assert_eq!(index(&((0, 0), (0, 0))), 0);
+        assert_eq!(index(&((1, 0), (0, 0))), 1);
+        let mut pos = ((0, 0), (0, 0));
+        for i in 0usize..1000 {
+            x.to_pos(i, &mut pos);
+            assert_eq!(index(&pos), i);
+        }
+    }
+
+    #[test]
+    fn of_big() {
+        let x: Cantor<Of<Cantor>> = Construct::new();
+        let index = |pos: &((BigUint, BigUint), (BigUint, BigUint))| -> BigUint {
+            x.to_index(pos)
+        };
+        let mut pos = Unbounded::<BigUint>::zero(&x);
+        for i in 0usize..1000 {
+            x.to_pos(BigUint::from(i), &mut pos);
+            assert_eq!(index(&pos), i.into());
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Of,
+    Scratch,
+    scratch::set_small,
+    space::Space,
+};
+
+/// Dimension is `(n, k)`, position is a list of `k` increasing numbers below `n`.
+///
+/// Represents all ways to pick `k` different objects out of `n`,
+/// where the order does not matter.
+/// Combinations are ordered colexicographically, such that
+/// all combinations of the first `m` objects come first.
+pub struct Combination<T = Data>(PhantomData<T>);
+
+impl<T> Construct for Combination<T> {
+    fn new() -> Self { Combination(PhantomData) }
+}
+
+/// Computes the binomial coefficient `n` choose `k`.
+pub(crate) fn binomial<N>(n: &N, k: usize) -> N
+    where N: From<usize> + PartialOrd,
+          for<'a> &'a N: Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Sub<&'a N, Output = N>,
+{
+    let mut res: N = 1usize.into();
+    if &N::from(k) > n {return 0usize.into()}
+    for i in 0..k {
+        // Divides exactly, because `res` is `n` choose `i` times `n - i`.
+        res = &(&res * &(n - &i.into())) / &(i + 1).into();
+    }
+    res
+}
+
+/// Computes the binomial coefficient `n` choose `k` into an existing number,
+/// using `t` as a temporary value.
+fn biguint_binomial_with(n: &BigUint, k: usize, res: &mut BigUint, t: &mut BigUint) {
+    use num_traits::{One, Zero};
+
+    if BigUint::from(k) > *n {
+        res.set_zero();
+        return;
+    }
+    res.set_one();
+    for i in 0..k {
+        t.clone_from(n);
+        *t -= i;
+        *res *= &*t;
+        // Divides exactly, because `res` is `n` choose `i` times `n - i`.
+        *res = std::mem::take(res) / u32::try_from(i + 1).expect("Out of range");
+    }
+}
+
+fn to_index<N>(pos: &[N]) -> N
+    where N: From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Sub<&'a N, Output = N>,
+{
+    let mut index: N = 0usize.into();
+    for (t, c) in pos.iter().enumerate() {
+        index = &index + &binomial(c, t + 1);
+    }
+    index
+}
+
+fn to_pos<N>(n: &N, k: usize, mut index: N, pos: &mut Vec<N>)
+    where N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Sub<&'a N, Output = N>,
+{
+    pos.clear();
+    pos.resize(k, 0usize.into());
+    let one: N = 1usize.into();
+    let two: N = 2usize.into();
+    let mut hi = n.clone();
+    for t in (0..k).rev() {
+        // Binary search for the largest `c < hi` with `binomial(c, t + 1) <= index`.
+        let mut lo: N = t.into();
+        while &lo + &one < hi {
+            let mid = &(&lo + &hi) / &two;
+            if binomial(&mid, t + 1) <= index {lo = mid} else {hi = mid}
+        }
+        index = &index - &binomial(&lo, t + 1);
+        pos[t] = lo.clone();
+        hi = lo;
+    }
+}
+
+impl Space<usize> for Combination<Data> {
+    type Dim = (usize, usize);
+    type Pos = Vec<usize>;
+    fn count(&self, &(n, k): &(usize, usize)) -> usize {
+        binomial(&n, k)
+    }
+    fn zero(&self, &(_, k): &(usize, usize)) -> Vec<usize> {
+        (0..k).collect()
+    }
+    fn to_index(&self, _dim: &(usize, usize), pos: &Vec<usize>) -> usize {
+        to_index(pos)
+    }
+    fn to_pos(&self, &(n, k): &(usize, usize), index: usize, pos: &mut Vec<usize>) {
+        to_pos(&n, k, index, pos)
+    }
+}
+
+impl Space<BigUint> for Combination<Data> {
+    type Dim = (BigUint, usize);
+    type Pos = Vec<BigUint>;
+    fn count(&self, (n, k): &Self::Dim) -> BigUint {
+        binomial(n, *k)
+    }
+    fn zero(&self, (_, k): &Self::Dim) -> Self::Pos {
+        (0..*k).map(|i| i.into()).collect()
+    }
+    fn to_index(&self, _dim: &Self::Dim, pos: &Self::Pos) -> BigUint {
+        to_index(pos)
+    }
+    fn to_pos(&self, (n, k): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        to_pos(n, *k, index, pos)
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
+
+        let mut b = scratch.take();
+        let mut t = scratch.take();
+        index.set_zero();
+        for (i, c) in pos.iter().enumerate() {
+            biguint_binomial_with(c, i + 1, &mut b, &mut t);
+            *index += &b;
+        }
+        scratch.put(b);
+        scratch.put(t);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        (n, k): &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        let k = *k;
+        let mut rest = scratch.take();
+        let mut lo = scratch.take();
+        let mut hi = scratch.take();
+        let mut mid = scratch.take();
+        let mut b = scratch.take();
+        let mut t = scratch.take();
+        rest.clone_from(index);
+        hi.clone_from(n);
+        pos.resize(k, 0usize.into());
+        for i in (0..k).rev() {
+            // Binary search for the largest `c < hi` with `binomial(c, i + 1) <= rest`.
+            set_small(&mut lo, i);
+            loop {
+                mid.clone_from(&lo);
+                mid += 1u32;
+                if mid >= hi {break}
+                mid += &hi;
+                mid >>= 1;
+                biguint_binomial_with(&mid, i + 1, &mut b, &mut t);
+                if b <= rest {
+                    std::mem::swap(&mut lo, &mut mid);
+                } else {
+                    std::mem::swap(&mut hi, &mut mid);
+                }
+            }
+            biguint_binomial_with(&lo, i + 1, &mut b, &mut t);
+            rest -= &b;
+            pos[i].clone_from(&lo);
+            std::mem::swap(&mut hi, &mut lo);
+        }
+        for x in [rest, lo, hi, mid, b, t] {scratch.put(x)}
+    }
+}
+
+impl<N, T> Space<N> for Combination<Of<T>>
+    where T: Space<N>,
+          Combination<Data>: Space<N, Dim = (N, usize), Pos = Vec<N>>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Sub<&'a N, Output = N>,
+{
+    type Dim = (T::Dim, usize);
+    type Pos = Vec<T::Pos>;
+    fn count(&self, (dim, k): &Self::Dim) -> N {
+        let of: T = Construct::new();
+        binomial(&of.count(dim), *k)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        let of: T = Construct::new();
+        let count = of.count(dim);
+        let mut pos = Vec::with_capacity(*k);
+        for i in 0..*k {
+            let mut p = of.zero(dim);
+            // There are no combinations when `k` is larger than the count.
+            if N::from(i) < count {of.to_pos(dim, i.into(), &mut p)}
+            pos.push(p);
+        }
+        pos
+    }
+    fn to_index(&self, (dim, _): &Self::Dim, pos: &Self::Pos) -> N {
+        let of: T = Construct::new();
+        let indices: Vec<N> = pos.iter().map(|p| of.to_index(dim, p)).collect();
+        to_index(&indices)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        let of: T = Construct::new();
+        let mut indices = vec![];
+        to_pos(&of.count(dim), *k, index, &mut indices);
+        pos.clear();
+        for i in indices {
+            let mut p = of.zero(dim);
+            of.to_pos(dim, i, &mut p);
+            pos.push(p);
+        }
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim, k): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Combination<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        for (x, i) in pos.iter().zip(&mut inner) {
+            of.to_index_with(scratch, dim, x, i);
+        }
+        data.to_index_with(scratch, &(of.count(dim), *k), &inner, index);
+        scratch.put_many(inner);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim, k): &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Combination<Data> = Construct::new();
+        let mut inner = scratch.take_many(*k);
+        data.to_pos_with(scratch, &(of.count(dim), *k), index, &mut inner);
+        pos.truncate(*k);
+        while pos.len() < *k {pos.push(of.zero(dim))}
+        for (i, x) in inner.iter().zip(pos) {
+            of.to_pos_with(scratch, dim, i, x);
+        }
+        scratch.put_many(inner);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos {
+            of.try_to_index(&dim.0, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, Combination>();
+        is_complete::<usize, Combination<Of<Pair>>>();
+    }
+
+    #[test]
+    fn data() {
+        let x: Combination = Construct::new();
+        let ref dim = (4, 2);
+        assert_eq!(x.count(dim), 6);
+        assert_eq!(x.to_index(dim, &vec![0, 1]), 0);
+        assert_eq!(x.to_index(dim, &vec![0, 2]), 1);
+        assert_eq!(x.to_index(dim, &vec![1, 2]), 2);
+        assert_eq!(x.to_index(dim, &vec![0, 3]), 3);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+        x.to_pos(dim, 5, &mut pos);
+        assert_eq!(pos, vec![2, 3]);
+
+        assert_eq!(x.count(&(5, 0)), 1);
+        assert_eq!(x.count(&(2, 3)), 0);
+        assert_eq!(x.count(&(10, 3)), 120);
+    }
+
+    #[test]
+    fn data_big() {
+        use std::convert::TryInto;
+
+        let x: Combination = Construct::new();
+        let ref dim: (BigUint, usize) = (6usize.into(), 3);
+        assert_eq!(x.count(dim), 20usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+        let ref dim: (BigUint, usize) = (BigUint::from(10usize).pow(30u32), 4);
+        let index = x.count(dim) - 1usize;
+        x.to_pos(dim, index.clone(), &mut pos);
+        assert_eq!(x.to_index(dim, &pos), index);
+    }
+
+    #[test]
+    fn of() {
+        let x: Combination<Of<Pair>> = Construct::new();
+        let ref dim = (4, 3);
+        assert_eq!(x.count(dim), 20);
+        assert_eq!(x.to_index(dim, &vec![(0, 1), (0, 2), (1, 2)]), 0);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+
+    #[test]
+    fn of_big() {
+        use std::convert::TryInto;
+
+        let x: Combination<Of<Pair>> = Construct::new();
+        let ref dim: (BigUint, usize) = (4usize.into(), 3);
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+}
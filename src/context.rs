@@ -17,6 +17,9 @@ use crate::{
     Data,
     Of,
     Pair,
+    Scratch,
+    pair::biguint_pair_index_with,
+    prepared::Prepare,
     space::Space
 };
 
@@ -124,6 +127,104 @@ fn biguint_ind_from_index(v: &[BigUint], index: &BigUint) -> (usize, BigUint) {
     (v.len(), sum)
 }
 
+/// Computes the product of all axes except one into an existing number.
+fn biguint_prod_except_with(v: &[BigUint], ind: usize, prod: &mut BigUint) {
+    use num_traits::One;
+
+    prod.set_one();
+    for (j, d) in v.iter().enumerate() {
+        if j != ind {*prod *= d}
+    }
+}
+
+/// Computes the size of the subspace where an axis changes
+/// into an existing number.
+fn biguint_subspace_count_with(v: &[BigUint], ind: usize, count: &mut BigUint, prod: &mut BigUint) {
+    // The pair count is the index of the pair `(0, dim)`.
+    biguint_pair_index_with(&BigUint::default(), &v[ind], count);
+    biguint_prod_except_with(v, ind, prod);
+    *count *= &*prod;
+}
+
+/// Constants of a dimension, computed once when converting many positions.
+///
+/// These are the tables of a prepared `Context`.
+pub struct ContextTables<N> {
+    /// The index where the subspace of each changing axis starts,
+    /// followed by the total count.
+    offsets: Vec<N>,
+    /// The product of all axes except the changing one.
+    prods: Vec<N>,
+}
+
+impl<N> ContextTables<N>
+    where Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + Ord,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N>,
+{
+    fn new(dim: &[N]) -> ContextTables<N> {
+        let pair: Pair<Data> = Construct::new();
+        let n = dim.len();
+        let mut suffix: Vec<N> = vec![1usize.into(); n + 1];
+        for i in (0..n).rev() {
+            suffix[i] = &suffix[i + 1] * &dim[i];
+        }
+        let mut offsets = Vec::with_capacity(n + 1);
+        let mut prods = Vec::with_capacity(n);
+        let mut prefix: N = 1usize.into();
+        let mut sum: N = 0usize.into();
+        for i in 0..n {
+            let prod = &prefix * &suffix[i + 1];
+            offsets.push(sum.clone());
+            sum = &sum + &(&pair.count(&dim[i]) * &prod);
+            prods.push(prod);
+            prefix = &prefix * &dim[i];
+        }
+        offsets.push(sum);
+        ContextTables {offsets, prods}
+    }
+
+    fn to_index(&self, dim: &[N], (p, ind, b): &(Vec<N>, usize, N)) -> N {
+        let ind = *ind;
+        let pair: Pair<Data> = Construct::new();
+        let ab = if p[ind] < *b {(p[ind].clone(), b.clone())} else {(b.clone(), p[ind].clone())};
+        // Pair doesn't care about dimension.
+        let single = pair.to_index(&0usize.into(), &ab);
+        let mut dim_index: N = 0usize.into();
+        for i in (0..p.len()).rev() {
+            if ind == i { continue; }
+            dim_index = &(&dim_index * &dim[i]) + &p[i];
+        }
+        &(&self.offsets[ind] + &(&single * &self.prods[ind])) + &dim_index
+    }
+
+    fn to_pos(&self, dim: &[N], index: N, (p, ind, b): &mut (Vec<N>, usize, N)) {
+        let pair: Pair<Data> = Construct::new();
+        let ind_val = self.offsets[1..].partition_point(|offset| offset <= &index);
+        let index = &index - &self.offsets[ind_val];
+        let mut prod = self.prods[ind_val].clone();
+        let single = &index / &prod;
+        let mut ab = (0usize.into(), 0usize.into());
+        pair.to_pos(&0usize.into(), single.clone(), &mut ab);
+
+        p.clear();
+        p.resize(dim.len(), 0usize.into());
+        let mut dim_index = &index - &(&single * &prod);
+        for i in (0..dim.len()).rev() {
+            if ind_val == i { continue; }
+            prod = &prod / &dim[i];
+            let p_i = &dim_index / &prod;
+            dim_index = &dim_index - &(&p_i * &prod);
+            p[i] = p_i;
+        }
+        p[ind_val] = ab.0;
+        *b = ab.1;
+        *ind = ind_val;
+    }
+}
 
 impl<T> Construct for Context<T> {
     fn new() -> Context<T> { Context(PhantomData) }
@@ -208,6 +309,40 @@ impl Space<usize> for Context<Data> {
         *b = max;
         *ind = ind_val;
     }
+    fn to_index_batch(&self, dim: &Vec<usize>, pos: &[Self::Pos], index: &mut [usize]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let tables = ContextTables::new(dim);
+        for (p, i) in pos.iter().zip(index) {
+            *i = tables.to_index(dim, p);
+        }
+    }
+    fn to_pos_batch(&self, dim: &Vec<usize>, index: &[usize], pos: &mut [Self::Pos]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let tables = ContextTables::new(dim);
+        for (&i, p) in index.iter().zip(pos) {
+            tables.to_pos(dim, i, p);
+        }
+    }
+}
+
+impl Prepare<usize> for Context<Data> {
+    type Tables = ContextTables<usize>;
+    fn tables(&self, dim: &Vec<usize>) -> ContextTables<usize> {ContextTables::new(dim)}
+    fn prepared_count(&self, dim: &Vec<usize>, tables: &ContextTables<usize>) -> usize {
+        tables.offsets[dim.len()]
+    }
+    fn prepared_to_index(&self, dim: &Vec<usize>, tables: &ContextTables<usize>, pos: &Self::Pos) -> usize {
+        tables.to_index(dim, pos)
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Vec<usize>,
+        tables: &ContextTables<usize>,
+        index: usize,
+        pos: &mut Self::Pos,
+    ) {
+        tables.to_pos(dim, index, pos)
+    }
 }
 
 impl Space<BigUint> for Context<Data> {
@@ -291,11 +426,128 @@ impl Space<BigUint> for Context<Data> {
         *b = max;
         *ind = ind_val;
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        (p, ind, b): &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
+
+        let ind = *ind;
+        let mut t = scratch.take();
+        let mut prod = scratch.take();
+        index.set_zero();
+        for i in (0..p.len()).rev() {
+            if ind == i { continue; }
+            *index *= &dim[i];
+            *index += &p[i];
+        }
+        if p[ind] < *b {
+            biguint_pair_index_with(&p[ind], b, &mut t);
+        } else {
+            biguint_pair_index_with(b, &p[ind], &mut t);
+        }
+        biguint_prod_except_with(dim, ind, &mut prod);
+        t *= &prod;
+        *index += &t;
+        for i in 0..ind {
+            biguint_subspace_count_with(dim, i, &mut t, &mut prod);
+            *index += &t;
+        }
+        scratch.put(t);
+        scratch.put(prod);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        (p, ind, b): &mut Self::Pos,
+    ) {
+        let pair_space: Pair<Data> = Construct::new();
+        let mut rest = scratch.take();
+        let mut t = scratch.take();
+        let mut prod = scratch.take();
+        rest.clone_from(index);
+        // Subtract the subspaces before the changing axis.
+        let mut ind_val = 0;
+        loop {
+            biguint_subspace_count_with(dim, ind_val, &mut t, &mut prod);
+            if rest < t { break; }
+            rest -= &t;
+            ind_val += 1;
+        }
+        // The rest equals: single * prod + dim_index
+        t.clone_from(&rest);
+        t /= &prod;
+        rest %= &prod;
+        p.resize(dim.len(), 0usize.into());
+        let mut pair = (std::mem::take(&mut p[ind_val]), std::mem::take(b));
+        // Pair doesn't care about dimension.
+        pair_space.to_pos_with(scratch, &BigUint::default(), &t, &mut pair);
+        p[ind_val] = pair.0;
+        *b = pair.1;
+
+        // Resolve other dimension components.
+        for i in (0..p.len()).rev() {
+            if ind_val == i { continue; }
+            prod /= &dim[i];
+            p[i].clone_from(&rest);
+            p[i] /= &prod;
+            rest %= &prod;
+        }
+        *ind = ind_val;
+        scratch.put(rest);
+        scratch.put(t);
+        scratch.put(prod);
+    }
+    fn to_index_batch(&self, dim: &Vec<BigUint>, pos: &[Self::Pos], index: &mut [BigUint]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let tables = ContextTables::new(dim);
+        for (p, i) in pos.iter().zip(index) {
+            *i = tables.to_index(dim, p);
+        }
+    }
+    fn to_pos_batch(&self, dim: &Vec<BigUint>, index: &[BigUint], pos: &mut [Self::Pos]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let tables = ContextTables::new(dim);
+        for (i, p) in index.iter().zip(pos) {
+            tables.to_pos(dim, i.clone(), p);
+        }
+    }
+}
+
+impl Prepare<BigUint> for Context<Data> {
+    type Tables = ContextTables<BigUint>;
+    fn tables(&self, dim: &Vec<BigUint>) -> ContextTables<BigUint> {ContextTables::new(dim)}
+    fn prepared_count(&self, dim: &Vec<BigUint>, tables: &ContextTables<BigUint>) -> BigUint {
+        tables.offsets[dim.len()].clone()
+    }
+    fn prepared_to_index(
+        &self,
+        dim: &Vec<BigUint>,
+        tables: &ContextTables<BigUint>,
+        pos: &Self::Pos,
+    ) -> BigUint {
+        tables.to_index(dim, pos)
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Vec<BigUint>,
+        tables: &ContextTables<BigUint>,
+        index: BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        tables.to_pos(dim, index, pos)
+    }
 }
 
 impl<N, T> Space<N> for Context<Of<T>>
     where T: Space<N>,
           Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          Context<Data>: Space<N, Dim = Vec<N>, Pos = (Vec<N>, usize, N)>,
           for<'a> N: Clone +
                      From<usize> +
                      Ord +
@@ -447,6 +699,92 @@ impl<N, T> Space<N> for Context<Of<T>>
         of.to_pos(&dim[ind_val], max, b);
         *ind = ind_val;
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        (p, ind, b): &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Context<Data> = Construct::new();
+        let counts: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let mut inner = (scratch.take_many(p.len()), *ind, scratch.take());
+        for ((d, x), i) in dim.iter().zip(p).zip(&mut inner.0) {
+            of.to_index_with(scratch, d, x, i);
+        }
+        of.to_index_with(scratch, &dim[*ind], b, &mut inner.2);
+        data.to_index_with(scratch, &counts, &inner, index);
+        scratch.put_many(inner.0);
+        scratch.put(inner.2);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        (p, ind, b): &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Context<Data> = Construct::new();
+        let counts: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let mut inner = (scratch.take_many(dim.len()), 0, scratch.take());
+        data.to_pos_with(scratch, &counts, index, &mut inner);
+        p.truncate(dim.len());
+        for d in &dim[p.len()..] {p.push(of.zero(d))}
+        for ((d, i), x) in dim.iter().zip(&inner.0).zip(p) {
+            of.to_pos_with(scratch, d, i, x);
+        }
+        *ind = inner.1;
+        of.to_pos_with(scratch, &dim[*ind], &inner.2, b);
+        scratch.put_many(inner.0);
+        scratch.put(inner.2);
+    }
+    fn to_index_batch(&self, dim: &Self::Dim, pos: &[Self::Pos], index: &mut [N]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let of: T = Construct::new();
+        let counts: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let tables = ContextTables::new(&counts);
+        let mut inner = (Vec::with_capacity(dim.len()), 0, 0usize.into());
+        for ((p, ind, b), i) in pos.iter().zip(index) {
+            inner.0.clear();
+            inner.0.extend(dim.iter().zip(p).map(|(d, x)| of.to_index(d, x)));
+            inner.1 = *ind;
+            inner.2 = of.to_index(&dim[*ind], b);
+            *i = tables.to_index(&counts, &inner);
+        }
+    }
+    fn to_pos_batch(&self, dim: &Self::Dim, index: &[N], pos: &mut [Self::Pos]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let of: T = Construct::new();
+        let counts: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let tables = ContextTables::new(&counts);
+        let mut inner = (Vec::with_capacity(dim.len()), 0, 0usize.into());
+        for (i, (p, ind, b)) in index.iter().zip(pos) {
+            tables.to_pos(&counts, i.clone(), &mut inner);
+            p.clear();
+            for (d, x) in dim.iter().zip(inner.0.drain(..)) {
+                let mut y = of.zero(d);
+                of.to_pos(d, x, &mut y);
+                p.push(y);
+            }
+            *ind = inner.1;
+            of.to_pos(&dim[*ind], inner.2.clone(), b);
+        }
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let (p, ind, b) = pos;
+        for (d, x) in dim.iter().zip(p) {
+            of.try_to_index(d, x)?;
+        }
+        of.try_to_index(&dim[*ind], b)?;
+        Some(self.to_index(dim, pos))
+    }
 }
 
 #[cfg(test)]
@@ -562,4 +900,48 @@ mod tests {
         x.to_pos(dim, 16usize.into(), &mut pos);
         assert_eq!(pos, conv((vec![(0, 2), (0, 2)], 1, (1, 2))));
     }
+
+    #[test]
+    fn batch() {
+        let x: Context = Construct::new();
+        let ref dim = vec![3, 4, 2];
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = x.zero(dim);
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        let ref dim: Vec<BigUint> = dim.iter().map(|&d| d.into()).collect();
+        let indices: Vec<BigUint> = (0..count).map(|i| i.into()).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut res = vec![0usize.into(); count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+    }
+
+    #[test]
+    fn batch_of() {
+        let x: Context<Of<Pair>> = Construct::new();
+        let ref dim = vec![3, 4];
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = x.zero(dim);
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+    }
 }
@@ -5,6 +5,7 @@ use crate::{
     Construct,
     Data,
     Of,
+    Scratch,
     space::Space,
 };
 
@@ -37,6 +38,24 @@ impl Space<BigUint> for Dimension<Data> {
     fn to_pos(&self, _dim: &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
         *pos = index;
     }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        index.clone_from(pos);
+    }
+    fn to_pos_with(
+        &self,
+        _scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        pos.clone_from(index);
+    }
 }
 
 impl<N, T: Space<N>> Space<N> for Dimension<Of<T>> {
@@ -58,6 +77,34 @@ impl<N, T: Space<N>> Space<N> for Dimension<Of<T>> {
         let of: T = Construct::new();
         of.to_pos(dim, index, pos);
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        of.to_index_with(scratch, dim, pos, index);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        of.to_pos_with(scratch, dim, index, pos);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        of.try_to_index(dim, pos)
+    }
 }
 
 #[cfg(test)]
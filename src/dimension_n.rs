@@ -13,6 +13,8 @@ use crate::{
     Construct,
     Data,
     Of,
+    Scratch,
+    prepared::Prepare,
     space::Space,
 };
 
@@ -59,6 +61,41 @@ impl Space<usize> for DimensionN<Data> {
     }
 }
 
+impl Prepare<usize> for DimensionN<Data> {
+    /// Products of the first axes, followed by the total count.
+    type Tables = Vec<usize>;
+    fn tables(&self, dim: &Vec<usize>) -> Vec<usize> {
+        let mut prods = Vec::with_capacity(dim.len() + 1);
+        let mut prod = 1;
+        prods.push(prod);
+        for &d in dim {
+            prod *= d;
+            prods.push(prod);
+        }
+        prods
+    }
+    fn prepared_count(&self, dim: &Vec<usize>, tables: &Vec<usize>) -> usize {
+        tables[dim.len()]
+    }
+    fn prepared_to_index(&self, _dim: &Vec<usize>, tables: &Vec<usize>, pos: &Vec<usize>) -> usize {
+        pos.iter().zip(tables).map(|(&p, &prod)| p * prod).sum()
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Vec<usize>,
+        tables: &Vec<usize>,
+        mut index: usize,
+        pos: &mut Vec<usize>,
+    ) {
+        pos.clear();
+        pos.resize(dim.len(), 0);
+        for i in (0..dim.len()).rev() {
+            pos[i] = index / tables[i];
+            index %= tables[i];
+        }
+    }
+}
+
 impl Space<BigUint> for DimensionN<Data> {
     type Dim = Vec<BigUint>;
     type Pos = Vec<BigUint>;
@@ -93,6 +130,82 @@ impl Space<BigUint> for DimensionN<Data> {
             *pos.get_mut(i).unwrap() = p_i;
         }
     }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
+
+        index.set_zero();
+        for i in (0..dim.len()).rev() {
+            *index *= &dim[i];
+            *index += &pos[i];
+        }
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        pos.resize(dim.len(), 0usize.into());
+        let mut rest = scratch.take();
+        rest.clone_from(index);
+        for (p, d) in pos.iter_mut().zip(dim) {
+            p.clone_from(&rest);
+            *p %= d;
+            rest /= d;
+        }
+        scratch.put(rest);
+    }
+}
+
+impl Prepare<BigUint> for DimensionN<Data> {
+    /// Products of the first axes, followed by the total count.
+    type Tables = Vec<BigUint>;
+    fn tables(&self, dim: &Vec<BigUint>) -> Vec<BigUint> {
+        let mut prods: Vec<BigUint> = Vec::with_capacity(dim.len() + 1);
+        prods.push(1usize.into());
+        for (i, d) in dim.iter().enumerate() {
+            let prod = &prods[i] * d;
+            prods.push(prod);
+        }
+        prods
+    }
+    fn prepared_count(&self, dim: &Vec<BigUint>, tables: &Vec<BigUint>) -> BigUint {
+        tables[dim.len()].clone()
+    }
+    fn prepared_to_index(
+        &self,
+        _dim: &Vec<BigUint>,
+        tables: &Vec<BigUint>,
+        pos: &Vec<BigUint>,
+    ) -> BigUint {
+        let mut index: BigUint = 0usize.into();
+        for (p, prod) in pos.iter().zip(tables) {
+            index += p * prod;
+        }
+        index
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Vec<BigUint>,
+        tables: &Vec<BigUint>,
+        mut index: BigUint,
+        pos: &mut Vec<BigUint>,
+    ) {
+        pos.clear();
+        pos.resize(dim.len(), 0usize.into());
+        for i in (0..dim.len()).rev() {
+            let p_i = &index / &tables[i];
+            index -= &p_i * &tables[i];
+            pos[i] = p_i;
+        }
+    }
 }
 
 impl<N, T> Space<N> for DimensionN<Of<T>>
@@ -105,6 +218,7 @@ impl<N, T> Space<N> for DimensionN<Of<T>>
              DivAssign<N>,
           for<'a> &'a N: Div<&'a N, Output = N> + Mul<&'a N, Output = N>,
           T: Space<N>,
+          DimensionN<Data>: Space<N, Dim = Vec<N>, Pos = Vec<N>>,
 {
     type Dim = Vec<T::Dim>;
     type Pos = Vec<T::Pos>;
@@ -153,6 +267,53 @@ impl<N, T> Space<N> for DimensionN<Of<T>>
             of.to_pos(&dim[i], p_i, p);
         }
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: DimensionN<Data> = Construct::new();
+        let counts: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let mut inner = scratch.take_many(dim.len());
+        for ((d, x), i) in dim.iter().zip(pos).zip(&mut inner) {
+            of.to_index_with(scratch, d, x, i);
+        }
+        data.to_index_with(scratch, &counts, &inner, index);
+        scratch.put_many(inner);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: DimensionN<Data> = Construct::new();
+        let counts: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let mut inner = scratch.take_many(dim.len());
+        data.to_pos_with(scratch, &counts, index, &mut inner);
+        pos.truncate(dim.len());
+        for d in &dim[pos.len()..] {pos.push(of.zero(d))}
+        for ((d, i), x) in dim.iter().zip(&inner).zip(pos) {
+            of.to_pos_with(scratch, d, i, x);
+        }
+        scratch.put_many(inner);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for (d, x) in dim.iter().zip(pos) {
+            of.try_to_index(d, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
 }
 
 #[cfg(test)]
@@ -19,6 +19,7 @@ use crate::{
     Of,
     NeqPair,
     Pair,
+    Scratch,
     space::Space,
 };
 
@@ -78,6 +79,27 @@ impl Space<usize> for DirectedContext<Data> {
             pos.2 = tmp;
         }
     }
+    fn to_index_batch(&self, dim: &Vec<usize>, pos: &[Self::Pos], index: &mut [usize]) {
+        use crate::Context;
+
+        let context: Context<Data> = Construct::new();
+        context.to_index_batch(dim, pos, index);
+        for ((p, ind, b), i) in pos.iter().zip(index) {
+            *i = 2 * *i + if p[*ind] > *b {1} else {0};
+        }
+    }
+    fn to_pos_batch(&self, dim: &Vec<usize>, index: &[usize], pos: &mut [Self::Pos]) {
+        use crate::Context;
+
+        let context: Context<Data> = Construct::new();
+        let halves: Vec<usize> = index.iter().map(|i| i / 2).collect();
+        context.to_pos_batch(dim, &halves, pos);
+        for (i, (p, ind, b)) in index.iter().zip(pos) {
+            if i % 2 == 1 {
+                std::mem::swap(&mut p[*ind], b);
+            }
+        }
+    }
 }
 
 impl Space<BigUint> for DirectedContext<Data> {
@@ -127,6 +149,66 @@ impl Space<BigUint> for DirectedContext<Data> {
             std::mem::swap(&mut pos.0[pos.1], &mut pos.2);
         }
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use crate::Context;
+
+        let context: Context<Data> = Construct::new();
+        context.to_index_with(scratch, dim, pos, index);
+        let (p, ind, b) = pos;
+        *index <<= 1;
+        if &p[*ind] > b {
+            *index += 1u32;
+        }
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        use crate::Context;
+
+        let context: Context<Data> = Construct::new();
+        let mut half = scratch.take();
+        half.clone_from(index);
+        half >>= 1;
+        context.to_pos_with(scratch, dim, &half, pos);
+        scratch.put(half);
+        if index.bit(0) {
+            std::mem::swap(&mut pos.0[pos.1], &mut pos.2);
+        }
+    }
+    fn to_index_batch(&self, dim: &Self::Dim, pos: &[Self::Pos], index: &mut [BigUint]) {
+        use crate::Context;
+
+        let context: Context<Data> = Construct::new();
+        context.to_index_batch(dim, pos, index);
+        for ((p, ind, b), i) in pos.iter().zip(index) {
+            *i *= 2usize;
+            if &p[*ind] > b {
+                *i += 1usize;
+            }
+        }
+    }
+    fn to_pos_batch(&self, dim: &Self::Dim, index: &[BigUint], pos: &mut [Self::Pos]) {
+        use crate::Context;
+
+        let context: Context<Data> = Construct::new();
+        let halves: Vec<BigUint> = index.iter().map(|i| i / 2usize).collect();
+        context.to_pos_batch(dim, &halves, pos);
+        for (i, (p, ind, b)) in index.iter().zip(pos) {
+            if i % 2usize != 0usize.into() {
+                std::mem::swap(&mut p[*ind], b);
+            }
+        }
+    }
 }
 
 impl<N, T> Space<N> for DirectedContext<Of<T>>
@@ -134,6 +216,7 @@ impl<N, T> Space<N> for DirectedContext<Of<T>>
           T::Pos: Clone,
           NeqPair<Data>: Space<N, Dim = N>,
           Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          crate::Context<Data>: Space<N, Dim = Vec<N>, Pos = (Vec<N>, usize, N)>,
           for<'a> N: Clone +
                      From<usize> +
                      Ord +
@@ -207,6 +290,86 @@ impl<N, T> Space<N> for DirectedContext<Of<T>>
             std::mem::swap(&mut pos.0[pos.1], &mut pos.2);
         }
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        use crate::Context;
+
+        let of: T = Construct::new();
+        let context: Context<Of<T>> = Construct::new();
+        context.to_index_with(scratch, dim, pos, index);
+        let (p, ind, b) = pos;
+        let (mut x, mut y) = (scratch.take(), scratch.take());
+        of.to_index_with(scratch, &dim[*ind], &p[*ind], &mut x);
+        of.to_index_with(scratch, &dim[*ind], b, &mut y);
+        let twice = std::mem::replace(index, 0usize.into()) * 2usize;
+        *index = if x > y {twice + 1usize} else {twice};
+        scratch.put(x);
+        scratch.put(y);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        use crate::Context;
+
+        let context: Context<Of<T>> = Construct::new();
+        let mut half = scratch.take();
+        half.clone_from(index);
+        half = half / 2usize;
+        context.to_pos_with(scratch, dim, &half, pos);
+        scratch.put(half);
+        if index % 2usize != 0usize.into() {
+            std::mem::swap(&mut pos.0[pos.1], &mut pos.2);
+        }
+    }
+    fn to_index_batch(&self, dim: &Self::Dim, pos: &[Self::Pos], index: &mut [N]) {
+        use crate::Context;
+
+        let of: T = Construct::new();
+        let context: Context<Of<T>> = Construct::new();
+        Space::to_index_batch(&context, dim, pos, index);
+        for ((p, ind, b), i) in pos.iter().zip(index) {
+            let x = std::mem::replace(i, 0usize.into()) * 2usize;
+            *i = if of.to_index(&dim[*ind], &p[*ind]) > of.to_index(&dim[*ind], b) {
+                x + 1usize
+            } else {
+                x
+            };
+        }
+    }
+    fn to_pos_batch(&self, dim: &Self::Dim, index: &[N], pos: &mut [Self::Pos]) {
+        use crate::Context;
+
+        let context: Context<Of<T>> = Construct::new();
+        let halves: Vec<N> = index.iter().map(|i| i.clone() / 2usize).collect();
+        Space::to_pos_batch(&context, dim, &halves, pos);
+        for (i, (p, ind, b)) in index.iter().zip(pos) {
+            if i % 2usize != 0usize.into() {
+                std::mem::swap(&mut p[*ind], b);
+            }
+        }
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let (p, ind, b) = pos;
+        for (d, x) in dim.iter().zip(p) {
+            of.try_to_index(d, x)?;
+        }
+        of.try_to_index(&dim[*ind], b)?;
+        Some(self.to_index(dim, pos))
+    }
 }
 
 #[cfg(test)]
@@ -263,4 +426,45 @@ mod tests {
         }
         // assert!(false);
     }
+
+    #[test]
+    fn batch() {
+        let x: DirectedContext = Construct::new();
+        let ref dim = vec![3, 2, 2];
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = x.zero(dim);
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        let ref dim = conv(vec![3, 2, 2]);
+        let indices: Vec<BigUint> = (0..count).map(|i| i.into()).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut res = vec![0usize.into(); count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        let x: DirectedContext<Of<Pair>> = Construct::new();
+        let ref dim = vec![3, 4];
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = x.zero(dim);
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+    }
 }
@@ -0,0 +1,360 @@
+use std::marker::PhantomData;
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Dimension,
+    HPoint,
+    NeqPair,
+    Of,
+    SqPair,
+    space::Space,
+};
+
+/// A discrete space that models directed homotopy paths
+/// at a specified homotopy level.
+///
+/// This is the same as `Homotopy`, except that the path from `a` to `b`
+/// is different from the path from `b` to `a`.
+///
+/// Dimension is `(<homotopy level>, <number of pieces>)`,
+/// position is `HPoint`.
+///
+/// Uses `SqPair` internally to include loop spaces.
+pub struct DirectedHomotopy<T = Data>(PhantomData<T>);
+
+/// A discrete space that models directed homotopy paths without loops
+/// at a specified homotopy level.
+///
+/// This is the same as `DirectedHomotopy`,
+/// except that no path goes from a point to itself.
+///
+/// Dimension is `(<homotopy level>, <number of pieces>)`,
+/// position is `HPoint`.
+///
+/// Uses `NeqPair` internally to exclude loop spaces.
+pub struct DirectedLoopFreeHomotopy<T = Data>(PhantomData<T>);
+
+impl<T> Construct for DirectedHomotopy<T> {
+    fn new() -> Self {DirectedHomotopy(PhantomData)}
+}
+
+impl<T> Construct for DirectedLoopFreeHomotopy<T> {
+    fn new() -> Self {DirectedLoopFreeHomotopy(PhantomData)}
+}
+
+/// Counts the paths at a homotopy level, using `P` to connect pieces of `T`.
+fn count<N, P, T>(level: usize, dim: &T::Dim) -> N
+    where T: Space<N>,
+          P: Space<N, Dim = N>,
+{
+    let of: T = Construct::new();
+    let s: P = Construct::new();
+    let mut count = of.count(dim);
+    for _ in 0..level {
+        count = s.count(&count);
+    }
+    count
+}
+
+fn zero<N, T>(level: usize, dim: &T::Dim) -> HPoint<T::Pos>
+    where T: Space<N>
+{
+    use HPoint::*;
+
+    match level {
+        0 => {
+            let of: T = Construct::new();
+            Point(of.zero(dim))
+        }
+        _ => Path(Box::new((zero::<N, T>(level - 1, dim), zero::<N, T>(level - 1, dim)))),
+    }
+}
+
+fn to_index<N, P, T>(level: usize, dim: &T::Dim, pos: &HPoint<T::Pos>) -> N
+    where T: Space<N>,
+          P: Space<N, Dim = N, Pos = (N, N)>,
+{
+    use HPoint::*;
+
+    match pos {
+        Point(x) => {
+            let of: T = Construct::new();
+            of.to_index(dim, x)
+        }
+        Path(ab) => {
+            let count = count::<N, P, T>(level - 1, dim);
+            let a = to_index::<N, P, T>(level - 1, dim, &ab.0);
+            let b = to_index::<N, P, T>(level - 1, dim, &ab.1);
+            let s: P = Construct::new();
+            s.to_index(&count, &(a, b))
+        }
+    }
+}
+
+fn to_pos<N, P, T>(level: usize, dim: &T::Dim, index: N, pos: &mut HPoint<T::Pos>)
+    where T: Space<N>,
+          P: Space<N, Dim = N, Pos = (N, N)>,
+{
+    use HPoint::*;
+
+    let of: T = Construct::new();
+    match level {
+        0 => {
+            let mut of_pos = of.zero(dim);
+            of.to_pos(dim, index, &mut of_pos);
+            *pos = Point(of_pos);
+        }
+        _ => {
+            let count = count::<N, P, T>(level - 1, dim);
+            let s: P = Construct::new();
+            let mut ab = s.zero(&count);
+            s.to_pos(&count, index, &mut ab);
+            let mut a = Point(of.zero(dim));
+            let mut b = Point(of.zero(dim));
+            to_pos::<N, P, T>(level - 1, dim, ab.0, &mut a);
+            to_pos::<N, P, T>(level - 1, dim, ab.1, &mut b);
+            *pos = Path(Box::new((a, b)));
+        }
+    }
+}
+
+impl Space<usize> for DirectedHomotopy<Data> {
+    type Dim = (usize, usize);
+    type Pos = HPoint;
+    fn count(&self, &(level, n): &(usize, usize)) -> usize {
+        count::<usize, SqPair, Dimension>(level, &n)
+    }
+    fn zero(&self, &(level, n): &(usize, usize)) -> HPoint {
+        zero::<usize, Dimension>(level, &n)
+    }
+    fn to_index(&self, &(level, n): &(usize, usize), pos: &HPoint) -> usize {
+        to_index::<usize, SqPair, Dimension>(level, &n, pos)
+    }
+    fn to_pos(&self, &(level, n): &(usize, usize), index: usize, pos: &mut HPoint) {
+        to_pos::<usize, SqPair, Dimension>(level, &n, index, pos)
+    }
+}
+
+impl Space<BigUint> for DirectedHomotopy<Data> {
+    type Dim = (usize, BigUint);
+    type Pos = HPoint<BigUint>;
+    fn count(&self, (level, n): &Self::Dim) -> BigUint {
+        count::<BigUint, SqPair, Dimension>(*level, n)
+    }
+    fn zero(&self, (level, n): &Self::Dim) -> Self::Pos {
+        zero::<BigUint, Dimension>(*level, n)
+    }
+    fn to_index(&self, (level, n): &Self::Dim, pos: &Self::Pos) -> BigUint {
+        to_index::<BigUint, SqPair, Dimension>(*level, n, pos)
+    }
+    fn to_pos(&self, (level, n): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        to_pos::<BigUint, SqPair, Dimension>(*level, n, index, pos)
+    }
+}
+
+impl<N, T> Space<N> for DirectedHomotopy<Of<T>>
+    where T: Space<N>,
+          SqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+{
+    type Dim = (usize, T::Dim);
+    type Pos = HPoint<T::Pos>;
+    fn count(&self, (level, dim): &Self::Dim) -> N {
+        count::<N, SqPair, T>(*level, dim)
+    }
+    fn zero(&self, (level, dim): &Self::Dim) -> Self::Pos {
+        zero::<N, T>(*level, dim)
+    }
+    fn to_index(&self, (level, dim): &Self::Dim, pos: &Self::Pos) -> N {
+        to_index::<N, SqPair, T>(*level, dim, pos)
+    }
+    fn to_pos(&self, (level, dim): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        to_pos::<N, SqPair, T>(*level, dim, index, pos)
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos.leaves() {
+            of.try_to_index(&dim.1, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+impl Space<usize> for DirectedLoopFreeHomotopy<Data> {
+    type Dim = (usize, usize);
+    type Pos = HPoint;
+    fn count(&self, &(level, n): &(usize, usize)) -> usize {
+        count::<usize, NeqPair, Dimension>(level, &n)
+    }
+    fn zero(&self, &(level, n): &(usize, usize)) -> HPoint {
+        zero::<usize, Dimension>(level, &n)
+    }
+    fn to_index(&self, &(level, n): &(usize, usize), pos: &HPoint) -> usize {
+        to_index::<usize, NeqPair, Dimension>(level, &n, pos)
+    }
+    fn to_pos(&self, &(level, n): &(usize, usize), index: usize, pos: &mut HPoint) {
+        to_pos::<usize, NeqPair, Dimension>(level, &n, index, pos)
+    }
+}
+
+impl Space<BigUint> for DirectedLoopFreeHomotopy<Data> {
+    type Dim = (usize, BigUint);
+    type Pos = HPoint<BigUint>;
+    fn count(&self, (level, n): &Self::Dim) -> BigUint {
+        count::<BigUint, NeqPair, Dimension>(*level, n)
+    }
+    fn zero(&self, (level, n): &Self::Dim) -> Self::Pos {
+        zero::<BigUint, Dimension>(*level, n)
+    }
+    fn to_index(&self, (level, n): &Self::Dim, pos: &Self::Pos) -> BigUint {
+        to_index::<BigUint, NeqPair, Dimension>(*level, n, pos)
+    }
+    fn to_pos(&self, (level, n): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        to_pos::<BigUint, NeqPair, Dimension>(*level, n, index, pos)
+    }
+}
+
+impl<N, T> Space<N> for DirectedLoopFreeHomotopy<Of<T>>
+    where T: Space<N>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+{
+    type Dim = (usize, T::Dim);
+    type Pos = HPoint<T::Pos>;
+    fn count(&self, (level, dim): &Self::Dim) -> N {
+        count::<N, NeqPair, T>(*level, dim)
+    }
+    fn zero(&self, (level, dim): &Self::Dim) -> Self::Pos {
+        zero::<N, T>(*level, dim)
+    }
+    fn to_index(&self, (level, dim): &Self::Dim, pos: &Self::Pos) -> N {
+        to_index::<N, NeqPair, T>(*level, dim, pos)
+    }
+    fn to_pos(&self, (level, dim): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        to_pos::<N, NeqPair, T>(*level, dim, index, pos)
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos.leaves() {
+            of.try_to_index(&dim.1, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use HPoint::*;
+
+    fn path<T>(a: HPoint<T>, b: HPoint<T>) -> HPoint<T> {
+        Path(Box::new((a, b)))
+    }
+
+    #[test]
+    fn features() {
+        is_complete::<usize, DirectedHomotopy>();
+        is_complete::<usize, DirectedHomotopy<Of<Pair>>>();
+        is_complete::<usize, DirectedLoopFreeHomotopy>();
+        is_complete::<usize, DirectedLoopFreeHomotopy<Of<Pair>>>();
+    }
+
+    #[test]
+    fn data() {
+        let x: DirectedHomotopy = Construct::new();
+        assert_eq!(x.count(&(0, 3)), 3);
+        assert_eq!(x.count(&(1, 3)), 9);
+        assert_eq!(x.count(&(2, 3)), 81);
+        let ref dim = (1, 2);
+        assert_eq!(x.to_index(dim, &path(Point(0), Point(0))), 0);
+        assert_eq!(x.to_index(dim, &path(Point(1), Point(0))), 1);
+        assert_eq!(x.to_index(dim, &path(Point(0), Point(1))), 2);
+        assert_eq!(x.to_index(dim, &path(Point(1), Point(1))), 3);
+        let ref dim = (2, 2);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(pos.level(), 2);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+        x.to_pos(dim, 1, &mut pos);
+        assert_eq!(pos, path(path(Point(1), Point(0)), path(Point(0), Point(0))));
+    }
+
+    #[test]
+    fn data_big() {
+        use std::convert::TryInto;
+
+        let x: DirectedHomotopy = Construct::new();
+        let ref dim: (usize, BigUint) = (2, 3usize.into());
+        assert_eq!(x.count(dim), 81usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+
+    #[test]
+    fn of() {
+        let x: DirectedHomotopy<Of<Pair>> = Construct::new();
+        let ref dim = (1, 3);
+        assert_eq!(x.count(dim), 9);
+        assert_eq!(x.to_index(dim, &path(Point((0, 2)), Point((0, 1)))), 1);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+
+    #[test]
+    fn of_big() {
+        use std::convert::TryInto;
+
+        let x: DirectedHomotopy<Of<Pair>> = Construct::new();
+        let ref dim: (usize, BigUint) = (2, 3usize.into());
+        assert_eq!(x.count(dim), 81usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+
+    #[test]
+    fn loop_free() {
+        let x: DirectedLoopFreeHomotopy = Construct::new();
+        assert_eq!(x.count(&(1, 3)), 6);
+        assert_eq!(x.count(&(2, 3)), 30);
+        let ref dim = (1, 3);
+        assert_eq!(x.to_index(dim, &path(Point(0), Point(1))), 0);
+        assert_eq!(x.to_index(dim, &path(Point(1), Point(0))), 1);
+        let ref dim = (2, 3);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            if let Path(ab) = &pos {
+                assert_ne!(ab.0, ab.1);
+            }
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+
+    #[test]
+    fn loop_free_big() {
+        use std::convert::TryInto;
+
+        let x: DirectedLoopFreeHomotopy<Of<Pair>> = Construct::new();
+        let ref dim: (usize, BigUint) = (2, 3usize.into());
+        assert_eq!(x.count(dim), 30usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+}
@@ -0,0 +1,1776 @@
+//! Spaces described at runtime.
+//!
+//! The shape of a typed space, such as `PowerSet<Of<Pair>>`, is fixed at compile time.
+//! When the shape is read from a file or picked by a user,
+//! it can be described by a `DynSpace` tree instead:
+//!
+//! ~~~
+//! use discrete::*;
+//!
+//! let x = DynSpace::PowerSet(Some(Box::new(DynSpace::Pair(None))));
+//! let dim = DynDim::from(4);
+//! assert_eq!(x.count(&dim), 64usize.into());
+//! ~~~
+//!
+//! Dimensions and positions have the same structure as those of the typed space,
+//! with numbers stored as `BigUint`, lists as `List` and tuples as `Tuple`.
+//! The walk through the tree uses the typed spaces,
+//! so a `DynSpace` gives the same indices as the space it describes.
+//!
+//! Methods panic if the dimension or position does not have the shape of the space.
+//! The `try_` methods return an error message instead.
+//!
+//! Spaces are parsed from and written as type expressions,
+//! and dimensions and positions as literals:
+//!
+//! ~~~
+//! use discrete::*;
+//!
+//! # fn main() -> Result<(), ParseDynError> {
+//! let x: DynSpace = "(DimensionN, Either<Context, DimensionN>)".parse()?;
+//! let dim: DynDim = "([2, 3], ([2, 2], [4]))".parse()?;
+//! let pos: DynPos = "([0, 1], Snd([3]))".parse()?;
+//! assert_eq!(x.to_string(), "(DimensionN, Either<Context, DimensionN>)");
+//! let mut new_pos = x.zero(&dim);
+//! x.to_pos(&dim, x.to_index(&dim, &pos), &mut new_pos);
+//! assert_eq!(new_pos, pos);
+//! # Ok(())
+//! # }
+//! ~~~
+
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Of,
+    HPoint,
+    Select,
+    Swaps,
+    Dimension,
+    DimensionN,
+    GrayDimensionN,
+    Pair,
+    EqPair,
+    NeqPair,
+    SqPair,
+    Permutation,
+    PowerSet,
+    GrayPowerSet,
+    Combination,
+    SeqUpTo,
+    Context,
+    DirectedContext,
+    KContext,
+    DirectedKContext,
+    PermutationContext,
+    Homotopy,
+    HomotopyUpTo,
+    DirectedHomotopy,
+    DirectedLoopFreeHomotopy,
+    Either,
+    Lex,
+    Colex,
+    Reversed,
+    Slice,
+    Stride,
+    space::Space,
+};
+
+/// Describes a discrete space at runtime.
+///
+/// Spaces that can be composed take `None` for `Data`
+/// and `Some` for `Of` another space.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DynSpace {
+    /// `Dimension`.
+    Dimension(Option<Box<DynSpace>>),
+    /// `DimensionN`.
+    DimensionN(Option<Box<DynSpace>>),
+    /// `GrayDimensionN`.
+    GrayDimensionN,
+    /// `Pair`.
+    Pair(Option<Box<DynSpace>>),
+    /// `EqPair`.
+    EqPair(Option<Box<DynSpace>>),
+    /// `NeqPair`.
+    NeqPair(Option<Box<DynSpace>>),
+    /// `SqPair`.
+    SqPair(Option<Box<DynSpace>>),
+    /// `Permutation`.
+    Permutation(Option<Box<DynSpace>>),
+    /// `PowerSet`.
+    PowerSet(Option<Box<DynSpace>>),
+    /// `GrayPowerSet`.
+    GrayPowerSet,
+    /// `Combination`.
+    Combination(Option<Box<DynSpace>>),
+    /// `SeqUpTo`.
+    SeqUpTo(Option<Box<DynSpace>>),
+    /// `Context`.
+    Context(Option<Box<DynSpace>>),
+    /// `DirectedContext`.
+    DirectedContext(Option<Box<DynSpace>>),
+    /// `KContext`.
+    KContext(Option<Box<DynSpace>>),
+    /// `DirectedKContext`.
+    DirectedKContext(Option<Box<DynSpace>>),
+    /// `PermutationContext`, where the dimension is the number of items.
+    PermutationContext(Swaps),
+    /// `Homotopy`.
+    Homotopy(Option<Box<DynSpace>>),
+    /// `HomotopyUpTo`.
+    HomotopyUpTo(Option<Box<DynSpace>>),
+    /// `DirectedHomotopy`.
+    DirectedHomotopy(Option<Box<DynSpace>>),
+    /// `DirectedLoopFreeHomotopy`.
+    DirectedLoopFreeHomotopy(Option<Box<DynSpace>>),
+    /// A tuple `(T, U)` of two spaces.
+    Tuple(Box<DynSpace>, Box<DynSpace>),
+    /// `Either<T, U>`.
+    Either(Box<DynSpace>, Box<DynSpace>),
+    /// `Reversed<T>`.
+    Reversed(Box<DynSpace>),
+    /// `Lex<T>`, where `T` is `Pair`, `EqPair`, `NeqPair`, `SqPair`, `Permutation` or `PowerSet`.
+    Lex(Box<DynSpace>),
+    /// `Colex<T>`, where `T` is `Pair`, `EqPair`, `NeqPair`, `SqPair`, `Permutation` or `PowerSet`.
+    Colex(Box<DynSpace>),
+    /// `Slice<T>`, where the dimension is `(<dimension of T>, (<start>, <end>))`.
+    Slice(Box<DynSpace>),
+    /// `Stride<T>`, where the dimension is `(<dimension of T>, <step>)`.
+    Stride(Box<DynSpace>),
+}
+
+/// The dimension of a `DynSpace`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DynDim {
+    /// A number.
+    Nat(BigUint),
+    /// A list, such as the dimension of `DimensionN`.
+    List(Vec<DynDim>),
+    /// A tuple, such as the dimension of `Homotopy`.
+    Tuple(Vec<DynDim>),
+}
+
+impl From<usize> for DynDim {
+    fn from(n: usize) -> DynDim {DynDim::Nat(n.into())}
+}
+
+impl From<BigUint> for DynDim {
+    fn from(n: BigUint) -> DynDim {DynDim::Nat(n)}
+}
+
+/// A position in a `DynSpace`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DynPos {
+    /// A number.
+    Nat(BigUint),
+    /// A list, such as a position in `Permutation`.
+    List(Vec<DynPos>),
+    /// A tuple, such as a position in `Pair`.
+    Tuple(Vec<DynPos>),
+    /// A path between two higher order points, see `HPoint`.
+    Path(Box<(DynPos, DynPos)>),
+    /// A position in the first space of `Either`.
+    Fst(Box<DynPos>),
+    /// A position in the second space of `Either`.
+    Snd(Box<DynPos>),
+}
+
+impl From<usize> for DynPos {
+    fn from(n: usize) -> DynPos {DynPos::Nat(n.into())}
+}
+
+impl From<BigUint> for DynPos {
+    fn from(n: BigUint) -> DynPos {DynPos::Nat(n)}
+}
+
+/// Walks the tree, calling a function of `eval` with the typed space of the node.
+///
+/// Returns an error if the dimension does not have the shape of the space.
+macro_rules! walk {
+    ($self:expr, $dim:expr, $f:ident($($arg:expr),*)) => {
+        match $self {
+            DynSpace::Dimension(None) =>
+                eval::$f::<Dimension>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Dimension(Some(of)) =>
+                eval::$f::<Dimension<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::DimensionN(None) =>
+                eval::$f::<DimensionN>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::DimensionN(Some(of)) =>
+                eval::$f::<DimensionN<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::GrayDimensionN =>
+                eval::$f::<GrayDimensionN>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Pair(None) =>
+                eval::$f::<Pair>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Pair(Some(of)) =>
+                eval::$f::<Pair<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::EqPair(None) =>
+                eval::$f::<EqPair>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::EqPair(Some(of)) =>
+                eval::$f::<EqPair<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::NeqPair(None) =>
+                eval::$f::<NeqPair>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::NeqPair(Some(of)) =>
+                eval::$f::<NeqPair<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::SqPair(None) =>
+                eval::$f::<SqPair>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::SqPair(Some(of)) =>
+                eval::$f::<SqPair<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::Permutation(None) =>
+                eval::$f::<Permutation>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Permutation(Some(of)) =>
+                eval::$f::<Permutation<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::PowerSet(None) =>
+                eval::$f::<PowerSet>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::PowerSet(Some(of)) =>
+                eval::$f::<PowerSet<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::GrayPowerSet =>
+                eval::$f::<GrayPowerSet>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Combination(None) =>
+                eval::$f::<Combination>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Combination(Some(of)) =>
+                eval::$f::<Combination<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::SeqUpTo(None) =>
+                eval::$f::<SeqUpTo>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::SeqUpTo(Some(of)) =>
+                eval::$f::<SeqUpTo<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::Context(None) =>
+                eval::$f::<Context>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Context(Some(of)) =>
+                eval::$f::<Context<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::DirectedContext(None) =>
+                eval::$f::<DirectedContext>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::DirectedContext(Some(of)) =>
+                eval::$f::<DirectedContext<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::KContext(None) =>
+                eval::$f::<KContext>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::KContext(Some(of)) =>
+                eval::$f::<KContext<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::DirectedKContext(None) =>
+                eval::$f::<DirectedKContext>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::DirectedKContext(Some(of)) =>
+                eval::$f::<DirectedKContext<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::PermutationContext(swaps) =>
+                eval::$f::<PermutationContext>(
+                    &(FromDim::from_dim($dim, $self)?, *swaps) $(, $arg)*),
+            DynSpace::Homotopy(None) =>
+                eval::$f::<Homotopy>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::Homotopy(Some(of)) =>
+                eval::$f::<Homotopy<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::HomotopyUpTo(None) =>
+                eval::$f::<HomotopyUpTo>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::HomotopyUpTo(Some(of)) =>
+                eval::$f::<HomotopyUpTo<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::DirectedHomotopy(None) =>
+                eval::$f::<DirectedHomotopy>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::DirectedHomotopy(Some(of)) =>
+                eval::$f::<DirectedHomotopy<Of<Dyn>>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::DirectedLoopFreeHomotopy(None) =>
+                eval::$f::<DirectedLoopFreeHomotopy>(&FromDim::from_dim($dim, $self)? $(, $arg)*),
+            DynSpace::DirectedLoopFreeHomotopy(Some(of)) =>
+                eval::$f::<DirectedLoopFreeHomotopy<Of<Dyn>>>(
+                    &FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::Tuple(t, u) =>
+                eval::$f::<(Dyn, Dyn)>(&split($dim, t, u)? $(, $arg)*),
+            DynSpace::Either(t, u) =>
+                eval::$f::<Either<Dyn, Dyn>>(&split($dim, t, u)? $(, $arg)*),
+            DynSpace::Reversed(of) =>
+                eval::$f::<Reversed<Dyn>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+            DynSpace::Lex(of) => match **of {
+                DynSpace::Pair(None) =>
+                    eval::$f::<Lex<Pair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::EqPair(None) =>
+                    eval::$f::<Lex<EqPair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::NeqPair(None) =>
+                    eval::$f::<Lex<NeqPair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::SqPair(None) =>
+                    eval::$f::<Lex<SqPair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::Permutation(None) =>
+                    eval::$f::<Lex<Permutation>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::PowerSet(None) =>
+                    eval::$f::<Lex<PowerSet>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                _ => Err(format!("`Lex` does not support `{}`", of)),
+            },
+            DynSpace::Colex(of) => match **of {
+                DynSpace::Pair(None) =>
+                    eval::$f::<Colex<Pair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::EqPair(None) =>
+                    eval::$f::<Colex<EqPair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::NeqPair(None) =>
+                    eval::$f::<Colex<NeqPair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::SqPair(None) =>
+                    eval::$f::<Colex<SqPair>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::Permutation(None) =>
+                    eval::$f::<Colex<Permutation>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                DynSpace::PowerSet(None) =>
+                    eval::$f::<Colex<PowerSet>>(&FromDim::from_dim($dim, of)? $(, $arg)*),
+                _ => Err(format!("`Colex` does not support `{}`", of)),
+            },
+            DynSpace::Slice(of) =>
+                eval::$f::<Slice<Dyn>>(&slice_dim($dim, of)? $(, $arg)*),
+            DynSpace::Stride(of) =>
+                eval::$f::<Stride<Dyn>>(&stride_dim($dim, of)? $(, $arg)*),
+        }
+    };
+}
+
+impl DynSpace {
+    /// Counts the size of space given the dimensions.
+    ///
+    /// Panics if the dimension does not have the shape of the space.
+    pub fn count(&self, dim: &DynDim) -> BigUint {
+        self.try_count(dim).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a default element.
+    ///
+    /// Panics if the dimension does not have the shape of the space.
+    pub fn zero(&self, dim: &DynDim) -> DynPos {
+        self.try_zero(dim).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Converts position to index.
+    ///
+    /// Panics if the dimension or position does not have the shape of the space,
+    /// or the position is outside of a view.
+    pub fn to_index(&self, dim: &DynDim, pos: &DynPos) -> BigUint {
+        self.try_to_index(dim, pos).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Converts index to position.
+    ///
+    /// Panics if the dimension does not have the shape of the space,
+    /// or the index is not less than the count.
+    pub fn to_pos(&self, dim: &DynDim, index: BigUint, pos: &mut DynPos) {
+        self.try_to_pos(dim, index, pos).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Counts the size of space given the dimensions.
+    pub fn try_count(&self, dim: &DynDim) -> Result<BigUint, String> {
+        self.check_dim(dim)?;
+        self.count_unchecked(dim)
+    }
+
+    /// Creates a default element.
+    pub fn try_zero(&self, dim: &DynDim) -> Result<DynPos, String> {
+        self.check_dim(dim)?;
+        self.zero_unchecked(dim)
+    }
+
+    /// Converts position to index.
+    ///
+    /// Returns an error if the position is not in the space,
+    /// e.g. a `Permutation` with a repeated item or a position outside of a view.
+    /// The index is converted back to a position and compared,
+    /// so a position must be written the way `to_pos` writes it,
+    /// except that an edge of `PermutationContext` can have either orientation.
+    pub fn try_to_index(&self, dim: &DynDim, pos: &DynPos) -> Result<BigUint, String> {
+        self.check_dim(dim)?;
+        self.checked_index(dim, pos)
+    }
+
+    /// Converts index to position.
+    ///
+    /// Returns an error if the index is not less than the count.
+    pub fn try_to_pos(&self, dim: &DynDim, index: BigUint, pos: &mut DynPos) -> Result<(), String> {
+        let count = self.try_count(dim)?;
+        if index >= count {
+            return Err(format!("Index {} is out of range, the count is {}", index, count))
+        }
+        self.to_pos_unchecked(dim, index, pos)
+    }
+
+    /// Counts the size of space, where the dimension is checked by the caller.
+    fn count_unchecked(&self, dim: &DynDim) -> Result<BigUint, String> {
+        walk!(self, dim, count())
+    }
+
+    /// Creates a default element, where the dimension is checked by the caller.
+    fn zero_unchecked(&self, dim: &DynDim) -> Result<DynPos, String> {
+        walk!(self, dim, zero())
+    }
+
+    /// Converts position to index, checking the position
+    /// but not the dimension, which is checked by the caller.
+    fn checked_index(&self, dim: &DynDim, pos: &DynPos) -> Result<BigUint, String> {
+        walk!(self, dim, to_index(pos, self))
+    }
+
+    /// Converts position to index, where the dimension and position are checked by the caller.
+    fn index(&self, dim: &DynDim, pos: &DynPos) -> Result<BigUint, String> {
+        walk!(self, dim, index(pos))
+    }
+
+    /// Converts index to position, where the dimension is checked
+    /// and the index is compared to the count by the caller.
+    fn to_pos_unchecked(&self, dim: &DynDim, index: BigUint, pos: &mut DynPos) -> Result<(), String> {
+        walk!(self, dim, to_pos(index, pos))
+    }
+
+    /// Checks that the dimension has the shape of the space,
+    /// and that its numbers fit the typed space, including the nodes inside.
+    fn check_dim(&self, dim: &DynDim) -> Result<(), String> {
+        walk!(self, dim, check())
+    }
+}
+
+/// Calls the typed space of a node, converting positions.
+mod eval {
+    use super::*;
+
+    pub fn check<S: Check>(dim: &S::Dim) -> Result<(), String> {
+        S::new().check_dim(dim)
+    }
+
+    pub fn count<S: Check>(dim: &S::Dim) -> Result<BigUint, String> {
+        Ok(S::new().count(dim))
+    }
+
+    pub fn zero<S>(dim: &S::Dim) -> Result<DynPos, String>
+        where S: Check, S::Pos: Convert
+    {
+        Ok(S::new().zero(dim).into_dyn())
+    }
+
+    /// Converts position to index, checking that the position is in the space.
+    pub fn to_index<S>(dim: &S::Dim, pos: &DynPos, space: &DynSpace) -> Result<BigUint, String>
+        where S: Check, S::Pos: Convert + PartialEq
+    {
+        let s = S::new();
+        let not_in = || format!("Position `{}` is not in `{}`", pos, space);
+        let mut p = Convert::from_dyn(pos)?;
+        if !s.check_pos(dim, &p)? {return Err(not_in())}
+        let index = s.try_to_index(dim, &p).ok_or_else(not_in)?;
+        if index >= s.count(dim) {return Err(not_in())}
+        let mut q = s.zero(dim);
+        s.to_pos(dim, index.clone(), &mut q);
+        s.canonical(&mut p);
+        if p != q {return Err(not_in())}
+        Ok(index)
+    }
+
+    /// Converts position to index, where the position is checked by the node above.
+    ///
+    /// Calls `to_index` rather than `try_to_index`, since the typed spaces
+    /// check the items before indexing them, which doubles the work per level.
+    pub fn index<S>(dim: &S::Dim, pos: &DynPos) -> Result<BigUint, String>
+        where S: Check, S::Pos: Convert
+    {
+        Ok(S::new().to_index(dim, &Convert::from_dyn(pos)?))
+    }
+
+    pub fn to_pos<S>(dim: &S::Dim, index: BigUint, pos: &mut DynPos) -> Result<(), String>
+        where S: Check, S::Pos: Convert
+    {
+        let s = S::new();
+        let mut p = s.zero(dim);
+        s.to_pos(dim, index, &mut p);
+        *pos = p.into_dyn();
+        Ok(())
+    }
+}
+
+/// A typed space standing in for a `DynSpace` node inside `Of`, tuples and `Either`.
+///
+/// The node is carried by the dimension.
+/// Positions are checked by `Check` before they reach the typed space,
+/// so converting them to index skips the checks of the node.
+struct Dyn<'a>(PhantomData<&'a ()>);
+
+/// The dimension of `Dyn`, which is a node together with its dimension.
+///
+/// The dimension is checked against the node when it is read by `FromDim`.
+#[derive(Clone, Copy)]
+struct Bound<'a> {
+    space: &'a DynSpace,
+    dim: &'a DynDim,
+}
+
+impl<'a> Construct for Dyn<'a> {
+    fn new() -> Self {Dyn(PhantomData)}
+}
+
+impl<'a> Space<BigUint> for Dyn<'a> {
+    type Dim = Bound<'a>;
+    type Pos = DynPos;
+    fn count(&self, b: &Bound<'a>) -> BigUint {
+        b.space.count_unchecked(b.dim).unwrap_or_else(|e| panic!("{}", e))
+    }
+    fn zero(&self, b: &Bound<'a>) -> DynPos {
+        b.space.zero_unchecked(b.dim).unwrap_or_else(|e| panic!("{}", e))
+    }
+    fn to_index(&self, b: &Bound<'a>, pos: &DynPos) -> BigUint {
+        b.space.index(b.dim, pos).unwrap_or_else(|e| panic!("{}", e))
+    }
+    fn to_pos(&self, b: &Bound<'a>, index: BigUint, pos: &mut DynPos) {
+        b.space.to_pos_unchecked(b.dim, index, pos).unwrap_or_else(|e| panic!("{}", e))
+    }
+    fn try_to_index(&self, b: &Bound<'a>, pos: &DynPos) -> Option<BigUint> {
+        b.space.index(b.dim, pos).ok()
+    }
+}
+
+/// Checks dimensions and positions before they reach a typed space,
+/// which panics on numbers that it does not expect.
+trait Check: Space<BigUint> {
+    /// Checks that the numbers of the dimension fit the typed space,
+    /// and the dimensions of the nodes inside.
+    fn check_dim(&self, _dim: &Self::Dim) -> Result<(), String> {Ok(())}
+    /// Returns `false` if the numbers of the position are out of range.
+    ///
+    /// Errors from the nodes inside are passed through.
+    fn check_pos(&self, dim: &Self::Dim, pos: &Self::Pos) -> Result<bool, String>;
+    /// Writes the position the way `to_pos` does, when the space accepts several ways.
+    fn canonical(&self, _pos: &mut Self::Pos) {}
+}
+
+/// The items of a space, which are numbers for `Data` and positions of a node for `Of`.
+trait Item {
+    type Dim;
+    type Pos;
+    /// Checks the dimension of the items, which is a node for `Of`.
+    fn check(_dim: &Self::Dim) -> Result<(), String> {Ok(())}
+    /// Counts the items.
+    fn count(dim: &Self::Dim) -> BigUint;
+    /// Gets the index of an item, or `None` if it is out of range.
+    fn index(dim: &Self::Dim, x: &Self::Pos) -> Result<Option<BigUint>, String>;
+}
+
+impl Item for Data {
+    type Dim = BigUint;
+    type Pos = BigUint;
+    fn count(dim: &BigUint) -> BigUint {dim.clone()}
+    fn index(dim: &BigUint, x: &BigUint) -> Result<Option<BigUint>, String> {
+        Ok(if x < dim {Some(x.clone())} else {None})
+    }
+}
+
+impl<'a> Item for Dyn<'a> {
+    type Dim = Bound<'a>;
+    type Pos = DynPos;
+    fn check(b: &Bound<'a>) -> Result<(), String> {b.space.check_dim(b.dim)}
+    fn count(b: &Bound<'a>) -> BigUint {Dyn::new().count(b)}
+    fn index(b: &Bound<'a>, x: &DynPos) -> Result<Option<BigUint>, String> {
+        b.space.checked_index(b.dim, x).map(Some)
+    }
+}
+
+impl<T: Item> Item for Of<T> {
+    type Dim = T::Dim;
+    type Pos = T::Pos;
+    fn check(dim: &T::Dim) -> Result<(), String> {T::check(dim)}
+    fn count(dim: &T::Dim) -> BigUint {T::count(dim)}
+    fn index(dim: &T::Dim, x: &T::Pos) -> Result<Option<BigUint>, String> {T::index(dim, x)}
+}
+
+/// Gets the indices of items, or `None` if one is out of range.
+fn indices<T: Item>(dim: &T::Dim, xs: &[T::Pos]) -> Result<Option<Vec<BigUint>>, String> {
+    let mut v = Vec::with_capacity(xs.len());
+    for x in xs {
+        match T::index(dim, x)? {
+            Some(i) => v.push(i),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(v))
+}
+
+/// Returns `true` if there is one item per dimension, each in range.
+fn items_in<T: Item>(dim: &[T::Dim], xs: &[T::Pos]) -> Result<bool, String> {
+    if dim.len() != xs.len() {return Ok(false)}
+    for (d, x) in dim.iter().zip(xs) {
+        if T::index(d, x)?.is_none() {return Ok(false)}
+    }
+    Ok(true)
+}
+
+/// Returns `true` if no index is repeated.
+fn distinct(v: &[BigUint]) -> bool {
+    let mut v: Vec<&BigUint> = v.iter().collect();
+    v.sort_unstable();
+    v.windows(2).all(|w| w[0] != w[1])
+}
+
+/// Checks that a number fits in `usize`, for spaces that convert it.
+fn fits(n: &BigUint) -> Result<(), String> {
+    usize::try_from(n).map(drop).map_err(|_| format!("Number {} is too large", n))
+}
+
+impl<T: Item> Check for Dimension<T>
+    where Dimension<T>: Space<BigUint, Dim = T::Dim, Pos = T::Pos>
+{
+    fn check_dim(&self, dim: &T::Dim) -> Result<(), String> {
+        T::check(dim)
+    }
+    fn check_pos(&self, dim: &T::Dim, pos: &T::Pos) -> Result<bool, String> {
+        Ok(T::index(dim, pos)?.is_some())
+    }
+}
+
+impl<T: Item> Check for DimensionN<T>
+    where DimensionN<T>: Space<BigUint, Dim = Vec<T::Dim>, Pos = Vec<T::Pos>>
+{
+    fn check_dim(&self, dim: &Vec<T::Dim>) -> Result<(), String> {
+        dim.iter().try_for_each(T::check)
+    }
+    fn check_pos(&self, dim: &Vec<T::Dim>, pos: &Vec<T::Pos>) -> Result<bool, String> {
+        items_in::<T>(dim, pos)
+    }
+}
+
+impl Check for GrayDimensionN {
+    fn check_pos(&self, dim: &Vec<BigUint>, pos: &Vec<BigUint>) -> Result<bool, String> {
+        items_in::<Data>(dim, pos)
+    }
+}
+
+/// Implements `Check` for a pair space, which compares the indices of the items.
+macro_rules! check_pair {
+    ($space:ident, $ok:expr) => {
+        impl<T: Item> Check for $space<T>
+            where $space<T>: Space<BigUint, Dim = T::Dim, Pos = (T::Pos, T::Pos)>
+        {
+            fn check_dim(&self, dim: &T::Dim) -> Result<(), String> {
+                T::check(dim)
+            }
+            fn check_pos(&self, dim: &T::Dim, (a, b): &(T::Pos, T::Pos)) -> Result<bool, String> {
+                let ok: fn(&BigUint, &BigUint) -> bool = $ok;
+                Ok(match (T::index(dim, a)?, T::index(dim, b)?) {
+                    (Some(a), Some(b)) => ok(&a, &b),
+                    _ => false,
+                })
+            }
+        }
+    };
+}
+
+check_pair!{Pair, |a, b| a < b}
+check_pair!{EqPair, |a, b| a <= b}
+check_pair!{NeqPair, |a, b| a != b}
+check_pair!{SqPair, |_, _| true}
+
+impl<T: Item> Check for Permutation<T>
+    where Permutation<T>: Space<BigUint, Dim = T::Dim, Pos = Vec<T::Pos>>
+{
+    fn check_dim(&self, dim: &T::Dim) -> Result<(), String> {
+        T::check(dim)?;
+        fits(&T::count(dim))
+    }
+    fn check_pos(&self, dim: &T::Dim, pos: &Vec<T::Pos>) -> Result<bool, String> {
+        Ok(match indices::<T>(dim, pos)? {
+            Some(v) => BigUint::from(v.len()) == T::count(dim) && distinct(&v),
+            None => false,
+        })
+    }
+}
+
+impl<T: Item> Check for PowerSet<T>
+    where PowerSet<T>: Space<BigUint, Dim = T::Dim, Pos = Vec<T::Pos>>
+{
+    fn check_dim(&self, dim: &T::Dim) -> Result<(), String> {
+        T::check(dim)?;
+        fits(&T::count(dim))
+    }
+    fn check_pos(&self, dim: &T::Dim, pos: &Vec<T::Pos>) -> Result<bool, String> {
+        Ok(indices::<T>(dim, pos)?.is_some_and(|v| distinct(&v)))
+    }
+}
+
+impl Check for GrayPowerSet {
+    fn check_dim(&self, dim: &BigUint) -> Result<(), String> {
+        fits(dim)
+    }
+    fn check_pos(&self, dim: &BigUint, pos: &Vec<BigUint>) -> Result<bool, String> {
+        Ok(indices::<Data>(dim, pos)?.is_some_and(|v| distinct(&v)))
+    }
+}
+
+impl<T: Item> Check for Combination<T>
+    where Combination<T>: Space<BigUint, Dim = (T::Dim, usize), Pos = Vec<T::Pos>>
+{
+    fn check_dim(&self, (dim, _): &(T::Dim, usize)) -> Result<(), String> {
+        T::check(dim)
+    }
+    fn check_pos(&self, (dim, _): &(T::Dim, usize), pos: &Vec<T::Pos>) -> Result<bool, String> {
+        Ok(indices::<T>(dim, pos)?.is_some())
+    }
+}
+
+impl<T: Item> Check for SeqUpTo<T>
+    where SeqUpTo<T>: Space<BigUint, Dim = (usize, T::Dim), Pos = Vec<T::Pos>>
+{
+    fn check_dim(&self, (_, dim): &(usize, T::Dim)) -> Result<(), String> {
+        T::check(dim)
+    }
+    fn check_pos(&self, (_, dim): &(usize, T::Dim), pos: &Vec<T::Pos>) -> Result<bool, String> {
+        Ok(indices::<T>(dim, pos)?.is_some())
+    }
+}
+
+/// Implements `Check` for a context space, which needs at least one axis.
+macro_rules! check_context {
+    ($space:ident) => {
+        impl<T: Item> Check for $space<T>
+            where $space<T>: Space<BigUint, Dim = Vec<T::Dim>, Pos = (Vec<T::Pos>, usize, T::Pos)>
+        {
+            fn check_dim(&self, dim: &Vec<T::Dim>) -> Result<(), String> {
+                if dim.is_empty() {return Err("Expected non-empty list".into())}
+                dim.iter().try_for_each(T::check)
+            }
+            fn check_pos(
+                &self,
+                dim: &Vec<T::Dim>,
+                (p, ind, b): &(Vec<T::Pos>, usize, T::Pos),
+            ) -> Result<bool, String> {
+                Ok(items_in::<T>(dim, p)? && *ind < dim.len() && T::index(&dim[*ind], b)?.is_some())
+            }
+        }
+    };
+}
+
+check_context!{Context}
+check_context!{DirectedContext}
+
+/// Implements `Check` for a space that changes `k` axes at once.
+macro_rules! check_k_context {
+    ($space:ident) => {
+        impl<T: Item> Check for $space<T>
+            where $space<T>: Space<BigUint,
+                Dim = (Vec<T::Dim>, usize),
+                Pos = (Vec<T::Pos>, Vec<usize>, Vec<T::Pos>)>
+        {
+            fn check_dim(&self, (dim, _): &(Vec<T::Dim>, usize)) -> Result<(), String> {
+                dim.iter().try_for_each(T::check)
+            }
+            fn check_pos(
+                &self,
+                (dim, k): &(Vec<T::Dim>, usize),
+                (xs, axes, ys): &(Vec<T::Pos>, Vec<usize>, Vec<T::Pos>),
+            ) -> Result<bool, String> {
+                if !items_in::<T>(dim, xs)? || axes.len() != *k || ys.len() != *k {return Ok(false)}
+                if !axes.windows(2).all(|w| w[0] < w[1]) || axes.last().is_some_and(|&i| i >= dim.len()) {
+                    return Ok(false)
+                }
+                let dim: Vec<&T::Dim> = axes.iter().map(|&i| &dim[i]).collect();
+                for (d, y) in dim.into_iter().zip(ys) {
+                    if T::index(d, y)?.is_none() {return Ok(false)}
+                }
+                Ok(true)
+            }
+        }
+    };
+}
+
+check_k_context!{KContext}
+check_k_context!{DirectedKContext}
+
+impl Check for PermutationContext {
+    fn check_dim(&self, (n, _): &(BigUint, Swaps)) -> Result<(), String> {
+        fits(n)
+    }
+    fn check_pos(
+        &self,
+        (n, swaps): &(BigUint, Swaps),
+        (perm, i, j): &(Vec<BigUint>, usize, usize),
+    ) -> Result<bool, String> {
+        let perm_ok = match indices::<Data>(n, perm)? {
+            Some(v) => BigUint::from(v.len()) == *n && distinct(&v),
+            None => false,
+        };
+        Ok(perm_ok && swaps.allows(perm.len(), *i.min(j), *i.max(j)))
+    }
+    fn canonical(&self, (perm, i, j): &mut (Vec<BigUint>, usize, usize)) {
+        if i > j {std::mem::swap(i, j)}
+        if perm[*i] > perm[*j] {perm.swap(*i, *j)}
+    }
+}
+
+/// Implements `Check` for a homotopy space,
+/// where paths can not be nested deeper than the level.
+macro_rules! check_homotopy {
+    ($space:ident) => {
+        impl<T: Item> Check for $space<T>
+            where $space<T>: Space<BigUint, Dim = (usize, T::Dim), Pos = HPoint<T::Pos>>
+        {
+            fn check_dim(&self, (_, dim): &(usize, T::Dim)) -> Result<(), String> {
+                T::check(dim)
+            }
+            fn check_pos(
+                &self,
+                (level, dim): &(usize, T::Dim),
+                pos: &HPoint<T::Pos>,
+            ) -> Result<bool, String> {
+                if pos.level() > *level {return Ok(false)}
+                for x in pos.leaves() {
+                    if T::index(dim, x)?.is_none() {return Ok(false)}
+                }
+                Ok(true)
+            }
+        }
+    };
+}
+
+check_homotopy!{Homotopy}
+check_homotopy!{HomotopyUpTo}
+check_homotopy!{DirectedHomotopy}
+check_homotopy!{DirectedLoopFreeHomotopy}
+
+impl<'a> Check for (Dyn<'a>, Dyn<'a>) {
+    fn check_dim(&self, (t, u): &(Bound<'a>, Bound<'a>)) -> Result<(), String> {
+        Dyn::check(t)?;
+        Dyn::check(u)
+    }
+    fn check_pos(&self, (t, u): &(Bound<'a>, Bound<'a>), (a, b): &(DynPos, DynPos)) -> Result<bool, String> {
+        Ok(Dyn::index(t, a)?.is_some() && Dyn::index(u, b)?.is_some())
+    }
+}
+
+impl<'a> Check for Either<Dyn<'a>, Dyn<'a>> {
+    fn check_dim(&self, (t, u): &(Bound<'a>, Bound<'a>)) -> Result<(), String> {
+        Dyn::check(t)?;
+        Dyn::check(u)
+    }
+    fn check_pos(
+        &self,
+        (t, u): &(Bound<'a>, Bound<'a>),
+        pos: &Select<DynPos, DynPos>,
+    ) -> Result<bool, String> {
+        Ok(match pos {
+            Select::Fst(a) => Dyn::index(t, a)?.is_some(),
+            Select::Snd(b) => Dyn::index(u, b)?.is_some(),
+        })
+    }
+}
+
+impl<'a> Check for Reversed<Dyn<'a>> {
+    fn check_dim(&self, b: &Bound<'a>) -> Result<(), String> {
+        Dyn::check(b)
+    }
+    fn check_pos(&self, b: &Bound<'a>, pos: &DynPos) -> Result<bool, String> {
+        Ok(Dyn::index(b, pos)?.is_some())
+    }
+}
+
+/// The range of a view is checked by `try_to_index`.
+impl<'a> Check for Slice<Dyn<'a>> {
+    fn check_dim(&self, (b, range): &(Bound<'a>, Range<BigUint>)) -> Result<(), String> {
+        Dyn::check(b)?;
+        if range.end > b.space.count_unchecked(b.dim)? {return Err("Slice range ends after the space".into())}
+        Ok(())
+    }
+    fn check_pos(&self, (b, _): &(Bound<'a>, Range<BigUint>), pos: &DynPos) -> Result<bool, String> {
+        Ok(Dyn::index(b, pos)?.is_some())
+    }
+}
+
+impl<'a> Check for Stride<Dyn<'a>> {
+    fn check_dim(&self, (b, _): &(Bound<'a>, BigUint)) -> Result<(), String> {
+        Dyn::check(b)
+    }
+    fn check_pos(&self, (b, _): &(Bound<'a>, BigUint), pos: &DynPos) -> Result<bool, String> {
+        Ok(Dyn::index(b, pos)?.is_some())
+    }
+}
+
+/// Implements `Check` for an ordering, which has the same positions as the space.
+macro_rules! check_order {
+    ($order:ident) => {
+        impl<T: Check> Check for $order<T>
+            where $order<T>: Space<BigUint, Dim = T::Dim, Pos = T::Pos>
+        {
+            fn check_dim(&self, dim: &T::Dim) -> Result<(), String> {
+                T::new().check_dim(dim)
+            }
+            fn check_pos(&self, dim: &T::Dim, pos: &T::Pos) -> Result<bool, String> {
+                T::new().check_pos(dim, pos)
+            }
+        }
+    };
+}
+
+check_order!{Lex}
+check_order!{Colex}
+
+/// Splits the dimension of a tuple or `Either` between two nodes.
+fn split<'a>(
+    dim: &'a DynDim,
+    t: &'a DynSpace,
+    u: &'a DynSpace,
+) -> Result<(Bound<'a>, Bound<'a>), String> {
+    match dim {
+        DynDim::Tuple(v) if v.len() == 2 =>
+            Ok((FromDim::from_dim(&v[0], t)?, FromDim::from_dim(&v[1], u)?)),
+        _ => Err("Expected tuple of 2 dimensions".into()),
+    }
+}
+
+/// Reads the dimension of `Slice`, checking that the range does not end before it starts.
+///
+/// The end of the range is compared to the count by `Check`, after the node inside is checked.
+fn slice_dim<'a>(dim: &'a DynDim, of: &'a DynSpace) -> Result<(Bound<'a>, Range<BigUint>), String> {
+    let (b, range): (Bound, Range<BigUint>) = FromDim::from_dim(dim, of)?;
+    if range.start > range.end {return Err("Slice range ends before it starts".into())}
+    Ok((b, range))
+}
+
+/// Reads the dimension of `Stride`, checking that the step is not zero.
+fn stride_dim<'a>(dim: &'a DynDim, of: &'a DynSpace) -> Result<(Bound<'a>, BigUint), String> {
+    let (b, step): (Bound, BigUint) = FromDim::from_dim(dim, of)?;
+    if step == 0usize.into() {return Err("Stride step is zero".into())}
+    Ok((b, step))
+}
+
+/// Reads a typed dimension, where `of` is the node of `Of`.
+trait FromDim<'a>: Sized {
+    fn from_dim(dim: &'a DynDim, of: &'a DynSpace) -> Result<Self, String>;
+}
+
+impl<'a> FromDim<'a> for BigUint {
+    fn from_dim(dim: &'a DynDim, _of: &'a DynSpace) -> Result<Self, String> {
+        match dim {
+            DynDim::Nat(n) => Ok(n.clone()),
+            _ => Err(String::from("Expected number")),
+        }
+    }
+}
+
+impl<'a> FromDim<'a> for usize {
+    fn from_dim(dim: &'a DynDim, _of: &'a DynSpace) -> Result<Self, String> {
+        match dim {
+            DynDim::Nat(n) => n.try_into().map_err(|_| format!("Number {} is too large", n)),
+            _ => Err(String::from("Expected number")),
+        }
+    }
+}
+
+impl<'a> FromDim<'a> for Bound<'a> {
+    fn from_dim(dim: &'a DynDim, of: &'a DynSpace) -> Result<Self, String> {
+        Ok(Bound {space: of, dim})
+    }
+}
+
+impl<'a> FromDim<'a> for Range<BigUint> {
+    fn from_dim(dim: &'a DynDim, _of: &'a DynSpace) -> Result<Self, String> {
+        match dim {
+            DynDim::Tuple(v) if v.len() == 2 => match (&v[0], &v[1]) {
+                (DynDim::Nat(start), DynDim::Nat(end)) => Ok(start.clone()..end.clone()),
+                _ => Err("Expected range of 2 numbers".into()),
+            },
+            _ => Err("Expected range of 2 numbers".into()),
+        }
+    }
+}
+
+impl<'a, T: FromDim<'a>> FromDim<'a> for Vec<T> {
+    fn from_dim(dim: &'a DynDim, of: &'a DynSpace) -> Result<Self, String> {
+        match dim {
+            DynDim::List(v) => v.iter().map(|d| T::from_dim(d, of)).collect(),
+            _ => Err(String::from("Expected list")),
+        }
+    }
+}
+
+impl<'a, T: FromDim<'a>, U: FromDim<'a>> FromDim<'a> for (T, U) {
+    fn from_dim(dim: &'a DynDim, of: &'a DynSpace) -> Result<Self, String> {
+        match dim {
+            DynDim::Tuple(v) if v.len() == 2 =>
+                Ok((T::from_dim(&v[0], of)?, U::from_dim(&v[1], of)?)),
+            _ => Err(String::from("Expected tuple of 2 dimensions")),
+        }
+    }
+}
+
+/// Converts between typed positions and `DynPos`.
+trait Convert: Sized {
+    fn into_dyn(self) -> DynPos;
+    fn from_dyn(pos: &DynPos) -> Result<Self, String>;
+}
+
+impl Convert for DynPos {
+    fn into_dyn(self) -> DynPos {self}
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {Ok(pos.clone())}
+}
+
+impl Convert for BigUint {
+    fn into_dyn(self) -> DynPos {DynPos::Nat(self)}
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::Nat(n) => Ok(n.clone()),
+            _ => Err(String::from("Expected number")),
+        }
+    }
+}
+
+impl Convert for usize {
+    fn into_dyn(self) -> DynPos {self.into()}
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::Nat(n) => n.try_into().map_err(|_| format!("Number {} is too large", n)),
+            _ => Err(String::from("Expected number")),
+        }
+    }
+}
+
+impl<T: Convert> Convert for Vec<T> {
+    fn into_dyn(self) -> DynPos {
+        DynPos::List(self.into_iter().map(T::into_dyn).collect())
+    }
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::List(v) => v.iter().map(T::from_dyn).collect(),
+            _ => Err(String::from("Expected list")),
+        }
+    }
+}
+
+impl<T: Convert, U: Convert> Convert for (T, U) {
+    fn into_dyn(self) -> DynPos {
+        DynPos::Tuple(vec![self.0.into_dyn(), self.1.into_dyn()])
+    }
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::Tuple(v) if v.len() == 2 =>
+                Ok((T::from_dyn(&v[0])?, U::from_dyn(&v[1])?)),
+            _ => Err(String::from("Expected tuple of 2 positions")),
+        }
+    }
+}
+
+impl<T: Convert, U: Convert, V: Convert> Convert for (T, U, V) {
+    fn into_dyn(self) -> DynPos {
+        DynPos::Tuple(vec![self.0.into_dyn(), self.1.into_dyn(), self.2.into_dyn()])
+    }
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::Tuple(v) if v.len() == 3 =>
+                Ok((T::from_dyn(&v[0])?, U::from_dyn(&v[1])?, V::from_dyn(&v[2])?)),
+            _ => Err(String::from("Expected tuple of 3 positions")),
+        }
+    }
+}
+
+impl<T: Convert> Convert for HPoint<T> {
+    fn into_dyn(self) -> DynPos {
+        match self {
+            HPoint::Point(x) => x.into_dyn(),
+            HPoint::Path(ab) => {
+                let (a, b) = *ab;
+                DynPos::Path(Box::new((a.into_dyn(), b.into_dyn())))
+            }
+        }
+    }
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::Path(ab) =>
+                Ok(HPoint::Path(Box::new((HPoint::from_dyn(&ab.0)?, HPoint::from_dyn(&ab.1)?)))),
+            x => Ok(HPoint::Point(T::from_dyn(x)?)),
+        }
+    }
+}
+
+impl<T: Convert, U: Convert> Convert for Select<T, U> {
+    fn into_dyn(self) -> DynPos {
+        match self {
+            Select::Fst(x) => DynPos::Fst(Box::new(x.into_dyn())),
+            Select::Snd(x) => DynPos::Snd(Box::new(x.into_dyn())),
+        }
+    }
+    fn from_dyn(pos: &DynPos) -> Result<Self, String> {
+        match pos {
+            DynPos::Fst(x) => Ok(Select::Fst(T::from_dyn(x)?)),
+            DynPos::Snd(x) => Ok(Select::Snd(U::from_dyn(x)?)),
+            _ => Err(String::from("Expected `Fst` or `Snd`")),
+        }
+    }
+}
+
+impl DynSpace {
+    /// Returns the name of the space, without arguments.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DynSpace::Dimension(_) => "Dimension",
+            DynSpace::DimensionN(_) => "DimensionN",
+            DynSpace::GrayDimensionN => "GrayDimensionN",
+            DynSpace::Pair(_) => "Pair",
+            DynSpace::EqPair(_) => "EqPair",
+            DynSpace::NeqPair(_) => "NeqPair",
+            DynSpace::SqPair(_) => "SqPair",
+            DynSpace::Permutation(_) => "Permutation",
+            DynSpace::PowerSet(_) => "PowerSet",
+            DynSpace::GrayPowerSet => "GrayPowerSet",
+            DynSpace::Combination(_) => "Combination",
+            DynSpace::SeqUpTo(_) => "SeqUpTo",
+            DynSpace::Context(_) => "Context",
+            DynSpace::DirectedContext(_) => "DirectedContext",
+            DynSpace::KContext(_) => "KContext",
+            DynSpace::DirectedKContext(_) => "DirectedKContext",
+            DynSpace::PermutationContext(_) => "PermutationContext",
+            DynSpace::Homotopy(_) => "Homotopy",
+            DynSpace::HomotopyUpTo(_) => "HomotopyUpTo",
+            DynSpace::DirectedHomotopy(_) => "DirectedHomotopy",
+            DynSpace::DirectedLoopFreeHomotopy(_) => "DirectedLoopFreeHomotopy",
+            DynSpace::Tuple(_, _) => "Tuple",
+            DynSpace::Either(_, _) => "Either",
+            DynSpace::Reversed(_) => "Reversed",
+            DynSpace::Lex(_) => "Lex",
+            DynSpace::Colex(_) => "Colex",
+            DynSpace::Slice(_) => "Slice",
+            DynSpace::Stride(_) => "Stride",
+        }
+    }
+}
+
+/// Creates a space from `Data` or `Of<T>`.
+type Compose = fn(Option<Box<DynSpace>>) -> DynSpace;
+
+/// The spaces that take `Data` or `Of<T>`.
+const COMPOSABLE: &[(&str, Compose)] = &[
+    ("Dimension", DynSpace::Dimension),
+    ("DimensionN", DynSpace::DimensionN),
+    ("Pair", DynSpace::Pair),
+    ("EqPair", DynSpace::EqPair),
+    ("NeqPair", DynSpace::NeqPair),
+    ("SqPair", DynSpace::SqPair),
+    ("Permutation", DynSpace::Permutation),
+    ("PowerSet", DynSpace::PowerSet),
+    ("Combination", DynSpace::Combination),
+    ("SeqUpTo", DynSpace::SeqUpTo),
+    ("Context", DynSpace::Context),
+    ("DirectedContext", DynSpace::DirectedContext),
+    ("KContext", DynSpace::KContext),
+    ("DirectedKContext", DynSpace::DirectedKContext),
+    ("Homotopy", DynSpace::Homotopy),
+    ("HomotopyUpTo", DynSpace::HomotopyUpTo),
+    ("DirectedHomotopy", DynSpace::DirectedHomotopy),
+    ("DirectedLoopFreeHomotopy", DynSpace::DirectedLoopFreeHomotopy),
+];
+
+/// Writes the type expression, e.g. `Pair<Of<PowerSet>>`.
+///
+/// `Data` is left out, and `PermutationContext` with all swaps
+/// is written without arguments.
+impl fmt::Display for DynSpace {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynSpace::Dimension(of) |
+            DynSpace::DimensionN(of) |
+            DynSpace::Pair(of) |
+            DynSpace::EqPair(of) |
+            DynSpace::NeqPair(of) |
+            DynSpace::SqPair(of) |
+            DynSpace::Permutation(of) |
+            DynSpace::PowerSet(of) |
+            DynSpace::Combination(of) |
+            DynSpace::SeqUpTo(of) |
+            DynSpace::Context(of) |
+            DynSpace::DirectedContext(of) |
+            DynSpace::KContext(of) |
+            DynSpace::DirectedKContext(of) |
+            DynSpace::Homotopy(of) |
+            DynSpace::HomotopyUpTo(of) |
+            DynSpace::DirectedHomotopy(of) |
+            DynSpace::DirectedLoopFreeHomotopy(of) => match of {
+                None => write!(w, "{}", self.name()),
+                Some(of) => write!(w, "{}<Of<{}>>", self.name(), of),
+            },
+            DynSpace::GrayDimensionN | DynSpace::GrayPowerSet => write!(w, "{}", self.name()),
+            DynSpace::PermutationContext(Swaps::All) => write!(w, "PermutationContext"),
+            DynSpace::PermutationContext(Swaps::Adjacent) => write!(w, "PermutationContext<Adjacent>"),
+            DynSpace::Tuple(t, u) => write!(w, "({}, {})", t, u),
+            DynSpace::Either(t, u) => write!(w, "Either<{}, {}>", t, u),
+            DynSpace::Reversed(t) |
+            DynSpace::Lex(t) |
+            DynSpace::Colex(t) |
+            DynSpace::Slice(t) |
+            DynSpace::Stride(t) => write!(w, "{}<{}>", self.name(), t),
+        }
+    }
+}
+
+/// Writes numbers, lists as `[a, b]` and tuples as `(a, b)`.
+impl fmt::Display for DynDim {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynDim::Nat(n) => write!(w, "{}", n),
+            DynDim::List(v) => write_list(w, "[", v, "]"),
+            DynDim::Tuple(v) => write_list(w, "(", v, ")"),
+        }
+    }
+}
+
+/// Writes numbers, lists as `[a, b]`, tuples as `(a, b)`,
+/// paths as `Path(a, b)` and selections as `Fst(a)` or `Snd(b)`.
+impl fmt::Display for DynPos {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynPos::Nat(n) => write!(w, "{}", n),
+            DynPos::List(v) => write_list(w, "[", v, "]"),
+            DynPos::Tuple(v) => write_list(w, "(", v, ")"),
+            DynPos::Path(ab) => write!(w, "Path({}, {})", ab.0, ab.1),
+            DynPos::Fst(x) => write!(w, "Fst({})", x),
+            DynPos::Snd(x) => write!(w, "Snd({})", x),
+        }
+    }
+}
+
+fn write_list<T: fmt::Display>(
+    w: &mut fmt::Formatter<'_>,
+    open: &str,
+    v: &[T],
+    close: &str
+) -> fmt::Result {
+    write!(w, "{}", open)?;
+    for (i, x) in v.iter().enumerate() {
+        if i > 0 {write!(w, ", ")?}
+        write!(w, "{}", x)?;
+    }
+    write!(w, "{}", close)
+}
+
+/// An error when parsing a space, dimension or position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDynError {
+    /// The byte offset in the string where the error occurred.
+    pub offset: usize,
+    /// Describes what went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for ParseDynError {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(w, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseDynError {}
+
+/// The deepest nesting that is parsed, so that deep input can not overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+fn err<U>(offset: usize, message: &str) -> Result<U, ParseDynError> {
+    Err(ParseDynError {offset, message: message.into()})
+}
+
+fn skip_ws(s: &str, mut i: usize) -> usize {
+    while let Some(c) = s[i..].chars().next() {
+        if !c.is_whitespace() {break}
+        i += c.len_utf8();
+    }
+    i
+}
+
+fn expect(s: &str, i: usize, c: char) -> Result<usize, ParseDynError> {
+    let i = skip_ws(s, i);
+    if s[i..].starts_with(c) {Ok(i + 1)}
+    else {err(i, &format!("Expected `{}`", c))}
+}
+
+/// Reads an identifier, returning it with the offset where it starts.
+fn ident(s: &str, i: usize) -> (&str, usize, usize) {
+    let i = skip_ws(s, i);
+    let end = s[i..].find(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|n| i + n).unwrap_or(s.len());
+    (&s[i..end], i, end)
+}
+
+/// Reads a comma separated list of items until `close`.
+fn items<T>(
+    s: &str,
+    i: usize,
+    close: char,
+    mut item: impl FnMut(&str, usize) -> Result<(T, usize), ParseDynError>
+) -> Result<(Vec<T>, usize), ParseDynError> {
+    let mut v = vec![];
+    let mut i = skip_ws(s, i);
+    if s[i..].starts_with(close) {return Ok((v, i + 1))}
+    loop {
+        let (x, j) = item(s, i)?;
+        v.push(x);
+        i = skip_ws(s, j);
+        if s[i..].starts_with(close) {return Ok((v, i + 1))}
+        if !s[i..].starts_with(',') {return err(i, &format!("Expected `,` or `{}`", close))}
+        i += 1;
+    }
+}
+
+fn number(s: &str, i: usize) -> Result<(BigUint, usize), ParseDynError> {
+    let i = skip_ws(s, i);
+    let end = s[i..].find(|c: char| !c.is_ascii_digit())
+        .map(|n| i + n).unwrap_or(s.len());
+    match BigUint::parse_bytes(&s.as_bytes()[i..end], 10) {
+        Some(n) => Ok((n, end)),
+        None => err(i, "Expected number, `[` or `(`"),
+    }
+}
+
+fn finish<T>(s: &str, (x, i): (T, usize)) -> Result<T, ParseDynError> {
+    let i = skip_ws(s, i);
+    if i < s.len() {return err(i, "Unexpected trailing characters")}
+    Ok(x)
+}
+
+/// An argument between `<` and `>`.
+enum Arg {
+    Data,
+    Of(DynSpace),
+    Swaps(Swaps),
+    Space(DynSpace),
+}
+
+fn arg(s: &str, i: usize, depth: usize) -> Result<((Arg, usize), usize), ParseDynError> {
+    let (name, start, end) = ident(s, i);
+    let arg = match name {
+        "Data" => (Arg::Data, end),
+        "All" => (Arg::Swaps(Swaps::All), end),
+        "Adjacent" => (Arg::Swaps(Swaps::Adjacent), end),
+        "Of" => {
+            let i = expect(s, end, '<')?;
+            let (of, i) = space(s, i, depth)?;
+            (Arg::Of(of), expect(s, i, '>')?)
+        }
+        _ => {
+            let (x, i) = space(s, start, depth)?;
+            (Arg::Space(x), i)
+        }
+    };
+    Ok(((arg.0, start), arg.1))
+}
+
+fn space(s: &str, i: usize, depth: usize) -> Result<(DynSpace, usize), ParseDynError> {
+    let i = skip_ws(s, i);
+    if depth >= MAX_DEPTH {return err(i, "Spaces are nested too deeply")}
+    if s[i..].starts_with('(') {
+        let (t, i) = space(s, i + 1, depth + 1)?;
+        let i = expect(s, i, ',')?;
+        let (u, i) = space(s, i, depth + 1)?;
+        let i = expect(s, i, ')')?;
+        return Ok((DynSpace::Tuple(Box::new(t), Box::new(u)), i))
+    }
+    let (name, start, end) = ident(s, i);
+    if name.is_empty() {return err(start, "Expected space")}
+    let known = COMPOSABLE.iter().any(|(n, _)| *n == name) || matches!(name,
+        "GrayDimensionN" | "GrayPowerSet" | "PermutationContext" | "Either" |
+        "Reversed" | "Lex" | "Colex" | "Slice" | "Stride");
+    if !known {return err(start, &format!("Unknown space `{}`", name))}
+    let j = skip_ws(s, end);
+    let (mut args, end) = if s[j..].starts_with('<') {
+        items(s, j + 1, '>', |s, i| arg(s, i, depth + 1))?
+    } else {
+        (vec![], end)
+    };
+    let arity = |n: usize, args: &[(Arg, usize)]| {
+        if args.len() == n {Ok(())}
+        else {
+            let plural = if n == 1 {""} else {"s"};
+            err(start, &format!("`{}` expects {} argument{}, found {}",
+                name, n, plural, args.len()))
+        }
+    };
+    let as_space = |arg: (Arg, usize)| match arg {
+        (Arg::Space(x), _) => Ok(Box::new(x)),
+        (_, offset) => err(offset, &format!("Expected a space in `{}`", name)),
+    };
+    let wraps = matches!(name, "Reversed" | "Lex" | "Colex" | "Slice" | "Stride");
+    if name != "Either" && !wraps && args.len() > 1 {arity(1, &args)?}
+    let x = if let Some(&(_, f)) = COMPOSABLE.iter().find(|(n, _)| *n == name) {
+        match args.pop() {
+            None | Some((Arg::Data, _)) => f(None),
+            Some((Arg::Of(of), _)) => f(Some(Box::new(of))),
+            Some((_, offset)) =>
+                return err(offset, &format!("Expected `Data` or `Of<...>` in `{}`", name)),
+        }
+    } else {
+        match name {
+            "GrayDimensionN" | "GrayPowerSet" => match args.pop() {
+                None | Some((Arg::Data, _)) =>
+                    if name == "GrayPowerSet" {DynSpace::GrayPowerSet}
+                    else {DynSpace::GrayDimensionN},
+                Some((_, offset)) =>
+                    return err(offset, &format!("Expected `Data` in `{}`", name)),
+            },
+            "PermutationContext" => match args.pop() {
+                None | Some((Arg::Data, _)) => DynSpace::PermutationContext(Swaps::All),
+                Some((Arg::Swaps(swaps), _)) => DynSpace::PermutationContext(swaps),
+                Some((_, offset)) =>
+                    return err(offset, "Expected `All` or `Adjacent` in `PermutationContext`"),
+            },
+            "Either" => {
+                arity(2, &args)?;
+                let u = as_space(args.pop().unwrap())?;
+                let t = as_space(args.pop().unwrap())?;
+                DynSpace::Either(t, u)
+            }
+            "Lex" | "Colex" => {
+                arity(1, &args)?;
+                let offset = args[0].1;
+                let t = as_space(args.pop().unwrap())?;
+                if !matches!(*t, DynSpace::Pair(None) | DynSpace::EqPair(None) |
+                    DynSpace::NeqPair(None) | DynSpace::SqPair(None) |
+                    DynSpace::Permutation(None) | DynSpace::PowerSet(None))
+                {
+                    return err(offset, &format!("`{}` supports `Pair`, `EqPair`, `NeqPair`, \
+                        `SqPair`, `Permutation` and `PowerSet`", name))
+                }
+                if name == "Lex" {DynSpace::Lex(t)} else {DynSpace::Colex(t)}
+            }
+            _ => {
+                arity(1, &args)?;
+                let t = as_space(args.pop().unwrap())?;
+                match name {
+                    "Reversed" => DynSpace::Reversed(t),
+                    "Slice" => DynSpace::Slice(t),
+                    _ => DynSpace::Stride(t),
+                }
+            }
+        }
+    };
+    Ok((x, end))
+}
+
+/// Reads a type expression, such as `PowerSet<Of<Pair>>` or `(Pair, Either<Context, DimensionN>)`.
+///
+/// `Data` arguments are optional.
+/// `PermutationContext<Adjacent>` selects adjacent swaps.
+impl FromStr for DynSpace {
+    type Err = ParseDynError;
+    fn from_str(s: &str) -> Result<Self, ParseDynError> {
+        finish(s, space(s, 0, 0)?)
+    }
+}
+
+fn dim(s: &str, i: usize, depth: usize) -> Result<(DynDim, usize), ParseDynError> {
+    let i = skip_ws(s, i);
+    if depth >= MAX_DEPTH {return err(i, "Dimensions are nested too deeply")}
+    let item = |s: &str, i| dim(s, i, depth + 1);
+    if s[i..].starts_with('[') {
+        let (v, i) = items(s, i + 1, ']', item)?;
+        Ok((DynDim::List(v), i))
+    } else if s[i..].starts_with('(') {
+        let (v, i) = items(s, i + 1, ')', item)?;
+        Ok((DynDim::Tuple(v), i))
+    } else {
+        let (n, i) = number(s, i)?;
+        Ok((DynDim::Nat(n), i))
+    }
+}
+
+/// Reads the format written by `Display`, e.g. `(2, [3, 4])`.
+impl FromStr for DynDim {
+    type Err = ParseDynError;
+    fn from_str(s: &str) -> Result<Self, ParseDynError> {
+        finish(s, dim(s, 0, 0)?)
+    }
+}
+
+fn pos(s: &str, i: usize, depth: usize) -> Result<(DynPos, usize), ParseDynError> {
+    let i = skip_ws(s, i);
+    if depth >= MAX_DEPTH {return err(i, "Positions are nested too deeply")}
+    let item = |s: &str, i| pos(s, i, depth + 1);
+    if s[i..].starts_with('[') {
+        let (v, i) = items(s, i + 1, ']', item)?;
+        return Ok((DynPos::List(v), i))
+    } else if s[i..].starts_with('(') {
+        let (v, i) = items(s, i + 1, ')', item)?;
+        return Ok((DynPos::Tuple(v), i))
+    }
+    let (name, start, end) = ident(s, i);
+    match name {
+        "Path" => {
+            let i = expect(s, end, '(')?;
+            let (a, i) = pos(s, i, depth + 1)?;
+            let i = expect(s, i, ',')?;
+            let (b, i) = pos(s, i, depth + 1)?;
+            Ok((DynPos::Path(Box::new((a, b))), expect(s, i, ')')?))
+        }
+        "Fst" | "Snd" => {
+            let i = expect(s, end, '(')?;
+            let (x, i) = pos(s, i, depth + 1)?;
+            let x = Box::new(x);
+            let x = if name == "Fst" {DynPos::Fst(x)} else {DynPos::Snd(x)};
+            Ok((x, expect(s, i, ')')?))
+        }
+        _ => {
+            let (n, i) = number(s, start)?;
+            Ok((DynPos::Nat(n), i))
+        }
+    }
+}
+
+/// Reads the format written by `Display`, e.g. `Snd([(0, 1), (1, 2)])`.
+impl FromStr for DynPos {
+    type Err = ParseDynError;
+    fn from_str(s: &str) -> Result<Self, ParseDynError> {
+        finish(s, pos(s, 0, 0)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use super::{Convert, DynSpace, DynDim, DynPos, MAX_DEPTH};
+
+    fn of(x: DynSpace) -> Option<Box<DynSpace>> {Some(Box::new(x))}
+
+    fn list(v: &[usize]) -> DynDim {
+        DynDim::List(v.iter().map(|&n| n.into()).collect())
+    }
+
+    /// Checks that the dynamic space agrees with the typed space.
+    fn check<S>(x: &DynSpace, dim: &DynDim, typed_dim: &S::Dim)
+        where S: space::Space<BigUint>,
+              S::Pos: Clone + Convert,
+    {
+        let s: S = Construct::new();
+        let count = x.count(dim);
+        assert_eq!(count, s.count(typed_dim));
+        let count: usize = (&count).try_into().unwrap();
+        let mut a = x.zero(dim);
+        let mut b = s.zero(typed_dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut a);
+            s.to_pos(typed_dim, i.into(), &mut b);
+            assert_eq!(a, b.clone().into_dyn());
+            assert_eq!(x.to_index(dim, &a), i.into());
+        }
+    }
+
+    #[test]
+    fn data() {
+        check::<Pair>(&DynSpace::Pair(None), &4.into(), &4usize.into());
+        check::<Permutation>(&DynSpace::Permutation(None), &4.into(), &4usize.into());
+        check::<Context>(&DynSpace::Context(None), &list(&[2, 3]),
+            &vec![2usize.into(), 3usize.into()]);
+        check::<Homotopy>(&DynSpace::Homotopy(None),
+            &DynDim::Tuple(vec![2.into(), 2.into()]), &(2, 2usize.into()));
+        check::<PermutationContext>(&DynSpace::PermutationContext(Swaps::Adjacent),
+            &3.into(), &(3usize.into(), Swaps::Adjacent));
+        check::<Combination>(&DynSpace::Combination(None),
+            &DynDim::Tuple(vec![5.into(), 3.into()]), &(5usize.into(), 3));
+        check::<SeqUpTo>(&DynSpace::SeqUpTo(None),
+            &DynDim::Tuple(vec![2.into(), 3.into()]), &(2, 3usize.into()));
+        check::<KContext>(&DynSpace::KContext(None),
+            &DynDim::Tuple(vec![list(&[2, 3, 2]), 2.into()]),
+            &(vec![2usize.into(), 3usize.into(), 2usize.into()], 2));
+        check::<DirectedKContext>(&DynSpace::DirectedKContext(None),
+            &DynDim::Tuple(vec![list(&[2, 3, 2]), 2.into()]),
+            &(vec![2usize.into(), 3usize.into(), 2usize.into()], 2));
+        check::<HomotopyUpTo>(&DynSpace::HomotopyUpTo(None),
+            &DynDim::Tuple(vec![2.into(), 3.into()]), &(2, 3usize.into()));
+        check::<DirectedHomotopy>(&DynSpace::DirectedHomotopy(None),
+            &DynDim::Tuple(vec![2.into(), 2.into()]), &(2, 2usize.into()));
+        check::<DirectedLoopFreeHomotopy>(&DynSpace::DirectedLoopFreeHomotopy(None),
+            &DynDim::Tuple(vec![2.into(), 3.into()]), &(2, 3usize.into()));
+        check::<GrayDimensionN>(&DynSpace::GrayDimensionN, &list(&[2, 3]),
+            &vec![2usize.into(), 3usize.into()]);
+        check::<GrayPowerSet>(&DynSpace::GrayPowerSet, &4.into(), &4usize.into());
+    }
+
+    #[test]
+    fn of_data() {
+        check::<PowerSet<Of<Pair>>>(&DynSpace::PowerSet(of(DynSpace::Pair(None))),
+            &4.into(), &4usize.into());
+        check::<Permutation<Of<Pair>>>(&DynSpace::Permutation(of(DynSpace::Pair(None))),
+            &3.into(), &3usize.into());
+        check::<DimensionN<Of<Pair>>>(&DynSpace::DimensionN(of(DynSpace::Pair(None))),
+            &list(&[3, 4]), &vec![3usize.into(), 4usize.into()]);
+        check::<Homotopy<Of<DimensionN>>>(&DynSpace::Homotopy(of(DynSpace::DimensionN(None))),
+            &DynDim::Tuple(vec![1.into(), list(&[2, 2])]),
+            &(1, vec![2usize.into(), 2usize.into()]));
+    }
+
+    #[test]
+    fn combinators() {
+        let x = DynSpace::Either(
+            Box::new(DynSpace::Pair(None)),
+            Box::new(DynSpace::Tuple(
+                Box::new(DynSpace::EqPair(None)),
+                Box::new(DynSpace::Reversed(Box::new(DynSpace::Dimension(None)))),
+            )),
+        );
+        let dim = DynDim::Tuple(vec![3.into(), DynDim::Tuple(vec![2.into(), 5.into()])]);
+        check::<Either<Pair, (EqPair, Reversed<Dimension>)>>(&x, &dim,
+            &(3usize.into(), (2usize.into(), 5usize.into())));
+        assert_eq!(x.count(&dim), 18usize.into());
+        let pos = DynPos::Snd(Box::new(DynPos::Tuple(vec![
+            DynPos::Tuple(vec![0.into(), 0.into()]),
+            4.into(),
+        ])));
+        assert_eq!(x.to_index(&dim, &pos), 3usize.into());
+    }
+
+    #[test]
+    fn orders_and_views() {
+        let lex = |x| DynSpace::Lex(Box::new(x));
+        let colex = |x| DynSpace::Colex(Box::new(x));
+        check::<Lex<Pair>>(&lex(DynSpace::Pair(None)), &4.into(), &4usize.into());
+        check::<Colex<NeqPair>>(&colex(DynSpace::NeqPair(None)), &3.into(), &3usize.into());
+        check::<Lex<PowerSet>>(&lex(DynSpace::PowerSet(None)), &3.into(), &3usize.into());
+        check::<Colex<Permutation>>(&colex(DynSpace::Permutation(None)), &3.into(), &3usize.into());
+
+        let x = DynSpace::Slice(Box::new(DynSpace::Pair(None)));
+        let dim: DynDim = "(5, (2, 7))".parse().unwrap();
+        check::<Slice<Pair>>(&x, &dim, &(5usize.into(), 2usize.into()..7usize.into()));
+        let x = DynSpace::Pair(of(DynSpace::Stride(Box::new(DynSpace::Dimension(None)))));
+        let dim: DynDim = "(7, 3)".parse().unwrap();
+        check::<Pair<Of<Stride<Dimension>>>>(&x, &dim, &(7usize.into(), 3usize.into()));
+    }
+
+    #[test]
+    fn errors() {
+        let x = DynSpace::Pair(of(DynSpace::Pair(None)));
+        assert_eq!(x.try_count(&list(&[3])).unwrap_err(), "Expected number");
+        let x = DynSpace::Tuple(Box::new(DynSpace::Pair(None)), Box::new(DynSpace::Pair(None)));
+        assert_eq!(x.try_count(&3.into()).unwrap_err(), "Expected tuple of 2 dimensions");
+        let big: DynDim = "(123456789012345678901234567890, 2)".parse().unwrap();
+        assert_eq!(DynSpace::Homotopy(None).try_count(&big).unwrap_err(),
+            "Number 123456789012345678901234567890 is too large");
+        let pos: DynPos = "(0, 1, 2)".parse().unwrap();
+        assert_eq!(DynSpace::Pair(None).try_to_index(&4.into(), &pos).unwrap_err(),
+            "Expected tuple of 2 positions");
+        let mut pos = DynSpace::Pair(None).zero(&4.into());
+        assert_eq!(DynSpace::Pair(None).try_to_pos(&4.into(), 6usize.into(), &mut pos).unwrap_err(),
+            "Index 6 is out of range, the count is 6");
+
+        let x = DynSpace::Lex(Box::new(DynSpace::Dimension(None)));
+        assert_eq!(x.try_count(&list(&[2])).unwrap_err(), "`Lex` does not support `Dimension`");
+
+        let x = DynSpace::Slice(Box::new(DynSpace::Pair(None)));
+        let e = |s: &str| x.try_count(&s.parse().unwrap()).unwrap_err();
+        assert_eq!(e("(4, (3, 2))"), "Slice range ends before it starts");
+        assert_eq!(e("(4, (0, 7))"), "Slice range ends after the space");
+        assert_eq!(e("(4, 2)"), "Expected range of 2 numbers");
+        let dim = "(4, (1, 3))".parse().unwrap();
+        let pos = "(2, 3)".parse().unwrap();
+        assert_eq!(x.try_to_index(&dim, &pos).unwrap_err(), "Position `(2, 3)` is not in `Slice<Pair>`");
+        let x = DynSpace::Stride(Box::new(DynSpace::Pair(None)));
+        assert_eq!(x.try_count(&"(4, 0)".parse().unwrap()).unwrap_err(), "Stride step is zero");
+    }
+
+    #[test]
+    fn positions() {
+        let e = |x: &str, dim: &str, pos: &str| {
+            let x: DynSpace = x.parse().unwrap();
+            x.try_to_index(&dim.parse().unwrap(), &pos.parse().unwrap())
+        };
+        let not_in = |pos: &str, x: &str| Err(format!("Position `{}` is not in `{}`", pos, x));
+        assert_eq!(e("Permutation", "3", "[0, 0, 5]"), not_in("[0, 0, 5]", "Permutation"));
+        assert_eq!(e("Permutation", "3", "[0, 0, 1]"), not_in("[0, 0, 1]", "Permutation"));
+        assert_eq!(e("Permutation", "3", "[0, 1]"), not_in("[0, 1]", "Permutation"));
+        assert_eq!(e("Pair", "4", "(2, 1)"), not_in("(2, 1)", "Pair"));
+        assert_eq!(e("Pair<Of<Pair>>", "4", "((0, 1), 5)"), Err("Expected tuple of 2 positions".into()));
+        assert_eq!(e("Pair<Of<PowerSet>>", "3", "([1], [2, 0])"), not_in("[2, 0]", "PowerSet"));
+        assert_eq!(e("Context", "[2, 3]", "([0, 1], 2, 1)"), not_in("([0, 1], 2, 1)", "Context"));
+        assert_eq!(e("KContext", "([2, 2], 1)", "([0, 0], [3], [1])"),
+            not_in("([0, 0], [3], [1])", "KContext"));
+        assert_eq!(e("Homotopy", "(0, 3)", "Path(0, 1)"), not_in("Path(0, 1)", "Homotopy"));
+        assert_eq!(e("PermutationContext", "3", "([2, 1, 0], 1, 0)"), Ok(0usize.into()));
+        assert_eq!(e("Slice<Permutation>", "(3, (1, 2))", "[2, 2, 2]"),
+            not_in("[2, 2, 2]", "Permutation"));
+        assert_eq!(e("Pair", "0", "(0, 1)"), not_in("(0, 1)", "Pair"));
+
+        let big = |n: &str| DynDim::Nat(n.parse().unwrap());
+        assert_eq!(DynSpace::Permutation(None).try_count(&big("100000000000000000000")).unwrap_err(),
+            "Number 100000000000000000000 is too large");
+        assert_eq!(DynSpace::GrayPowerSet.try_count(&big("100000000000000000000000")).unwrap_err(),
+            "Number 100000000000000000000000 is too large");
+        let x: DynSpace = "Permutation<Of<Pair>>".parse().unwrap();
+        assert_eq!(x.try_count(&big("10000000000")).unwrap_err(),
+            "Number 49999999995000000000 is too large");
+        assert_eq!(DynSpace::Pair(None).try_count(&0.into()), Ok(0usize.into()));
+        assert_eq!(DynSpace::Context(None).try_count(&list(&[])).unwrap_err(), "Expected non-empty list");
+    }
+
+    #[test]
+    fn big() {
+        let x = DynSpace::Permutation(of(DynSpace::Pair(None)));
+        let dim: DynDim = 100.into();
+        let count = x.count(&dim);
+        let s: Permutation<Of<Pair>> = Construct::new();
+        assert_eq!(count, s.count(&100usize.into()));
+        let index = &count - 1usize;
+        let mut pos = x.zero(&dim);
+        x.to_pos(&dim, index.clone(), &mut pos);
+        assert_eq!(x.to_index(&dim, &pos), index);
+    }
+
+    #[test]
+    fn parse() {
+        for s in [
+            "Pair<Of<PowerSet<Of<Permutation>>>>",
+            "(DimensionN, Either<Context, DimensionN>)",
+            "Reversed<Homotopy<Of<(Pair, GrayPowerSet)>>>",
+            "PermutationContext<Adjacent>",
+            "Pair<Of<Lex<Pair>>>",
+            "(Colex<PowerSet>, Slice<Stride<DimensionN>>)",
+        ] {
+            let x: DynSpace = s.parse().unwrap();
+            assert_eq!(x.to_string(), s);
+        }
+        let x: DynSpace = " PowerSet < Of < Pair<Data> > >".parse().unwrap();
+        assert_eq!(x, DynSpace::PowerSet(of(DynSpace::Pair(None))));
+        let x: DynSpace = "PermutationContext<All>".parse().unwrap();
+        assert_eq!(x.to_string(), "PermutationContext");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let e = |s: &str| {
+            let e = s.parse::<DynSpace>().unwrap_err();
+            (e.offset, e.message)
+        };
+        assert_eq!(e("Pair<Of<Foo>>"), (8, "Unknown space `Foo`".into()));
+        assert_eq!(e("Pair<Data, Data>"), (0, "`Pair` expects 1 argument, found 2".into()));
+        assert_eq!(e("Either<Pair>"), (0, "`Either` expects 2 arguments, found 1".into()));
+        assert_eq!(e("Pair<Pair>"), (5, "Expected `Data` or `Of<...>` in `Pair`".into()));
+        assert_eq!(e("Either<Pair, Data>"), (13, "Expected a space in `Either`".into()));
+        assert_eq!(e("(Pair Pair)"), (6, "Expected `,`".into()));
+        assert_eq!(e("Pair<Of<Pair>"), (13, "Expected `,` or `>`".into()));
+        assert_eq!(e("Pair Pair"), (5, "Unexpected trailing characters".into()));
+        assert_eq!(e(""), (0, "Expected space".into()));
+        assert_eq!(e("Lex<Dimension>"), (4, "`Lex` supports `Pair`, `EqPair`, `NeqPair`, \
+            `SqPair`, `Permutation` and `PowerSet`".into()));
+        assert_eq!(e("Slice<Pair, Pair>"), (0, "`Slice` expects 1 argument, found 2".into()));
+        assert_eq!(e(&"Reversed<".repeat(10000)), (64 * 9, "Spaces are nested too deeply".into()));
+        assert_eq!(e(&"(".repeat(10000)), (64, "Spaces are nested too deeply".into()));
+
+        let deep = "[".repeat(10000);
+        assert_eq!(deep.parse::<DynDim>().unwrap_err().message, "Dimensions are nested too deeply");
+        assert_eq!(deep.parse::<DynPos>().unwrap_err().message, "Positions are nested too deeply");
+        let deep = "Fst(".repeat(10000);
+        assert_eq!(deep.parse::<DynPos>().unwrap_err().message, "Positions are nested too deeply");
+
+        // The deepest nesting that is parsed can be used without overflowing the stack.
+        let n = MAX_DEPTH - 1;
+        let x: DynSpace = format!("{}Dimension{}", "DimensionN<Of<".repeat(n), ">>".repeat(n)).parse().unwrap();
+        let dim: DynDim = format!("{}2{}", "[".repeat(n), "]".repeat(n)).parse().unwrap();
+        let pos: DynPos = format!("{}1{}", "[".repeat(n), "]".repeat(n)).parse().unwrap();
+        assert_eq!(x.count(&dim), 2usize.into());
+        assert_eq!(x.to_index(&dim, &pos), 1usize.into());
+        let mut p = x.zero(&dim);
+        x.to_pos(&dim, 1usize.into(), &mut p);
+        assert_eq!(p, pos);
+    }
+
+    #[test]
+    fn parse_values() {
+        let dim: DynDim = "(2, [3, 4])".parse().unwrap();
+        assert_eq!(dim, DynDim::Tuple(vec![2.into(), list(&[3, 4])]));
+        assert_eq!(dim.to_string(), "(2, [3, 4])");
+        assert_eq!("[]".parse::<DynDim>().unwrap(), DynDim::List(vec![]));
+        assert_eq!("[1,]".parse::<DynDim>().unwrap_err().offset, 3);
+        assert_eq!("[1 2]".parse::<DynDim>().unwrap_err().message, "Expected `,` or `]`");
+
+        let s = "Snd([Path(0, Path(1, 2)), (3, Fst(4))])";
+        let pos: DynPos = s.parse().unwrap();
+        assert_eq!(pos.to_string(), s);
+        let big = "123456789012345678901234567890";
+        assert_eq!(big.parse::<DynPos>().unwrap().to_string(), big);
+        assert_eq!("Fst 1".parse::<DynPos>().unwrap_err().message, "Expected `(`");
+    }
+}
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use std::ops::{Add, Sub};
 
-use crate::Construct;
+use crate::{Construct, Scratch};
 use crate::space::Space;
 
 /// Selects between two spaces.
@@ -75,4 +75,62 @@ impl<T, U, N> Space<N> for Either<T, U>
             *pos = Select::Snd(zero)
         }
     }
+    fn try_to_index(&self, (dim_t, dim_u): &Self::Dim, s: &Self::Pos) -> Option<N> {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        match s {
+            Select::Fst(pt) => t.try_to_index(dim_t, pt),
+            Select::Snd(pu) => Some(t.count(dim_t) + u.try_to_index(dim_u, pu)?),
+        }
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim_t, dim_u): &Self::Dim,
+        s: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        match s {
+            Select::Fst(pt) => t.to_index_with(scratch, dim_t, pt, index),
+            Select::Snd(pu) => {
+                u.to_index_with(scratch, dim_u, pu, index);
+                let x = std::mem::replace(index, 0usize.into());
+                *index = x + t.count(dim_t);
+            }
+        }
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim_t, dim_u): &Self::Dim,
+        ind: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let count = t.count(dim_t);
+        if *ind < count {
+            if let Select::Snd(_) = pos {
+                *pos = Select::Fst(t.zero(dim_t));
+            }
+            let Select::Fst(pt) = pos else {unreachable!()};
+            t.to_pos_with(scratch, dim_t, ind, pt);
+        } else {
+            if let Select::Fst(_) = pos {
+                *pos = Select::Snd(u.zero(dim_u));
+            }
+            let Select::Snd(pu) = pos else {unreachable!()};
+            let mut rest = scratch.take();
+            rest.clone_from(ind);
+            let rest = rest - count;
+            u.to_pos_with(scratch, dim_u, &rest, pu);
+            scratch.put(rest);
+        }
+    }
 }
@@ -5,6 +5,15 @@ use crate::{
     Construct,
     Data,
     Of,
+    Scratch,
+    pair::{
+        biguint_triangular_root,
+        biguint_triangular_root_with,
+        biguint_triangular_with,
+        triangular,
+        triangular_root,
+    },
+    prepared::Prepare,
     space::Space,
 };
 
@@ -21,12 +30,11 @@ impl Space<usize> for EqPair<Data> {
     fn count(&self, dim: &usize) -> usize { dim * (dim + 1) / 2 }
     fn zero(&self, _dim: &usize) -> (usize, usize) { (0, 0) }
     fn to_index(&self, _dim: &usize, &(min, max): &(usize, usize)) -> usize {
-        min + max * (max + 1) / 2
+        min + triangular(max)
     }
     fn to_pos(&self, _dim: &usize, index: usize, pos: &mut (usize, usize)) {
-        let max = ((-1f64 + (8f64 * index as f64 + 1f64).sqrt()) / 2f64) as usize;
-        let min = index - max * (max + 1) / 2;
-        *pos = (min, max)
+        let (max, t) = triangular_root(index);
+        *pos = (index - t, max)
     }
 }
 
@@ -39,9 +47,31 @@ impl Space<BigUint> for EqPair<Data> {
         min + max * (max + 1usize) / 2usize
     }
     fn to_pos(&self, _dim: &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
-        let max: BigUint = ((8usize * &index + 1usize).sqrt() - 1usize) / 2usize;
-        let min: BigUint = index - &max * (&max + 1usize) / 2usize;
-        *pos = (min, max)
+        let (max, t) = biguint_triangular_root(&index);
+        *pos = (index - t, max)
+    }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        (min, max): &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        biguint_triangular_with(max, index);
+        *index += min;
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        index: &BigUint,
+        (min, max): &mut Self::Pos,
+    ) {
+        let mut t = scratch.take();
+        biguint_triangular_root_with(index, max, &mut t);
+        min.clone_from(index);
+        *min -= &t;
+        scratch.put(t);
     }
 }
 
@@ -86,6 +116,93 @@ impl<N, T> Space<N> for EqPair<Of<T>>
         of.to_pos(dim, pair_min, min);
         of.to_pos(dim, pair_max, max);
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        (min, max): &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: EqPair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        of.to_index_with(scratch, dim, min, &mut pair.0);
+        of.to_index_with(scratch, dim, max, &mut pair.1);
+        data.to_index_with(scratch, &of.count(dim), &pair, index);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        (min, max): &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: EqPair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        data.to_pos_with(scratch, &of.count(dim), index, &mut pair);
+        of.to_pos_with(scratch, dim, &pair.0, min);
+        of.to_pos_with(scratch, dim, &pair.1, max);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, (min, max): &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let data: EqPair<Data> = Construct::new();
+        let min = of.try_to_index(dim, min)?;
+        let max = of.try_to_index(dim, max)?;
+        Some(data.to_index(&self.count(dim), &(min, max)))
+    }
+}
+
+impl<N, T> Prepare<N> for EqPair<Of<T>>
+    where T: Prepare<N>,
+          EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+{
+    /// Tables of the inner space, followed by its count.
+    type Tables = (T::Tables, N);
+    fn tables(&self, dim: &Self::Dim) -> Self::Tables {
+        let of: T = Construct::new();
+        let tables = of.tables(dim);
+        let count = of.prepared_count(dim, &tables);
+        (tables, count)
+    }
+    fn prepared_count(&self, _dim: &Self::Dim, (_, count): &Self::Tables) -> N {
+        let data: EqPair<Data> = Construct::new();
+        data.count(count)
+    }
+    fn prepared_to_index(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        (min, max): &Self::Pos,
+    ) -> N {
+        let of: T = Construct::new();
+        let data: EqPair<Data> = Construct::new();
+        let min = of.prepared_to_index(dim, tables, min);
+        let max = of.prepared_to_index(dim, tables, max);
+        data.to_index(count, &(min, max))
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        index: N,
+        (min, max): &mut Self::Pos,
+    ) {
+        let of: T = Construct::new();
+        let data: EqPair<Data> = Construct::new();
+        let mut pair = data.zero(count);
+        data.to_pos(count, index, &mut pair);
+        of.prepared_to_pos(dim, tables, pair.0, min);
+        of.prepared_to_pos(dim, tables, pair.1, max);
+    }
 }
 
 #[cfg(test)]
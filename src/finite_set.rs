@@ -0,0 +1,156 @@
+use std::marker::PhantomData;
+use std::convert::TryInto;
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Of,
+    unbounded::Unbounded,
+};
+
+/// An unbounded space of all finite sets of natural numbers,
+/// using the binary encoding.
+///
+/// Position is a list of numbers, index is the sum of `2^x` for each `x`.
+/// Unlike `PowerSet`, there is no upper bound on the elements.
+/// Elements are listed in increasing order by `to_pos`.
+pub struct FiniteSet<T = Data>(PhantomData<T>);
+
+impl<T> Construct for FiniteSet<T> {
+    fn new() -> Self { FiniteSet(PhantomData) }
+}
+
+impl Unbounded<usize> for FiniteSet<Data> {
+    type Pos = Vec<usize>;
+    fn zero(&self) -> Vec<usize> { vec![] }
+    fn to_index(&self, pos: &Vec<usize>) -> usize {
+        let mut index = 0;
+        for &x in pos {
+            index |= u32::try_from(x).ok().and_then(|x| 1usize.checked_shl(x))
+                .expect("Index too large for `usize`, use `BigUint`");
+        }
+        index
+    }
+    fn to_pos(&self, mut index: usize, pos: &mut Vec<usize>) {
+        pos.clear();
+        while index != 0 {
+            pos.push(index.trailing_zeros() as usize);
+            index &= index - 1;
+        }
+    }
+}
+
+impl Unbounded<BigUint> for FiniteSet<Data> {
+    type Pos = Vec<BigUint>;
+    fn zero(&self) -> Vec<BigUint> { vec![] }
+    fn to_index(&self, pos: &Vec<BigUint>) -> BigUint {
+        let mut index: BigUint = 0usize.into();
+        for x in pos {
+            index.set_bit(x.try_into().unwrap(), true);
+        }
+        index
+    }
+    fn to_pos(&self, index: BigUint, pos: &mut Vec<BigUint>) {
+        pos.clear();
+        for (i, mut digit) in index.iter_u64_digits().enumerate() {
+            while digit != 0 {
+                pos.push((i as u64 * 64 + digit.trailing_zeros() as u64).into());
+                digit &= digit - 1;
+            }
+        }
+    }
+}
+
+impl<N, T> Unbounded<N> for FiniteSet<Of<T>>
+    where T: Unbounded<N>,
+          FiniteSet<Data>: Unbounded<N, Pos = Vec<N>>,
+{
+    type Pos = Vec<T::Pos>;
+    fn zero(&self) -> Self::Pos { vec![] }
+    fn to_index(&self, pos: &Self::Pos) -> N {
+        let of: T = Construct::new();
+        let data: FiniteSet<Data> = Construct::new();
+        data.to_index(&pos.iter().map(|x| of.to_index(x)).collect())
+    }
+    fn to_pos(&self, index: N, pos: &mut Self::Pos) {
+        let of: T = Construct::new();
+        let data: FiniteSet<Data> = Construct::new();
+        let mut set = data.zero();
+        data.to_pos(index, &mut set);
+        pos.clear();
+        for x in set {
+            let mut p = of.zero();
+            of.to_pos(x, &mut p);
+            pos.push(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn data() {
+        let x: FiniteSet = Construct::new();
+        let index = |pos: &Vec<usize>| -> usize { x.to_index(pos) };
+        assert_eq!(index(&vec![]), 0);
+        assert_eq!(index(&vec![0]), 1);
+        assert_eq!(index(&vec![1]), 2);
+        assert_eq!(index(&vec![0, 1]), 3);
+        let mut pos = vec![];
+        x.to_pos(9usize, &mut pos);
+        assert_eq!(pos, vec![0, 3]);
+        for i in 0..1000 {
+            x.to_pos(i, &mut pos);
+            assert_eq!(index(&pos), i);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Index too large for `usize`")]
+    fn too_large() {
+        let x: FiniteSet = Construct::new();
+        Unbounded::<usize>::to_index(&x, &vec![64]);
+    }
+
+    fn conv(v: Vec<usize>) -> Vec<BigUint> {
+        v.into_iter().map(|n| n.into()).collect()
+    }
+
+    #[test]
+    fn data_big() {
+        let x: FiniteSet = Construct::new();
+        let index: BigUint = x.to_index(&conv(vec![0, 3]));
+        assert_eq!(index, 9usize.into());
+        let pos = conv(vec![1, 64, 200]);
+        let index: BigUint = x.to_index(&pos);
+        let mut new_pos = vec![];
+        x.to_pos(index, &mut new_pos);
+        assert_eq!(new_pos, pos);
+    }
+
+    #[test]
+    fn of() {
+        let x: FiniteSet<Of<Cantor>> = Construct::new();
+        let index: usize = x.to_index(&vec![(0, 0), (0, 1)]);
+        assert_eq!(index, 5);
+        let mut pos = vec![];
+        x.to_pos(5usize, &mut pos);
+        assert_eq!(pos, vec![(0, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn of_big() {
+        let x: FiniteSet<Of<Cantor>> = Construct::new();
+        let pos: Vec<(BigUint, BigUint)> = vec![
+            (0usize.into(), 0usize.into()),
+            (10usize.into(), 10usize.into()),
+        ];
+        let index: BigUint = x.to_index(&pos);
+        let mut new_pos = vec![];
+        x.to_pos(index, &mut new_pos);
+        assert_eq!(new_pos, pos);
+    }
+}
@@ -0,0 +1,225 @@
+use std::marker::PhantomData;
+use std::ops::{Div, Rem};
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Scratch,
+    space::Space,
+};
+
+/// Same as `DimensionN`, but ordered by the reflected mixed-radix Gray code.
+///
+/// Dimension is a list of numbers, position is a list of numbers.
+/// Consecutive indices differ by a single step along one coordinate.
+///
+/// The last coordinate changes slowest.
+/// Whenever a coordinate has an odd value,
+/// the coordinates before it are traversed in reverse.
+pub struct GrayDimensionN<T = Data>(PhantomData<T>);
+
+impl<T> Construct for GrayDimensionN<T> {
+    fn new() -> Self { GrayDimensionN(PhantomData) }
+}
+
+impl GrayDimensionN<Data> {
+    /// Returns the coordinate that steps going from `index - 1` to `index`.
+    ///
+    /// Returns `None` for index zero.
+    pub fn changed_coordinate<N>(&self, dim: &[N], index: &N) -> Option<usize>
+        where N: Clone + From<usize> + PartialEq,
+              for<'a> &'a N: Rem<&'a N, Output = N> + Div<&'a N, Output = N>,
+    {
+        // The coordinate of the lowest non-zero digit, since the digits below it wrapped.
+        let zero: N = 0usize.into();
+        let mut rest = index.clone();
+        for (i, d) in dim.iter().enumerate() {
+            if &rest % d != zero {return Some(i)}
+            rest = &rest / d;
+        }
+        None
+    }
+}
+
+impl Space<usize> for GrayDimensionN<Data> {
+    type Dim = Vec<usize>;
+    type Pos = Vec<usize>;
+    fn count(&self, dim: &Vec<usize>) -> usize {
+        dim.iter().product()
+    }
+    fn zero(&self, dim: &Vec<usize>) -> Vec<usize> {
+        vec![0; dim.len()]
+    }
+    fn to_index(&self, dim: &Vec<usize>, pos: &Vec<usize>) -> usize {
+        let mut index = 0;
+        let mut reflect = false;
+        for i in (0..dim.len()).rev() {
+            let digit = if reflect {dim[i] - 1 - pos[i]} else {pos[i]};
+            reflect ^= pos[i] % 2 == 1;
+            index = index * dim[i] + digit;
+        }
+        index
+    }
+    fn to_pos(&self, dim: &Vec<usize>, mut index: usize, pos: &mut Vec<usize>) {
+        pos.clear();
+        for &d in dim {
+            pos.push(index % d);
+            index /= d;
+        }
+        let mut reflect = false;
+        for i in (0..dim.len()).rev() {
+            if reflect {pos[i] = dim[i] - 1 - pos[i]}
+            reflect ^= pos[i] % 2 == 1;
+        }
+    }
+}
+
+impl Space<BigUint> for GrayDimensionN<Data> {
+    type Dim = Vec<BigUint>;
+    type Pos = Vec<BigUint>;
+    fn count(&self, dim: &Vec<BigUint>) -> BigUint {
+        dim.iter().product()
+    }
+    fn zero(&self, dim: &Vec<BigUint>) -> Vec<BigUint> {
+        vec![0usize.into(); dim.len()]
+    }
+    fn to_index(&self, dim: &Vec<BigUint>, pos: &Vec<BigUint>) -> BigUint {
+        let mut index: BigUint = 0usize.into();
+        let mut reflect = false;
+        for i in (0..dim.len()).rev() {
+            index *= &dim[i];
+            if reflect {
+                index += &dim[i] - 1usize - &pos[i];
+            } else {
+                index += &pos[i];
+            }
+            reflect ^= pos[i].bit(0);
+        }
+        index
+    }
+    fn to_pos(&self, dim: &Vec<BigUint>, mut index: BigUint, pos: &mut Vec<BigUint>) {
+        pos.clear();
+        for d in dim {
+            pos.push(&index % d);
+            index /= d;
+        }
+        let mut reflect = false;
+        for i in (0..dim.len()).rev() {
+            if reflect {pos[i] = &dim[i] - 1usize - &pos[i]}
+            reflect ^= pos[i].bit(0);
+        }
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Vec<BigUint>,
+        pos: &Vec<BigUint>,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
+
+        let mut t = scratch.take();
+        index.set_zero();
+        let mut reflect = false;
+        for i in (0..dim.len()).rev() {
+            *index *= &dim[i];
+            if reflect {
+                t.clone_from(&dim[i]);
+                t -= 1u32;
+                t -= &pos[i];
+                *index += &t;
+            } else {
+                *index += &pos[i];
+            }
+            reflect ^= pos[i].bit(0);
+        }
+        scratch.put(t);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Vec<BigUint>,
+        index: &BigUint,
+        pos: &mut Vec<BigUint>,
+    ) {
+        let mut rest = scratch.take();
+        let mut t = scratch.take();
+        rest.clone_from(index);
+        pos.resize(dim.len(), 0usize.into());
+        for (p, d) in pos.iter_mut().zip(dim) {
+            p.clone_from(&rest);
+            *p %= d;
+            rest /= d;
+        }
+        let mut reflect = false;
+        for i in (0..dim.len()).rev() {
+            if reflect {
+                t.clone_from(&dim[i]);
+                t -= 1u32;
+                t -= &pos[i];
+                std::mem::swap(&mut pos[i], &mut t);
+            }
+            reflect ^= pos[i].bit(0);
+        }
+        scratch.put(rest);
+        scratch.put(t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, GrayDimensionN>();
+        is_complete::<BigUint, GrayDimensionN>();
+    }
+
+    #[test]
+    fn data() {
+        let x: GrayDimensionN = Construct::new();
+        let ref dim = vec![3, 2, 4];
+        assert_eq!(x.count(dim), 24);
+        let mut prev = x.zero(dim);
+        let mut pos = x.zero(dim);
+        for i in 0..24 {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+            if i > 0 {
+                // Exactly one coordinate steps by one.
+                let c = x.changed_coordinate(dim, &i).unwrap();
+                for j in 0..3 {
+                    if j == c {
+                        assert_eq!(prev[j].abs_diff(pos[j]), 1);
+                    } else {
+                        assert_eq!(prev[j], pos[j]);
+                    }
+                }
+            }
+            prev.clone_from(&pos);
+        }
+        assert_eq!(x.changed_coordinate(dim, &0), None);
+        x.to_pos(dim, 3, &mut pos);
+        assert_eq!(pos, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn data_big() {
+        let x: GrayDimensionN = Construct::new();
+        let conv = |v: &[usize]| v.iter().map(|&a| a.into()).collect::<Vec<BigUint>>();
+        let ref dim = conv(&[3, 2, 4]);
+        let ref small_dim = vec![3, 2, 4];
+        let mut pos = x.zero(dim);
+        let mut small_pos = x.zero(small_dim);
+        for i in 0..24usize {
+            x.to_pos(dim, i.into(), &mut pos);
+            x.to_pos(small_dim, i, &mut small_pos);
+            assert_eq!(pos, conv(&small_pos));
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+        let i: BigUint = 12usize.into();
+        assert_eq!(x.changed_coordinate(dim, &i), Some(2));
+    }
+}
@@ -0,0 +1,199 @@
+use std::marker::PhantomData;
+use std::convert::TryInto;
+
+use crate::{
+    BigUint,
+    Bits,
+    Construct,
+    Data,
+    Scratch,
+    scratch::set_small_list,
+    space::Space,
+};
+
+/// Same as `PowerSet`, but ordered by the reflected binary Gray code.
+///
+/// Dimension is natural number, position is a list of numbers.
+/// Consecutive indices differ by adding or removing a single number.
+pub struct GrayPowerSet<T = Data>(PhantomData<T>);
+
+impl<T> Construct for GrayPowerSet<T> {
+    fn new() -> Self { GrayPowerSet(PhantomData) }
+}
+
+impl GrayPowerSet<Data> {
+    /// Returns the number added or removed going from `index - 1` to `index`.
+    ///
+    /// Returns `None` for index zero.
+    pub fn changed_coordinate<N: Bits>(&self, index: &N) -> Option<usize> {
+        index.ones().next()
+    }
+}
+
+impl Space<usize> for GrayPowerSet<Data> {
+    type Dim = usize;
+    type Pos = Vec<usize>;
+    fn count(&self, dim: &usize) -> usize {
+        1 << *dim
+    }
+    fn zero(&self, _dim: &usize) -> Vec<usize> {
+        vec![]
+    }
+    fn to_index(&self, _dim: &usize, pos: &Vec<usize>) -> usize {
+        let mut index = 0;
+        for &i in pos {
+            index |= 1 << i;
+        }
+        // Each bit is the parity of the Gray code bits above it.
+        let mut shift = 1;
+        while shift < usize::BITS {
+            index ^= index >> shift;
+            shift <<= 1;
+        }
+        index
+    }
+    fn to_pos(&self, dim: &usize, index: usize, pos: &mut Vec<usize>) {
+        let gray = index ^ (index >> 1);
+        pos.clear();
+        pos.extend(gray.ones().take_while(|i| i < dim));
+    }
+}
+
+impl Space<BigUint> for GrayPowerSet<Data> {
+    type Dim = BigUint;
+    type Pos = Vec<BigUint>;
+    fn count(&self, dim: &BigUint) -> BigUint {
+        BigUint::power_of_two(dim.try_into().unwrap())
+    }
+    fn zero(&self, _dim: &BigUint) -> Vec<BigUint> {
+        vec![]
+    }
+    fn to_index(&self, _dim: &BigUint, pos: &Vec<BigUint>) -> BigUint {
+        let mut gray: BigUint = 0usize.into();
+        for i in pos {
+            Bits::set_bit(&mut gray, i.try_into().unwrap(), true);
+        }
+        // Each bit is the parity of the Gray code bits above it,
+        // computed one digit at a time from the most significant one.
+        let mut digits = gray.to_u32_digits();
+        let mut parity = 0;
+        for d in digits.iter_mut().rev() {
+            let mut x = *d;
+            let mut shift = 1;
+            while shift < u32::BITS {
+                x ^= x >> shift;
+                shift <<= 1;
+            }
+            if parity == 1 {x = !x}
+            parity = x & 1;
+            *d = x;
+        }
+        BigUint::new(digits)
+    }
+    fn to_pos(&self, dim: &BigUint, index: BigUint, pos: &mut Vec<BigUint>) {
+        let dim: usize = dim.try_into().unwrap();
+        let gray: BigUint = &index ^ (&index >> 1usize);
+        pos.clear();
+        pos.extend(gray.ones().take_while(|&i| i < dim).map(BigUint::from));
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        _dim: &BigUint,
+        pos: &Vec<BigUint>,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
+
+        index.set_zero();
+        for i in pos {
+            Bits::set_bit(index, i.try_into().unwrap(), true);
+        }
+        // Each bit is the parity of the Gray code bits above it,
+        // computed by doubling the shift until it covers all bits.
+        let mut t = scratch.take();
+        let mut shift = 1;
+        while shift < index.bits() {
+            t.clone_from(index);
+            t >>= shift;
+            *index ^= &t;
+            shift <<= 1;
+        }
+        scratch.put(t);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &BigUint,
+        index: &BigUint,
+        pos: &mut Vec<BigUint>,
+    ) {
+        let dim: usize = dim.try_into().unwrap();
+        let mut gray = scratch.take();
+        gray.clone_from(index);
+        gray >>= 1;
+        gray ^= index;
+        set_small_list(pos, gray.ones().take_while(|&i| i < dim));
+        scratch.put(gray);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, GrayPowerSet>();
+        is_complete::<BigUint, GrayPowerSet>();
+    }
+
+    #[test]
+    fn data() {
+        let x: GrayPowerSet = Construct::new();
+        let ref dim = 5;
+        assert_eq!(x.count(dim), 32);
+        let mut prev = vec![];
+        let mut pos = vec![];
+        for i in 0..32 {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+            if i > 0 {
+                // Exactly one number is added or removed.
+                let c = x.changed_coordinate(&i).unwrap();
+                let diff: Vec<usize> = (0..5).filter(|a| prev.contains(a) != pos.contains(a)).collect();
+                assert_eq!(diff, vec![c]);
+            }
+            prev = pos.clone();
+        }
+        assert_eq!(x.changed_coordinate(&0usize), None);
+        x.to_pos(dim, 31, &mut pos);
+        assert_eq!(pos, vec![4]);
+    }
+
+    #[test]
+    fn data_big() {
+        let x: GrayPowerSet = Construct::new();
+        let ref dim: BigUint = 200usize.into();
+        let count: BigUint = x.count(dim);
+        let mut pos = vec![];
+        let last = &count - 1usize;
+        x.to_pos(dim, last.clone(), &mut pos);
+        assert_eq!(pos, vec![BigUint::from(199usize)]);
+        assert_eq!(x.to_index(dim, &pos), last);
+
+        let i: BigUint = (BigUint::from(1usize) << 150usize) + 12345usize;
+        x.to_pos(dim, i.clone(), &mut pos);
+        assert_eq!(x.to_index(dim, &pos), i);
+        assert_eq!(x.changed_coordinate(&i), Some(0));
+
+        // Agrees with the `usize` ordering for small indices.
+        let small: GrayPowerSet = Construct::new();
+        let mut small_pos = vec![];
+        for i in 0..64usize {
+            x.to_pos(dim, i.into(), &mut pos);
+            small.to_pos(&6, i, &mut small_pos);
+            assert_eq!(pos, small_pos.iter().map(|&a| BigUint::from(a)).collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::convert::TryInto;
+
+use crate::{
+    BigUint,
+    Construct,
+    unbounded::Unbounded,
+};
+
+/// Stores a hereditarily finite set, which is a finite set of such sets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HFSet(pub Vec<HFSet>);
+
+/// An unbounded space of all hereditarily finite sets,
+/// using the Ackermann encoding.
+///
+/// Position is `HFSet`, index is the sum of `2^index(x)` for each member `x`.
+/// The empty set has index 0.
+/// Members are listed in increasing index order by `to_pos`.
+pub struct HereditarilyFinite;
+
+impl Construct for HereditarilyFinite {
+    fn new() -> Self { HereditarilyFinite }
+}
+
+impl Unbounded<usize> for HereditarilyFinite {
+    type Pos = HFSet;
+    fn zero(&self) -> HFSet { HFSet(vec![]) }
+    /// Panics if a member has index 64 or more, use `BigUint` for such sets.
+    fn to_index(&self, pos: &HFSet) -> usize {
+        let mut index = 0;
+        for x in &pos.0 {
+            let bit = Unbounded::<usize>::to_index(self, x);
+            index |= u32::try_from(bit).ok().and_then(|bit| 1usize.checked_shl(bit))
+                .expect("Index too large for `usize`, use `BigUint`");
+        }
+        index
+    }
+    fn to_pos(&self, mut index: usize, pos: &mut HFSet) {
+        pos.0.clear();
+        while index != 0 {
+            let mut x = HFSet(vec![]);
+            self.to_pos(index.trailing_zeros() as usize, &mut x);
+            pos.0.push(x);
+            index &= index - 1;
+        }
+    }
+}
+
+impl Unbounded<BigUint> for HereditarilyFinite {
+    type Pos = HFSet;
+    fn zero(&self) -> HFSet { HFSet(vec![]) }
+    fn to_index(&self, pos: &HFSet) -> BigUint {
+        let mut index: BigUint = 0usize.into();
+        for x in &pos.0 {
+            let bit: BigUint = self.to_index(x);
+            index.set_bit(bit.try_into().unwrap(), true);
+        }
+        index
+    }
+    fn to_pos(&self, index: BigUint, pos: &mut HFSet) {
+        pos.0.clear();
+        for (i, mut digit) in index.iter_u64_digits().enumerate() {
+            while digit != 0 {
+                let bit = i as u64 * 64 + digit.trailing_zeros() as u64;
+                let mut x = HFSet(vec![]);
+                self.to_pos(BigUint::from(bit), &mut x);
+                pos.0.push(x);
+                digit &= digit - 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn data() {
+        let x: HereditarilyFinite = Construct::new();
+        let empty = HFSet(vec![]);
+        let one = HFSet(vec![empty.clone()]);
+        let two = HFSet(vec![empty.clone(), one.clone()]);
+        assert_eq!(Unbounded::<usize>::to_index(&x, &empty), 0);
+        assert_eq!(Unbounded::<usize>::to_index(&x, &one), 1);
+        assert_eq!(Unbounded::<usize>::to_index(&x, &HFSet(vec![one.clone()])), 2);
+        assert_eq!(Unbounded::<usize>::to_index(&x, &two), 3);
+        let mut pos = Unbounded::<usize>::zero(&x);
+        x.to_pos(3usize, &mut pos);
+        assert_eq!(pos, two);
+        for i in 0usize..1000 {
+            x.to_pos(i, &mut pos);
+            assert_eq!(Unbounded::<usize>::to_index(&x, &pos), i);
+        }
+    }
+
+    #[test]
+    fn data_big() {
+        let x: HereditarilyFinite = Construct::new();
+        let mut pos = Unbounded::<BigUint>::zero(&x);
+        for i in 0usize..1000 {
+            x.to_pos(BigUint::from(i), &mut pos);
+            assert_eq!(Unbounded::<BigUint>::to_index(&x, &pos), i.into());
+        }
+        let big = BigUint::from(1usize) << 100usize;
+        x.to_pos(big.clone(), &mut pos);
+        assert_eq!(Unbounded::<BigUint>::to_index(&x, &pos), big);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index too large for `usize`")]
+    fn too_large() {
+        let x: HereditarilyFinite = Construct::new();
+        let one = HFSet(vec![HFSet(vec![])]);
+        // Has index 6, so the set containing it has index 64.
+        let six = HFSet(vec![one.clone(), HFSet(vec![one])]);
+        let pos = HFSet(vec![HFSet(vec![six])]);
+        Unbounded::<usize>::to_index(&x, &pos);
+    }
+}
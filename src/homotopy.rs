@@ -1,4 +1,6 @@
+use std::fmt;
 use std::marker::PhantomData;
+use std::str::FromStr;
 
 use crate::{
     BigUint,
@@ -6,6 +8,8 @@ use crate::{
     Data,
     Of,
     EqPair,
+    Scratch,
+    prepared::Prepare,
     space::Space
 };
 
@@ -19,6 +23,11 @@ pub enum HPoint<T = usize> {
 }
 
 impl<T> HPoint<T> {
+    /// Creates a path between two higher order points.
+    pub fn path(a: HPoint<T>, b: HPoint<T>) -> HPoint<T> {
+        HPoint::Path(Box::new((a, b)))
+    }
+
     /// Gets the homotopy level of a higher order point for homotopy spaces.
     pub fn level(&self) -> usize {
         use HPoint::*;
@@ -28,6 +37,189 @@ impl<T> HPoint<T> {
             Path(ab) => ab.0.level().max(ab.1.level()) + 1,
         }
     }
+
+    /// Gets the start of a path, or `None` if this is a point.
+    pub fn source(&self) -> Option<&HPoint<T>> {
+        match self {
+            HPoint::Point(_) => None,
+            HPoint::Path(ab) => Some(&ab.0),
+        }
+    }
+
+    /// Gets the end of a path, or `None` if this is a point.
+    pub fn target(&self) -> Option<&HPoint<T>> {
+        match self {
+            HPoint::Point(_) => None,
+            HPoint::Path(ab) => Some(&ab.1),
+        }
+    }
+
+    /// Follows the start of paths down to the specified homotopy level.
+    ///
+    /// Returns the point itself when it is already at or below the level.
+    /// Each path is assumed to be one level above its ends, as in homotopy spaces.
+    pub fn source_at(&self, level: usize) -> &HPoint<T> {
+        let mut p = self;
+        let mut p_level = self.level();
+        while p_level > level {
+            match p {
+                HPoint::Point(_) => break,
+                HPoint::Path(ab) => p = &ab.0,
+            }
+            p_level -= 1;
+        }
+        p
+    }
+
+    /// Follows the end of paths down to the specified homotopy level.
+    ///
+    /// Returns the point itself when it is already at or below the level.
+    /// Each path is assumed to be one level above its ends, as in homotopy spaces.
+    pub fn target_at(&self, level: usize) -> &HPoint<T> {
+        let mut p = self;
+        let mut p_level = self.level();
+        while p_level > level {
+            match p {
+                HPoint::Point(_) => break,
+                HPoint::Path(ab) => p = &ab.1,
+            }
+            p_level -= 1;
+        }
+        p
+    }
+
+    /// Returns `true` if this is a path from a higher order point to itself.
+    pub fn is_loop(&self) -> bool where T: PartialEq {
+        match self {
+            HPoint::Point(_) => false,
+            HPoint::Path(ab) => ab.0 == ab.1,
+        }
+    }
+
+    /// Maps the leaf points, preserving the structure of paths.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, mut f: F) -> HPoint<U> {
+        fn map_mut<T, U, F: FnMut(&T) -> U>(p: &HPoint<T>, f: &mut F) -> HPoint<U> {
+            match p {
+                HPoint::Point(x) => HPoint::Point(f(x)),
+                HPoint::Path(ab) => HPoint::path(map_mut(&ab.0, f), map_mut(&ab.1, f)),
+            }
+        }
+
+        map_mut(self, &mut f)
+    }
+
+    /// Iterates over the leaf points from left to right.
+    pub fn leaves(&self) -> Leaves<'_, T> {
+        Leaves {stack: vec![self]}
+    }
+}
+
+/// Iterates over the leaf points of a higher order point.
+pub struct Leaves<'a, T> {
+    stack: Vec<&'a HPoint<T>>,
+}
+
+impl<'a, T> Iterator for Leaves<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        while let Some(p) = self.stack.pop() {
+            match p {
+                HPoint::Point(x) => return Some(x),
+                HPoint::Path(ab) => {
+                    self.stack.push(&ab.1);
+                    self.stack.push(&ab.0);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Writes a compact format, e.g. `((0,1),(1,1))`.
+impl<T: fmt::Display> fmt::Display for HPoint<T> {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HPoint::Point(x) => write!(w, "{}", x),
+            HPoint::Path(ab) => write!(w, "({},{})", ab.0, ab.1),
+        }
+    }
+}
+
+/// An error when parsing a higher order point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHPointError {
+    /// The byte offset in the string where the error occurred.
+    pub offset: usize,
+    /// Describes what went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for ParseHPointError {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(w, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseHPointError {}
+
+/// Reads the compact format written by `Display`, e.g. `((0,1),(1,1))`.
+///
+/// Whitespace is ignored around points and delimiters.
+/// Points are parsed with `FromStr` and can not contain `(`, `)` or `,`.
+/// Paths can be nested up to 256 levels.
+impl<T: FromStr> FromStr for HPoint<T> {
+    type Err = ParseHPointError;
+    fn from_str(s: &str) -> Result<Self, ParseHPointError> {
+        const MAX_DEPTH: usize = 256;
+
+        fn err<U>(offset: usize, message: &str) -> Result<U, ParseHPointError> {
+            Err(ParseHPointError {offset, message: message.into()})
+        }
+
+        fn skip_ws(s: &str, mut i: usize) -> usize {
+            while let Some(c) = s[i..].chars().next() {
+                if !c.is_whitespace() {break}
+                i += c.len_utf8();
+            }
+            i
+        }
+
+        fn expect(s: &str, i: usize, c: char) -> Result<usize, ParseHPointError> {
+            let i = skip_ws(s, i);
+            if s[i..].starts_with(c) {Ok(i + 1)}
+            else {err(i, &format!("Expected `{}`", c))}
+        }
+
+        fn parse<T: FromStr>(
+            s: &str,
+            i: usize,
+            depth: usize
+        ) -> Result<(HPoint<T>, usize), ParseHPointError> {
+            let i = skip_ws(s, i);
+            if s[i..].starts_with('(') {
+                if depth >= MAX_DEPTH {return err(i, "Paths are nested too deeply")}
+                let (a, i) = parse(s, i + 1, depth + 1)?;
+                let i = expect(s, i, ',')?;
+                let (b, i) = parse(s, i, depth + 1)?;
+                let i = expect(s, i, ')')?;
+                Ok((HPoint::path(a, b), i))
+            } else {
+                let end = s[i..].find(['(', ')', ','])
+                    .map(|n| i + n).unwrap_or(s.len());
+                let token = s[i..end].trim();
+                if token.is_empty() {return err(i, "Expected point")}
+                match token.parse() {
+                    Ok(x) => Ok((HPoint::Point(x), end)),
+                    Err(_) => err(i, &format!("Invalid point `{}`", token)),
+                }
+            }
+        }
+
+        let (p, i) = parse(s, 0, 0)?;
+        let i = skip_ws(s, i);
+        if i < s.len() {return err(i, "Unexpected trailing characters")}
+        Ok(p)
+    }
 }
 
 /// A discrete space that models undirected homotopy paths
@@ -113,6 +305,20 @@ impl Space<usize> for Homotopy<Data> {
             }
         }
     }
+    fn to_index_batch(&self, &(level, n): &(usize, usize), pos: &[HPoint], index: &mut [usize]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let counts = level_counts(level, &n);
+        for (p, i) in pos.iter().zip(index) {
+            *i = prepared_to_index(&counts, level, p);
+        }
+    }
+    fn to_pos_batch(&self, &(level, n): &(usize, usize), index: &[usize], pos: &mut [HPoint]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let counts = level_counts(level, &n);
+        for (&i, p) in index.iter().zip(pos) {
+            prepared_to_pos(&counts, level, i, p);
+        }
+    }
 }
 
 impl Space<BigUint> for Homotopy<Data> {
@@ -184,6 +390,265 @@ impl Space<BigUint> for Homotopy<Data> {
             }
         }
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        (_, n): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        biguint_to_index_with(scratch, n, pos, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        (level, n): &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        biguint_to_pos_with(scratch, &level_counts(*level, n), *level, index, pos)
+    }
+    fn to_index_batch(&self, (level, n): &Self::Dim, pos: &[Self::Pos], index: &mut [BigUint]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let counts = level_counts(*level, n);
+        for (p, i) in pos.iter().zip(index) {
+            *i = prepared_to_index(&counts, *level, p);
+        }
+    }
+    fn to_pos_batch(&self, (level, n): &Self::Dim, index: &[BigUint], pos: &mut [Self::Pos]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let counts = level_counts(*level, n);
+        for (i, p) in index.iter().zip(pos) {
+            prepared_to_pos(&counts, *level, i.clone(), p);
+        }
+    }
+}
+
+/// Counts each homotopy level from 0 up to `level`.
+fn level_counts<N: Clone>(level: usize, n: &N) -> Vec<N>
+    where EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>
+{
+    let s: EqPair = Construct::new();
+    let mut counts = Vec::with_capacity(level + 1);
+    counts.push(n.clone());
+    for i in 0..level {
+        let count = s.count(&counts[i]);
+        counts.push(count);
+    }
+    counts
+}
+
+/// Converts position to index, using the counts of each level.
+fn prepared_to_index<N: Clone + Ord>(counts: &[N], level: usize, pos: &HPoint<N>) -> N
+    where EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>
+{
+    use HPoint::*;
+
+    match pos {
+        Point(x) => x.clone(),
+        Path(ab) => {
+            let a = prepared_to_index(counts, level - 1, &ab.0);
+            let b = prepared_to_index(counts, level - 1, &ab.1);
+            let s: EqPair = Construct::new();
+            if a <= b {s.to_index(&counts[level], &(a, b))}
+            else {s.to_index(&counts[level], &(b, a))}
+        }
+    }
+}
+
+/// Converts index to position, using the counts of each level.
+fn prepared_to_pos<N: From<usize>>(counts: &[N], level: usize, index: N, pos: &mut HPoint<N>)
+    where EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>
+{
+    use HPoint::*;
+
+    if level == 0 {
+        *pos = Point(index);
+        return;
+    }
+    let s: EqPair = Construct::new();
+    let mut ab = (0usize.into(), 0usize.into());
+    // Level 1 uses the number of pieces, like the unprepared space.
+    let count = if level == 1 {&counts[0]} else {&counts[level]};
+    s.to_pos(count, index, &mut ab);
+    let mut a = Point(0usize.into());
+    let mut b = Point(0usize.into());
+    prepared_to_pos(counts, level - 1, ab.0, &mut a);
+    prepared_to_pos(counts, level - 1, ab.1, &mut b);
+    *pos = Path(Box::new((a, b)));
+}
+
+/// Converts position to index, reusing temporary values.
+fn biguint_to_index_with(scratch: &mut Scratch, n: &BigUint, pos: &HPoint<BigUint>, index: &mut BigUint) {
+    use HPoint::*;
+
+    match pos {
+        Point(x) => index.clone_from(x),
+        Path(ab) => {
+            let mut a = scratch.take();
+            let mut b = scratch.take();
+            biguint_to_index_with(scratch, n, &ab.0, &mut a);
+            biguint_to_index_with(scratch, n, &ab.1, &mut b);
+            let pair = if a <= b {(a, b)} else {(b, a)};
+            // `EqPair` does not use the dimension when converting to index.
+            let s: EqPair = Construct::new();
+            s.to_index_with(scratch, n, &pair, index);
+            scratch.put(pair.0);
+            scratch.put(pair.1);
+        }
+    }
+}
+
+/// Converts index to position, reusing temporary values and the boxes of the position.
+fn biguint_to_pos_with(
+    scratch: &mut Scratch,
+    counts: &[BigUint],
+    level: usize,
+    index: &BigUint,
+    pos: &mut HPoint<BigUint>,
+) {
+    use HPoint::*;
+
+    if level == 0 {
+        match pos {
+            Point(x) => x.clone_from(index),
+            Path(_) => *pos = Point(index.clone()),
+        }
+        return;
+    }
+    if let Point(_) = pos {
+        *pos = HPoint::path(Point(0usize.into()), Point(0usize.into()));
+    }
+    let Path(ab) = pos else {unreachable!()};
+    let s: EqPair = Construct::new();
+    let mut pair = (scratch.take(), scratch.take());
+    // Level 1 uses the number of pieces, like `to_pos`.
+    let count = if level == 1 {&counts[0]} else {&counts[level]};
+    s.to_pos_with(scratch, count, index, &mut pair);
+    biguint_to_pos_with(scratch, counts, level - 1, &pair.0, &mut ab.0);
+    biguint_to_pos_with(scratch, counts, level - 1, &pair.1, &mut ab.1);
+    scratch.put(pair.0);
+    scratch.put(pair.1);
+}
+
+/// Converts position of `Homotopy<Of<T>>` to index, reusing temporary values.
+///
+/// `EqPair` does not use the dimension when converting, so `n` is only passed along.
+fn of_to_index_with<N, T>(
+    scratch: &mut Scratch<N>,
+    dim: &T::Dim,
+    n: &N,
+    pos: &HPoint<T::Pos>,
+    index: &mut N,
+)
+    where T: Space<N>,
+          EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + Ord + From<usize>,
+{
+    use HPoint::*;
+
+    match pos {
+        Point(x) => {
+            let of: T = Construct::new();
+            of.to_index_with(scratch, dim, x, index);
+        }
+        Path(ab) => {
+            let mut a = scratch.take();
+            let mut b = scratch.take();
+            of_to_index_with::<N, T>(scratch, dim, n, &ab.0, &mut a);
+            of_to_index_with::<N, T>(scratch, dim, n, &ab.1, &mut b);
+            let pair = if a <= b {(a, b)} else {(b, a)};
+            let s: EqPair = Construct::new();
+            s.to_index_with(scratch, n, &pair, index);
+            scratch.put(pair.0);
+            scratch.put(pair.1);
+        }
+    }
+}
+
+/// Converts index to position of `Homotopy<Of<T>>`,
+/// reusing temporary values and the boxes of the position.
+fn of_to_pos_with<N, T>(
+    scratch: &mut Scratch<N>,
+    dim: &T::Dim,
+    n: &N,
+    level: usize,
+    index: &N,
+    pos: &mut HPoint<T::Pos>,
+)
+    where T: Space<N>,
+          EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + Ord + From<usize>,
+{
+    use HPoint::*;
+
+    let of: T = Construct::new();
+    if level == 0 {
+        if let Path(_) = pos {
+            *pos = Point(of.zero(dim));
+        }
+        let Point(x) = pos else {unreachable!()};
+        of.to_pos_with(scratch, dim, index, x);
+        return;
+    }
+    if let Point(_) = pos {
+        *pos = HPoint::path(Point(of.zero(dim)), Point(of.zero(dim)));
+    }
+    let Path(ab) = pos else {unreachable!()};
+    let s: EqPair = Construct::new();
+    let mut pair = (scratch.take(), scratch.take());
+    s.to_pos_with(scratch, n, index, &mut pair);
+    of_to_pos_with::<N, T>(scratch, dim, n, level - 1, &pair.0, &mut ab.0);
+    of_to_pos_with::<N, T>(scratch, dim, n, level - 1, &pair.1, &mut ab.1);
+    scratch.put(pair.0);
+    scratch.put(pair.1);
+}
+
+impl Prepare<usize> for Homotopy<Data> {
+    /// Counts of each homotopy level.
+    type Tables = Vec<usize>;
+    fn tables(&self, &(level, n): &(usize, usize)) -> Vec<usize> {level_counts(level, &n)}
+    fn prepared_count(&self, &(level, _): &(usize, usize), tables: &Vec<usize>) -> usize {
+        tables[level]
+    }
+    fn prepared_to_index(&self, &(level, _): &(usize, usize), tables: &Vec<usize>, pos: &HPoint) -> usize {
+        prepared_to_index(tables, level, pos)
+    }
+    fn prepared_to_pos(
+        &self,
+        &(level, _): &(usize, usize),
+        tables: &Vec<usize>,
+        index: usize,
+        pos: &mut HPoint,
+    ) {
+        prepared_to_pos(tables, level, index, pos)
+    }
+}
+
+impl Prepare<BigUint> for Homotopy<Data> {
+    /// Counts of each homotopy level.
+    type Tables = Vec<BigUint>;
+    fn tables(&self, (level, n): &(usize, BigUint)) -> Vec<BigUint> {level_counts(*level, n)}
+    fn prepared_count(&self, (level, _): &(usize, BigUint), tables: &Vec<BigUint>) -> BigUint {
+        tables[*level].clone()
+    }
+    fn prepared_to_index(
+        &self,
+        (level, _): &(usize, BigUint),
+        tables: &Vec<BigUint>,
+        pos: &HPoint<BigUint>,
+    ) -> BigUint {
+        prepared_to_index(tables, *level, pos)
+    }
+    fn prepared_to_pos(
+        &self,
+        (level, _): &(usize, BigUint),
+        tables: &Vec<BigUint>,
+        index: BigUint,
+        pos: &mut HPoint<BigUint>,
+    ) {
+        prepared_to_pos(tables, *level, index, pos)
+    }
 }
 
 impl<N, T> Space<N> for Homotopy<Of<T>>
@@ -278,4 +743,153 @@ impl<N, T> Space<N> for Homotopy<Of<T>>
             }
         }
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (_, dim): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        of_to_index_with::<N, T>(scratch, dim, &of.count(dim), pos, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (level, dim): &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        of_to_pos_with::<N, T>(scratch, dim, &of.count(dim), *level, index, pos)
+    }
+    fn to_index_batch(&self, (level, dim): &Self::Dim, pos: &[Self::Pos], index: &mut [N]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let of: T = Construct::new();
+        let counts = level_counts(*level, &of.count(dim));
+        for (p, i) in pos.iter().zip(index) {
+            *i = prepared_to_index(&counts, *level, &p.map(|x| of.to_index(dim, x)));
+        }
+    }
+    fn to_pos_batch(&self, (level, dim): &Self::Dim, index: &[N], pos: &mut [Self::Pos]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let of: T = Construct::new();
+        let counts = level_counts(*level, &of.count(dim));
+        let mut inner = HPoint::Point(0usize.into());
+        for (i, p) in index.iter().zip(pos) {
+            prepared_to_pos(&counts, *level, i.clone(), &mut inner);
+            *p = inner.map(|x| {
+                let mut y = of.zero(dim);
+                of.to_pos(dim, x.clone(), &mut y);
+                y
+            });
+        }
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos.leaves() {
+            of.try_to_index(&dim.1, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use HPoint::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, Homotopy>();
+        is_complete::<usize, Homotopy<Of<Pair>>>();
+    }
+
+    #[test]
+    fn endpoints() {
+        let a = HPoint::path(Point(0), Point(1));
+        let b = HPoint::path(Point(1), Point(1));
+        let p = HPoint::path(a.clone(), b.clone());
+        assert_eq!(p.source(), Some(&a));
+        assert_eq!(p.target(), Some(&b));
+        assert_eq!(Point(0).source(), None);
+        assert_eq!(p.source_at(1), &a);
+        assert_eq!(p.source_at(0), &Point(0));
+        assert_eq!(p.target_at(0), &Point(1));
+        assert_eq!(p.source_at(2), &p);
+        assert!(!a.is_loop());
+        assert!(b.is_loop());
+        assert!(!Point(0).is_loop());
+    }
+
+    #[test]
+    fn map_leaves() {
+        let p = HPoint::path(HPoint::path(Point(0), Point(1)), Point(2));
+        assert_eq!(p.leaves().cloned().collect::<Vec<_>>(), vec![0, 1, 2]);
+        let q = p.map(|x| x * 10);
+        assert_eq!(q.leaves().cloned().collect::<Vec<_>>(), vec![0, 10, 20]);
+        assert_eq!(q.level(), p.level());
+    }
+
+    #[test]
+    fn format() {
+        let p = HPoint::path(HPoint::path(Point(0), Point(1)), HPoint::path(Point(1), Point(1)));
+        assert_eq!(p.to_string(), "((0,1),(1,1))");
+        assert_eq!("((0,1),(1,1))".parse::<HPoint>(), Ok(p.clone()));
+        assert_eq!(" ( (0, 1) , (1,1) ) ".parse::<HPoint>(), Ok(p));
+        assert_eq!("3".parse::<HPoint>(), Ok(Point(3)));
+        assert_eq!("(0,1".parse::<HPoint>().unwrap_err().offset, 4);
+        assert_eq!("(0,x)".parse::<HPoint>().unwrap_err().offset, 3);
+        assert!("(0,1))".parse::<HPoint>().is_err());
+        assert!("".parse::<HPoint>().is_err());
+        let deep = "(".repeat(100_000);
+        assert_eq!(deep.parse::<HPoint>().unwrap_err().message, "Paths are nested too deeply");
+        let ok = format!("{}0{}", "(".repeat(256), ",0)".repeat(256));
+        assert!(ok.parse::<HPoint>().is_ok());
+    }
+
+    #[test]
+    fn batch() {
+        let x: Homotopy = Construct::new();
+        let ref dim = (2, 3);
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = x.zero(dim);
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        let ref dim: (usize, BigUint) = (2, 3usize.into());
+        let indices: Vec<BigUint> = (0..count).map(|i| i.into()).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut res = vec![0usize.into(); count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        let x: Homotopy<Of<Pair>> = Construct::new();
+        let ref dim = (2, 3);
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![x.zero(dim); count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = x.zero(dim);
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+    }
 }
@@ -0,0 +1,270 @@
+use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Dimension,
+    EqPair,
+    HPoint,
+    Of,
+    space::Space,
+};
+
+/// A discrete space that models all undirected homotopy paths
+/// up to a specified homotopy level.
+///
+/// Unlike `Homotopy`, which only contains the paths of one level,
+/// this space contains the pieces themselves and every path between
+/// two higher order points of lower level,
+/// including paths between points of different levels.
+///
+/// Dimension is `(<max homotopy level>, <number of pieces>)`,
+/// position is `HPoint`.
+///
+/// The index order is the same as the `Ord` order of `HPoint`:
+/// Points come first, followed by paths `(a, b)` with `a <= b`
+/// sorted lexicographically.
+/// For `HomotopyUpTo<Of<T>>`, this holds when the index order of `T`
+/// agrees with the `Ord` order of its positions.
+pub struct HomotopyUpTo<T = Data>(PhantomData<T>);
+
+impl<T> Construct for HomotopyUpTo<T> {
+    fn new() -> Self {HomotopyUpTo(PhantomData)}
+}
+
+fn count<N, T>(level: usize, dim: &T::Dim) -> N
+    where T: Space<N>,
+          EqPair<Data>: Space<N, Dim = N>,
+          for<'a> &'a N: Add<&'a N, Output = N>,
+{
+    let of: T = Construct::new();
+    let s: EqPair = Construct::new();
+    let n = of.count(dim);
+    let mut count = of.count(dim);
+    for _ in 0..level {
+        count = &n + &s.count(&count);
+    }
+    count
+}
+
+fn to_index<N, T>(level: usize, dim: &T::Dim, pos: &HPoint<T::Pos>) -> N
+    where T: Space<N>,
+          EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: From<usize> + Ord,
+          for<'a> &'a N: Add<&'a N, Output = N> + Sub<&'a N, Output = N>,
+{
+    use HPoint::*;
+
+    let of: T = Construct::new();
+    match pos {
+        Point(x) => of.to_index(dim, x),
+        Path(ab) => {
+            let one: N = 1usize.into();
+            let s: EqPair = Construct::new();
+            let n = of.count(dim);
+            let count: N = count::<N, T>(level - 1, dim);
+            let a = to_index::<N, T>(level - 1, dim, &ab.0);
+            let b = to_index::<N, T>(level - 1, dim, &ab.1);
+            let (a, b) = if a <= b {(a, b)} else {(b, a)};
+            // Reversing both the order of pieces and the pair
+            // turns the order of `EqPair` into lexicographic order.
+            let last = &count - &one;
+            let rev = s.to_index(&count, &(&last - &b, &last - &a));
+            &n + &(&(&s.count(&count) - &one) - &rev)
+        }
+    }
+}
+
+fn to_pos<N, T>(level: usize, dim: &T::Dim, index: N, pos: &mut HPoint<T::Pos>)
+    where T: Space<N>,
+          EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: From<usize> + Ord,
+          for<'a> &'a N: Add<&'a N, Output = N> + Sub<&'a N, Output = N>,
+{
+    use HPoint::*;
+
+    let of: T = Construct::new();
+    let n = of.count(dim);
+    if level == 0 || index < n {
+        let mut p = of.zero(dim);
+        of.to_pos(dim, index, &mut p);
+        *pos = Point(p);
+        return;
+    }
+
+    let one: N = 1usize.into();
+    let s: EqPair = Construct::new();
+    let count: N = count::<N, T>(level - 1, dim);
+    let rev = &(&s.count(&count) - &one) - &(&index - &n);
+    let mut xy = s.zero(&count);
+    s.to_pos(&count, rev, &mut xy);
+    let last = &count - &one;
+    let mut a = Point(of.zero(dim));
+    let mut b = Point(of.zero(dim));
+    to_pos::<N, T>(level - 1, dim, &last - &xy.1, &mut a);
+    to_pos::<N, T>(level - 1, dim, &last - &xy.0, &mut b);
+    *pos = Path(Box::new((a, b)));
+}
+
+fn zero<N, T>(dim: &T::Dim) -> HPoint<T::Pos>
+    where T: Space<N>
+{
+    let of: T = Construct::new();
+    HPoint::Point(of.zero(dim))
+}
+
+impl Space<usize> for HomotopyUpTo<Data> {
+    type Dim = (usize, usize);
+    type Pos = HPoint;
+    fn count(&self, &(level, n): &(usize, usize)) -> usize {
+        count::<usize, Dimension>(level, &n)
+    }
+    fn zero(&self, &(_, n): &(usize, usize)) -> HPoint {
+        zero::<usize, Dimension>(&n)
+    }
+    fn to_index(&self, &(level, n): &(usize, usize), pos: &HPoint) -> usize {
+        to_index::<usize, Dimension>(level, &n, pos)
+    }
+    fn to_pos(&self, &(level, n): &(usize, usize), index: usize, pos: &mut HPoint) {
+        to_pos::<usize, Dimension>(level, &n, index, pos)
+    }
+}
+
+impl Space<BigUint> for HomotopyUpTo<Data> {
+    type Dim = (usize, BigUint);
+    type Pos = HPoint<BigUint>;
+    fn count(&self, (level, n): &Self::Dim) -> BigUint {
+        count::<BigUint, Dimension>(*level, n)
+    }
+    fn zero(&self, (_, n): &Self::Dim) -> Self::Pos {
+        zero::<BigUint, Dimension>(n)
+    }
+    fn to_index(&self, (level, n): &Self::Dim, pos: &Self::Pos) -> BigUint {
+        to_index::<BigUint, Dimension>(*level, n, pos)
+    }
+    fn to_pos(&self, (level, n): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        to_pos::<BigUint, Dimension>(*level, n, index, pos)
+    }
+}
+
+impl<N, T> Space<N> for HomotopyUpTo<Of<T>>
+    where T: Space<N>,
+          EqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: From<usize> + Ord,
+          for<'a> &'a N: Add<&'a N, Output = N> + Sub<&'a N, Output = N>,
+{
+    type Dim = (usize, T::Dim);
+    type Pos = HPoint<T::Pos>;
+    fn count(&self, (level, dim): &Self::Dim) -> N {
+        count::<N, T>(*level, dim)
+    }
+    fn zero(&self, (_, dim): &Self::Dim) -> Self::Pos {
+        zero::<N, T>(dim)
+    }
+    fn to_index(&self, (level, dim): &Self::Dim, pos: &Self::Pos) -> N {
+        to_index::<N, T>(*level, dim, pos)
+    }
+    fn to_pos(&self, (level, dim): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        to_pos::<N, T>(*level, dim, index, pos)
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos.leaves() {
+            of.try_to_index(&dim.1, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use HPoint::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, HomotopyUpTo>();
+        is_complete::<usize, HomotopyUpTo<Of<Pair>>>();
+    }
+
+    #[test]
+    fn data() {
+        let x: HomotopyUpTo = Construct::new();
+        assert_eq!(x.count(&(0, 2)), 2);
+        assert_eq!(x.count(&(1, 2)), 5);
+        assert_eq!(x.count(&(2, 2)), 17);
+        let ref dim = (1, 2);
+        assert_eq!(x.to_index(dim, &Point(0)), 0);
+        assert_eq!(x.to_index(dim, &Point(1)), 1);
+        assert_eq!(x.to_index(dim, &HPoint::path(Point(0), Point(0))), 2);
+        assert_eq!(x.to_index(dim, &HPoint::path(Point(0), Point(1))), 3);
+        assert_eq!(x.to_index(dim, &HPoint::path(Point(1), Point(1))), 4);
+
+        let ref dim = (3, 2);
+        let mut prev = None;
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert!(pos.level() <= 3);
+            assert_eq!(x.to_index(dim, &pos), i);
+            if let Some(prev) = prev {
+                assert!(prev < pos);
+            }
+            prev = Some(pos.clone());
+        }
+    }
+
+    #[test]
+    fn mixed() {
+        let x: HomotopyUpTo = Construct::new();
+        let ref dim = (2, 2);
+        let pos = HPoint::path(Point(1), HPoint::path(Point(0), Point(1)));
+        let index = x.to_index(dim, &pos);
+        let mut new_pos = x.zero(dim);
+        x.to_pos(dim, index, &mut new_pos);
+        assert_eq!(new_pos, pos);
+    }
+
+    #[test]
+    fn data_big() {
+        use std::convert::TryInto;
+
+        let x: HomotopyUpTo = Construct::new();
+        let ref dim: (usize, BigUint) = (2, 2usize.into());
+        assert_eq!(x.count(dim), 17usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+
+    #[test]
+    fn of() {
+        let x: HomotopyUpTo<Of<Pair>> = Construct::new();
+        let ref dim = (2, 3);
+        assert_eq!(x.count(dim), 3 + 9 * 10 / 2);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+
+    #[test]
+    fn of_big() {
+        use std::convert::TryInto;
+
+        let x: HomotopyUpTo<Of<Pair>> = Construct::new();
+        let ref dim: (usize, BigUint) = (2, 3usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Neighbour and incidence queries for context spaces.
+//!
+//! A position `(p, ind, b)` in a context space is an edge
+//! between the node `p` and the node `p` with `p[ind]` replaced by `b`.
+//! Nodes are positions in `DimensionN` with the same dimension,
+//! so they can be referred to by their index.
+
+use std::ops::AddAssign;
+
+use crate::{
+    BigUint,
+    Construct,
+    Context,
+    Data,
+    Dimension,
+    DimensionN,
+    DirectedContext,
+    Of,
+    space::Space,
+};
+
+/// Implemented by context spaces to relate edges with nodes in `DimensionN`.
+pub trait Incidence<N>: Space<N> {
+    /// Counts the nodes, which is the count of `DimensionN` with the same dimension.
+    fn node_count(&self, dim: &Self::Dim) -> N;
+    /// Gets the `(from, to)` node indices of an edge.
+    ///
+    /// For undirected edges, `from` is the node with `p[ind]`
+    /// and `to` is the node with `b`.
+    fn nodes(&self, dim: &Self::Dim, edge: &Self::Pos) -> (N, N);
+    /// Gets the edge between two nodes,
+    /// or `None` if they do not differ by exactly one axis.
+    fn edge(&self, dim: &Self::Dim, from: &N, to: &N) -> Option<Self::Pos>;
+    /// Lists the edges going out from a node.
+    ///
+    /// For undirected edges, this is all edges incident to the node.
+    fn out_edges(&self, dim: &Self::Dim, node: &N) -> Vec<Self::Pos>;
+    /// Lists the edges coming into a node.
+    ///
+    /// For undirected edges, this is all edges incident to the node.
+    fn in_edges(&self, dim: &Self::Dim, node: &N) -> Vec<Self::Pos>;
+}
+
+/// Finds the single axis where two nodes differ.
+fn changed_axis<N, T>(dim: &[T::Dim], p: &[T::Pos], q: &[T::Pos]) -> Option<usize>
+    where T: Space<N>,
+          N: PartialEq,
+{
+    let of: T = Construct::new();
+    let mut axis = None;
+    for i in 0..dim.len() {
+        if of.to_index(&dim[i], &p[i]) != of.to_index(&dim[i], &q[i]) {
+            if axis.is_some() {return None}
+            axis = Some(i);
+        }
+    }
+    axis
+}
+
+/// Calls a closure with `(axis, lower, value)` for every neighbour of a node,
+/// where `value` replaces the node's value along the axis,
+/// and `lower` tells whether it has a lower index.
+fn neighbours<N, T, F>(dim: &[T::Dim], p: &[T::Pos], mut f: F)
+    where T: Space<N>,
+          N: Clone + From<usize> + PartialOrd + for<'a> AddAssign<&'a N>,
+          F: FnMut(usize, bool, T::Pos),
+{
+    let of: T = Construct::new();
+    let one: N = 1usize.into();
+    for i in 0..dim.len() {
+        let count = of.count(&dim[i]);
+        let current = of.to_index(&dim[i], &p[i]);
+        let mut v: N = 0usize.into();
+        while v < count {
+            if v != current {
+                let mut x = of.zero(&dim[i]);
+                of.to_pos(&dim[i], v.clone(), &mut x);
+                f(i, v < current, x);
+            }
+            v += &one;
+        }
+    }
+}
+
+impl<N, T> Incidence<N> for Context<Of<T>>
+    where T: Space<N>,
+          T::Pos: Clone,
+          Context<Of<T>>: Space<N, Dim = Vec<T::Dim>, Pos = (Vec<T::Pos>, usize, T::Pos)>,
+          DimensionN<Of<T>>: Space<N, Dim = Vec<T::Dim>, Pos = Vec<T::Pos>>,
+          N: Clone + From<usize> + PartialOrd + for<'a> AddAssign<&'a N>,
+{
+    fn node_count(&self, dim: &Self::Dim) -> N {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        nodes.count(dim)
+    }
+    fn nodes(&self, dim: &Self::Dim, (p, ind, b): &Self::Pos) -> (N, N) {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut q = p.clone();
+        q[*ind] = b.clone();
+        (nodes.to_index(dim, p), nodes.to_index(dim, &q))
+    }
+    fn edge(&self, dim: &Self::Dim, from: &N, to: &N) -> Option<Self::Pos> {
+        let of: T = Construct::new();
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut p = nodes.zero(dim);
+        let mut q = nodes.zero(dim);
+        nodes.to_pos(dim, from.clone(), &mut p);
+        nodes.to_pos(dim, to.clone(), &mut q);
+        let ind = changed_axis::<N, T>(dim, &p, &q)?;
+        if of.to_index(&dim[ind], &p[ind]) > of.to_index(&dim[ind], &q[ind]) {
+            std::mem::swap(&mut p, &mut q);
+        }
+        let b = q[ind].clone();
+        Some((p, ind, b))
+    }
+    fn out_edges(&self, dim: &Self::Dim, node: &N) -> Vec<Self::Pos> {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut p = nodes.zero(dim);
+        nodes.to_pos(dim, node.clone(), &mut p);
+        let mut edges = vec![];
+        neighbours::<N, T, _>(dim, &p, |i, lower, x| {
+            if lower {
+                let mut q = p.clone();
+                let b = std::mem::replace(&mut q[i], x);
+                edges.push((q, i, b));
+            } else {
+                edges.push((p.clone(), i, x));
+            }
+        });
+        edges
+    }
+    fn in_edges(&self, dim: &Self::Dim, node: &N) -> Vec<Self::Pos> {
+        self.out_edges(dim, node)
+    }
+}
+
+impl<N, T> Incidence<N> for DirectedContext<Of<T>>
+    where T: Space<N>,
+          T::Pos: Clone,
+          DirectedContext<Of<T>>: Space<N, Dim = Vec<T::Dim>, Pos = (Vec<T::Pos>, usize, T::Pos)>,
+          DimensionN<Of<T>>: Space<N, Dim = Vec<T::Dim>, Pos = Vec<T::Pos>>,
+          N: Clone + From<usize> + PartialOrd + for<'a> AddAssign<&'a N>,
+{
+    fn node_count(&self, dim: &Self::Dim) -> N {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        nodes.count(dim)
+    }
+    fn nodes(&self, dim: &Self::Dim, (p, ind, b): &Self::Pos) -> (N, N) {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut q = p.clone();
+        q[*ind] = b.clone();
+        (nodes.to_index(dim, p), nodes.to_index(dim, &q))
+    }
+    fn edge(&self, dim: &Self::Dim, from: &N, to: &N) -> Option<Self::Pos> {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut p = nodes.zero(dim);
+        let mut q = nodes.zero(dim);
+        nodes.to_pos(dim, from.clone(), &mut p);
+        nodes.to_pos(dim, to.clone(), &mut q);
+        let ind = changed_axis::<N, T>(dim, &p, &q)?;
+        let b = q[ind].clone();
+        Some((p, ind, b))
+    }
+    fn out_edges(&self, dim: &Self::Dim, node: &N) -> Vec<Self::Pos> {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut p = nodes.zero(dim);
+        nodes.to_pos(dim, node.clone(), &mut p);
+        let mut edges = vec![];
+        neighbours::<N, T, _>(dim, &p, |i, _, x| edges.push((p.clone(), i, x)));
+        edges
+    }
+    fn in_edges(&self, dim: &Self::Dim, node: &N) -> Vec<Self::Pos> {
+        let nodes: DimensionN<Of<T>> = Construct::new();
+        let mut p = nodes.zero(dim);
+        nodes.to_pos(dim, node.clone(), &mut p);
+        let mut edges = vec![];
+        neighbours::<N, T, _>(dim, &p, |i, _, x| {
+            let mut q = p.clone();
+            let b = std::mem::replace(&mut q[i], x);
+            edges.push((q, i, b));
+        });
+        edges
+    }
+}
+
+impl Incidence<usize> for Context<Data> {
+    fn node_count(&self, dim: &Self::Dim) -> usize {
+        let nodes: DimensionN = Construct::new();
+        nodes.count(dim)
+    }
+    fn nodes(&self, dim: &Self::Dim, edge: &Self::Pos) -> (usize, usize) {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.nodes(dim, edge)
+    }
+    fn edge(&self, dim: &Self::Dim, from: &usize, to: &usize) -> Option<Self::Pos> {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.edge(dim, from, to)
+    }
+    fn out_edges(&self, dim: &Self::Dim, node: &usize) -> Vec<Self::Pos> {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.out_edges(dim, node)
+    }
+    fn in_edges(&self, dim: &Self::Dim, node: &usize) -> Vec<Self::Pos> {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.in_edges(dim, node)
+    }
+}
+
+impl Incidence<BigUint> for Context<Data> {
+    fn node_count(&self, dim: &Self::Dim) -> BigUint {
+        let nodes: DimensionN = Construct::new();
+        nodes.count(dim)
+    }
+    fn nodes(&self, dim: &Self::Dim, edge: &Self::Pos) -> (BigUint, BigUint) {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.nodes(dim, edge)
+    }
+    fn edge(&self, dim: &Self::Dim, from: &BigUint, to: &BigUint) -> Option<Self::Pos> {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.edge(dim, from, to)
+    }
+    fn out_edges(&self, dim: &Self::Dim, node: &BigUint) -> Vec<Self::Pos> {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.out_edges(dim, node)
+    }
+    fn in_edges(&self, dim: &Self::Dim, node: &BigUint) -> Vec<Self::Pos> {
+        let of: Context<Of<Dimension>> = Construct::new();
+        of.in_edges(dim, node)
+    }
+}
+
+impl Incidence<usize> for DirectedContext<Data> {
+    fn node_count(&self, dim: &Self::Dim) -> usize {
+        let nodes: DimensionN = Construct::new();
+        nodes.count(dim)
+    }
+    fn nodes(&self, dim: &Self::Dim, edge: &Self::Pos) -> (usize, usize) {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.nodes(dim, edge)
+    }
+    fn edge(&self, dim: &Self::Dim, from: &usize, to: &usize) -> Option<Self::Pos> {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.edge(dim, from, to)
+    }
+    fn out_edges(&self, dim: &Self::Dim, node: &usize) -> Vec<Self::Pos> {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.out_edges(dim, node)
+    }
+    fn in_edges(&self, dim: &Self::Dim, node: &usize) -> Vec<Self::Pos> {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.in_edges(dim, node)
+    }
+}
+
+impl Incidence<BigUint> for DirectedContext<Data> {
+    fn node_count(&self, dim: &Self::Dim) -> BigUint {
+        let nodes: DimensionN = Construct::new();
+        nodes.count(dim)
+    }
+    fn nodes(&self, dim: &Self::Dim, edge: &Self::Pos) -> (BigUint, BigUint) {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.nodes(dim, edge)
+    }
+    fn edge(&self, dim: &Self::Dim, from: &BigUint, to: &BigUint) -> Option<Self::Pos> {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.edge(dim, from, to)
+    }
+    fn out_edges(&self, dim: &Self::Dim, node: &BigUint) -> Vec<Self::Pos> {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.out_edges(dim, node)
+    }
+    fn in_edges(&self, dim: &Self::Dim, node: &BigUint) -> Vec<Self::Pos> {
+        let of: DirectedContext<Of<Dimension>> = Construct::new();
+        of.in_edges(dim, node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn context() {
+        let x: Context = Construct::new();
+        let nodes: DimensionN = Construct::new();
+        let ref dim = vec![3, 2];
+        for i in 0..x.count(dim) {
+            let mut edge = x.zero(dim);
+            x.to_pos(dim, i, &mut edge);
+            let (from, to): (usize, usize) = x.nodes(dim, &edge);
+            assert!(from < to);
+            assert_eq!(x.edge(dim, &from, &to), Some(edge.clone()));
+            assert_eq!(x.edge(dim, &to, &from), Some(edge.clone()));
+            assert!(x.out_edges(dim, &from).contains(&edge));
+            assert!(x.in_edges(dim, &to).contains(&edge));
+        }
+        assert_eq!(x.edge(dim, &0, &0), None);
+        assert_eq!(x.edge(dim, &0, &4), None);
+        let total: usize = (0..nodes.count(dim)).map(|n| x.out_edges(dim, &n).len()).sum();
+        assert_eq!(total, 2 * x.count(dim));
+        assert_eq!(x.out_edges(dim, &0), vec![
+            (vec![0, 0], 0, 1),
+            (vec![0, 0], 0, 2),
+            (vec![0, 0], 1, 1),
+        ]);
+    }
+
+    #[test]
+    fn directed_context() {
+        let x: DirectedContext = Construct::new();
+        let nodes: DimensionN = Construct::new();
+        let ref dim = vec![3, 2];
+        for i in 0..x.count(dim) {
+            let mut edge = x.zero(dim);
+            x.to_pos(dim, i, &mut edge);
+            let (from, to): (usize, usize) = x.nodes(dim, &edge);
+            assert_eq!(x.edge(dim, &from, &to), Some(edge.clone()));
+            assert!(x.out_edges(dim, &from).contains(&edge));
+            assert!(x.in_edges(dim, &to).contains(&edge));
+            assert!(!x.out_edges(dim, &to).contains(&edge));
+        }
+        let total: usize = (0..nodes.count(dim)).map(|n| x.out_edges(dim, &n).len()).sum();
+        assert_eq!(total, x.count(dim));
+        assert_eq!(x.in_edges(dim, &0), vec![
+            (vec![1, 0], 0, 0),
+            (vec![2, 0], 0, 0),
+            (vec![0, 1], 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn big() {
+        use std::convert::TryInto;
+
+        let x: DirectedContext = Construct::new();
+        let ref dim: Vec<BigUint> = vec![3usize.into(), 2usize.into()];
+        let count: usize = x.count(dim).try_into().unwrap();
+        for i in 0..count {
+            let mut edge = x.zero(dim);
+            x.to_pos(dim, i.into(), &mut edge);
+            let (from, to): (BigUint, BigUint) = x.nodes(dim, &edge);
+            assert_eq!(x.edge(dim, &from, &to), Some(edge.clone()));
+            assert!(x.out_edges(dim, &from).contains(&edge));
+        }
+    }
+
+    #[test]
+    fn of() {
+        let x: Context<Of<Pair>> = Construct::new();
+        let ref dim = vec![3, 3];
+        for i in 0..x.count(dim) {
+            let mut edge = x.zero(dim);
+            x.to_pos(dim, i, &mut edge);
+            let (from, to): (usize, usize) = x.nodes(dim, &edge);
+            assert_eq!(x.edge(dim, &from, &to), Some(edge.clone()));
+            assert!(x.out_edges(dim, &to).contains(&edge));
+        }
+    }
+}
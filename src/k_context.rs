@@ -0,0 +1,738 @@
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Dimension,
+    NeqPair,
+    Of,
+    Pair,
+    Scratch,
+    space::Space,
+};
+
+/// A discrete space that models changes of exactly `k` axes at once
+/// in a N-dimensional space.
+///
+/// This generalizes `Context`, which changes one axis at a time,
+/// to simultaneous updates of `k` axes.
+/// It can also be thought of as the edges in an undirected graph,
+/// where each node is described by a N-dimensional coordinate,
+/// and all nodes are connected which differ by exactly `k` axes.
+///
+/// Dimension is `(Vec<usize>, k)`.
+///
+/// The position is a tuple `(Vec<usize>, Vec<usize>, Vec<usize>)`,
+/// where the first component describes the node coordinates,
+/// the second component lists the increasing indices of the coordinates that change,
+/// and the third component lists the new values.
+/// Like in `Context`, the first changed coordinate is lower than its new value,
+/// while the other changed coordinates can be both lower and higher.
+///
+/// The changed axes are ordered the same way as `Combination`,
+/// with a `Pair` choice for the first changed axis
+/// and a `NeqPair` choice for the others.
+/// For `k = 1`, the index is the same as in `Context`.
+pub struct KContext<T = Data>(PhantomData<T>);
+
+/// Same as `KContext`, but for directed edges.
+///
+/// Uses a `NeqPair` choice for each changed axis,
+/// so the new values can be both lower and higher.
+/// For `k = 1`, the index is the same as in `DirectedContext`.
+pub struct DirectedKContext<T = Data>(PhantomData<T>);
+
+impl<T> Construct for KContext<T> {
+    fn new() -> Self { KContext(PhantomData) }
+}
+
+impl<T> Construct for DirectedKContext<T> {
+    fn new() -> Self { DirectedKContext(PhantomData) }
+}
+
+/// Precomputed weights of each axis when it is kept or changed.
+///
+/// The sets of changed axes are ordered like `Combination`,
+/// but the sets can not be ranked with `Combination` and multiplied by a block size,
+/// because axes have different sizes and the number of edges
+/// for a set of changed axes depends on which axes are in it.
+/// Instead, `table` counts the edges of all sets that come before,
+/// which takes `O(n k)` steps instead of summing over every earlier set.
+struct Weights<N> {
+    directed: bool,
+    /// The size of each axis.
+    keep: Vec<N>,
+    /// The number of `Pair` choices of each axis.
+    pair: Vec<N>,
+    /// The number of `NeqPair` choices of each axis.
+    neq_pair: Vec<N>,
+    /// `table[m][j]` is the number of ways to change `j` of the first `m` axes,
+    /// counting the values of all the first `m` axes.
+    table: Vec<Vec<N>>,
+}
+
+impl<N> Weights<N>
+    where Pair<Data>: Space<N, Dim = N>,
+          NeqPair<Data>: Space<N, Dim = N>,
+          N: Clone + From<usize>,
+          for<'a> &'a N: Add<&'a N, Output = N> + Mul<&'a N, Output = N>,
+{
+    fn new<T: Space<N>>(dim: &[T::Dim], k: usize, directed: bool) -> Weights<N> {
+        let of: T = Construct::new();
+        let pair: Pair = Construct::new();
+        let neq_pair: NeqPair = Construct::new();
+        let keep: Vec<N> = dim.iter().map(|d| of.count(d)).collect();
+        let mut w = Weights {
+            directed,
+            pair: keep.iter().map(|c| pair.count(c)).collect(),
+            neq_pair: keep.iter().map(|c| neq_pair.count(c)).collect(),
+            keep,
+            table: vec![vec![0usize.into(); k + 1]],
+        };
+        w.table[0][0] = 1usize.into();
+        for i in 0..dim.len() {
+            let prev = &w.table[i];
+            let mut row: Vec<N> = Vec::with_capacity(k + 1);
+            for j in 0..k + 1 {
+                let mut sum = &prev[j] * &w.keep[i];
+                if j > 0 {
+                    sum = &sum + &(&prev[j - 1] * w.change(i, j));
+                }
+                row.push(sum);
+            }
+            w.table.push(row);
+        }
+        w
+    }
+
+    /// Gets the number of choices of axis `i` when it changes,
+    /// where `r` is the number of changed axes up to and including `i`.
+    fn change(&self, i: usize, r: usize) -> &N {
+        if !self.directed && r == 1 {&self.pair[i]} else {&self.neq_pair[i]}
+    }
+}
+
+fn count<N, T>(dim: &[T::Dim], k: usize, directed: bool) -> N
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N>,
+          NeqPair<Data>: Space<N, Dim = N>,
+          N: Clone + From<usize>,
+          for<'a> &'a N: Add<&'a N, Output = N> + Mul<&'a N, Output = N>,
+{
+    let w = Weights::new::<T>(dim, k, directed);
+    w.table[dim.len()][k].clone()
+}
+
+fn zero<N, T>(dim: &[T::Dim], k: usize) -> (Vec<T::Pos>, Vec<usize>, Vec<T::Pos>)
+    where T: Space<N>
+{
+    let of: T = Construct::new();
+    let p = dim.iter().map(|d| of.zero(d)).collect();
+    let b = dim.iter().take(k).map(|d| of.zero(d)).collect();
+    (p, (0..k).collect(), b)
+}
+
+/// Converts the inner indices of the coordinates and new values to an index.
+fn indices_to_index<N>(w: &Weights<N>, k: usize, xs: &[N], axes: &[usize], ys: &[N]) -> N
+    where Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> + Mul<&'a N, Output = N>,
+{
+    let pair: Pair = Construct::new();
+    let neq_pair: NeqPair = Construct::new();
+    let mut changed = vec![None; xs.len()];
+    for (j, &i) in axes.iter().enumerate() {
+        changed[i] = Some(j);
+    }
+
+    // An undirected edge is the same when swapping the end points,
+    // so it is stored with the first changed coordinate increasing.
+    let flip = !w.directed && axes.first().is_some_and(|&i| xs[i] > ys[0]);
+
+    let mut offset: N = 0usize.into();
+    let mut pairs: N = 0usize.into();
+    let mut rest: N = 0usize.into();
+    let mut prod: N = 1usize.into();
+    let mut rest_prod: N = 1usize.into();
+    let mut r = k;
+    for i in (0..xs.len()).rev() {
+        match changed[i] {
+            Some(j) => {
+                // Skip the subspaces where this axis does not change.
+                offset = &offset + &(&(&prod * &w.keep[i]) * &w.table[i][r]);
+                let (x, y) = (xs[i].clone(), ys[j].clone());
+                let xy = if flip {(y, x)} else {(x, y)};
+                let ind = if !w.directed && r == 1 {
+                    pair.to_index(&w.keep[i], &xy)
+                } else {
+                    neq_pair.to_index(&w.keep[i], &xy)
+                };
+                pairs = &(&pairs * w.change(i, r)) + &ind;
+                prod = &prod * w.change(i, r);
+                r -= 1;
+            }
+            None => {
+                rest = &(&rest * &w.keep[i]) + &xs[i];
+                rest_prod = &rest_prod * &w.keep[i];
+                prod = &prod * &w.keep[i];
+            }
+        }
+    }
+    &offset + &(&(&pairs * &rest_prod) + &rest)
+}
+
+/// Converts an index to the inner indices of the coordinates and new values.
+fn index_to_indices<N>(
+    w: &Weights<N>,
+    k: usize,
+    mut index: N,
+    xs: &mut Vec<N>,
+    axes: &mut Vec<usize>,
+    ys: &mut Vec<N>,
+)
+    where Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    let pair: Pair = Construct::new();
+    let neq_pair: NeqPair = Construct::new();
+    let n = w.keep.len();
+
+    // Find the changed axes by skipping subspaces that come before.
+    let mut changed = vec![0; n];
+    let mut prod: N = 1usize.into();
+    let mut rest_prod: N = 1usize.into();
+    let mut r = k;
+    for i in (0..n).rev() {
+        if r > 0 {
+            let skip = &(&prod * &w.keep[i]) * &w.table[i][r];
+            if index >= skip {
+                index = &index - &skip;
+                changed[i] = r;
+                prod = &prod * w.change(i, r);
+                r -= 1;
+                continue;
+            }
+        }
+        prod = &prod * &w.keep[i];
+        rest_prod = &rest_prod * &w.keep[i];
+    }
+    let mut pairs = &index / &rest_prod;
+    let mut rest = &index % &rest_prod;
+
+    xs.clear();
+    axes.clear();
+    ys.clear();
+    for (i, &r) in changed.iter().enumerate() {
+        if r > 0 {
+            let radix = w.change(i, r);
+            let mut xy = pair.zero(&w.keep[i]);
+            if !w.directed && r == 1 {
+                pair.to_pos(&w.keep[i], &pairs % radix, &mut xy);
+            } else {
+                neq_pair.to_pos(&w.keep[i], &pairs % radix, &mut xy);
+            }
+            pairs = &pairs / radix;
+            xs.push(xy.0);
+            axes.push(i);
+            ys.push(xy.1);
+        } else {
+            xs.push(&rest % &w.keep[i]);
+            rest = &rest / &w.keep[i];
+        }
+    }
+}
+
+fn to_index<N, T>(
+    dim: &[T::Dim],
+    k: usize,
+    directed: bool,
+    (p, axes, b): &(Vec<T::Pos>, Vec<usize>, Vec<T::Pos>),
+) -> N
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> + Mul<&'a N, Output = N>,
+{
+    let of: T = Construct::new();
+    let w = Weights::new::<T>(dim, k, directed);
+    let xs: Vec<N> = dim.iter().zip(p).map(|(d, x)| of.to_index(d, x)).collect();
+    let ys: Vec<N> = axes.iter().zip(b).map(|(&i, y)| of.to_index(&dim[i], y)).collect();
+    indices_to_index(&w, k, &xs, axes, &ys)
+}
+
+fn to_pos<N, T>(
+    dim: &[T::Dim],
+    k: usize,
+    directed: bool,
+    index: N,
+    (p, axes, b): &mut (Vec<T::Pos>, Vec<usize>, Vec<T::Pos>),
+)
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    let of: T = Construct::new();
+    let w = Weights::new::<T>(dim, k, directed);
+    let (mut xs, mut ys) = (vec![], vec![]);
+    index_to_indices(&w, k, index, &mut xs, axes, &mut ys);
+    p.clear();
+    for (d, x) in dim.iter().zip(xs) {
+        let mut pos = of.zero(d);
+        of.to_pos(d, x, &mut pos);
+        p.push(pos);
+    }
+    b.clear();
+    for (&i, y) in axes.iter().zip(ys) {
+        let mut pos = of.zero(&dim[i]);
+        of.to_pos(&dim[i], y, &mut pos);
+        b.push(pos);
+    }
+}
+
+/// Same as `to_index`, but converts the inner positions with temporary values.
+fn to_index_with<N, T>(
+    scratch: &mut Scratch<N>,
+    dim: &[T::Dim],
+    k: usize,
+    directed: bool,
+    (p, axes, b): &(Vec<T::Pos>, Vec<usize>, Vec<T::Pos>),
+    index: &mut N,
+)
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> + Mul<&'a N, Output = N>,
+{
+    let of: T = Construct::new();
+    let w = Weights::new::<T>(dim, k, directed);
+    let mut xs = scratch.take_many(p.len());
+    let mut ys = scratch.take_many(b.len());
+    for ((d, x), i) in dim.iter().zip(p).zip(&mut xs) {
+        of.to_index_with(scratch, d, x, i);
+    }
+    for ((&j, y), i) in axes.iter().zip(b).zip(&mut ys) {
+        of.to_index_with(scratch, &dim[j], y, i);
+    }
+    *index = indices_to_index(&w, k, &xs, axes, &ys);
+    scratch.put_many(xs);
+    scratch.put_many(ys);
+}
+
+/// Same as `to_pos`, but reuses the inner positions.
+fn to_pos_with<N, T>(
+    scratch: &mut Scratch<N>,
+    dim: &[T::Dim],
+    k: usize,
+    directed: bool,
+    index: &N,
+    (p, axes, b): &mut (Vec<T::Pos>, Vec<usize>, Vec<T::Pos>),
+)
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    let of: T = Construct::new();
+    let w = Weights::new::<T>(dim, k, directed);
+    let (mut xs, mut ys) = (vec![], vec![]);
+    index_to_indices(&w, k, index.clone(), &mut xs, axes, &mut ys);
+    p.truncate(dim.len());
+    for d in &dim[p.len()..] {p.push(of.zero(d))}
+    for ((d, x), pos) in dim.iter().zip(&xs).zip(p.iter_mut()) {
+        of.to_pos_with(scratch, d, x, pos);
+    }
+    b.truncate(axes.len());
+    for &i in &axes[b.len()..] {b.push(of.zero(&dim[i]))}
+    for ((&i, y), pos) in axes.iter().zip(&ys).zip(b.iter_mut()) {
+        of.to_pos_with(scratch, &dim[i], y, pos);
+    }
+}
+
+impl Space<usize> for KContext<Data> {
+    type Dim = (Vec<usize>, usize);
+    type Pos = (Vec<usize>, Vec<usize>, Vec<usize>);
+    fn count(&self, (dim, k): &Self::Dim) -> usize {
+        count::<usize, Dimension>(dim, *k, false)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        zero::<usize, Dimension>(dim, *k)
+    }
+    fn to_index(&self, (dim, k): &Self::Dim, pos: &Self::Pos) -> usize {
+        to_index::<usize, Dimension>(dim, *k, false, pos)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: usize, pos: &mut Self::Pos) {
+        to_pos::<usize, Dimension>(dim, *k, false, index, pos)
+    }
+}
+
+impl Space<BigUint> for KContext<Data> {
+    type Dim = (Vec<BigUint>, usize);
+    type Pos = (Vec<BigUint>, Vec<usize>, Vec<BigUint>);
+    fn count(&self, (dim, k): &Self::Dim) -> BigUint {
+        count::<BigUint, Dimension>(dim, *k, false)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        zero::<BigUint, Dimension>(dim, *k)
+    }
+    fn to_index(&self, (dim, k): &Self::Dim, pos: &Self::Pos) -> BigUint {
+        to_index::<BigUint, Dimension>(dim, *k, false, pos)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        to_pos::<BigUint, Dimension>(dim, *k, false, index, pos)
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        (dim, k): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        to_index_with::<BigUint, Dimension>(scratch, dim, *k, false, pos, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        (dim, k): &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        to_pos_with::<BigUint, Dimension>(scratch, dim, *k, false, index, pos)
+    }
+}
+
+impl<N, T> Space<N> for KContext<Of<T>>
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    type Dim = (Vec<T::Dim>, usize);
+    type Pos = (Vec<T::Pos>, Vec<usize>, Vec<T::Pos>);
+    fn count(&self, (dim, k): &Self::Dim) -> N {
+        count::<N, T>(dim, *k, false)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        zero::<N, T>(dim, *k)
+    }
+    fn to_index(&self, (dim, k): &Self::Dim, pos: &Self::Pos) -> N {
+        to_index::<N, T>(dim, *k, false, pos)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        to_pos::<N, T>(dim, *k, false, index, pos)
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim, k): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        to_index_with::<N, T>(scratch, dim, *k, false, pos, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim, k): &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        to_pos_with::<N, T>(scratch, dim, *k, false, index, pos)
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let (p, axes, b) = pos;
+        for (d, x) in dim.0.iter().zip(p) {
+            of.try_to_index(d, x)?;
+        }
+        for (&i, x) in axes.iter().zip(b) {
+            of.try_to_index(&dim.0[i], x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+impl Space<usize> for DirectedKContext<Data> {
+    type Dim = (Vec<usize>, usize);
+    type Pos = (Vec<usize>, Vec<usize>, Vec<usize>);
+    fn count(&self, (dim, k): &Self::Dim) -> usize {
+        count::<usize, Dimension>(dim, *k, true)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        zero::<usize, Dimension>(dim, *k)
+    }
+    fn to_index(&self, (dim, k): &Self::Dim, pos: &Self::Pos) -> usize {
+        to_index::<usize, Dimension>(dim, *k, true, pos)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: usize, pos: &mut Self::Pos) {
+        to_pos::<usize, Dimension>(dim, *k, true, index, pos)
+    }
+}
+
+impl Space<BigUint> for DirectedKContext<Data> {
+    type Dim = (Vec<BigUint>, usize);
+    type Pos = (Vec<BigUint>, Vec<usize>, Vec<BigUint>);
+    fn count(&self, (dim, k): &Self::Dim) -> BigUint {
+        count::<BigUint, Dimension>(dim, *k, true)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        zero::<BigUint, Dimension>(dim, *k)
+    }
+    fn to_index(&self, (dim, k): &Self::Dim, pos: &Self::Pos) -> BigUint {
+        to_index::<BigUint, Dimension>(dim, *k, true, pos)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        to_pos::<BigUint, Dimension>(dim, *k, true, index, pos)
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        (dim, k): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        to_index_with::<BigUint, Dimension>(scratch, dim, *k, true, pos, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        (dim, k): &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        to_pos_with::<BigUint, Dimension>(scratch, dim, *k, true, index, pos)
+    }
+}
+
+impl<N, T> Space<N> for DirectedKContext<Of<T>>
+    where T: Space<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone + From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    type Dim = (Vec<T::Dim>, usize);
+    type Pos = (Vec<T::Pos>, Vec<usize>, Vec<T::Pos>);
+    fn count(&self, (dim, k): &Self::Dim) -> N {
+        count::<N, T>(dim, *k, true)
+    }
+    fn zero(&self, (dim, k): &Self::Dim) -> Self::Pos {
+        zero::<N, T>(dim, *k)
+    }
+    fn to_index(&self, (dim, k): &Self::Dim, pos: &Self::Pos) -> N {
+        to_index::<N, T>(dim, *k, true, pos)
+    }
+    fn to_pos(&self, (dim, k): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        to_pos::<N, T>(dim, *k, true, index, pos)
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim, k): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        to_index_with::<N, T>(scratch, dim, *k, true, pos, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim, k): &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        to_pos_with::<N, T>(scratch, dim, *k, true, index, pos)
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let (p, axes, b) = pos;
+        for (d, x) in dim.0.iter().zip(p) {
+            of.try_to_index(d, x)?;
+        }
+        for (&i, x) in axes.iter().zip(b) {
+            of.try_to_index(&dim.0[i], x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, KContext>();
+        is_complete::<usize, KContext<Of<Pair>>>();
+        is_complete::<usize, DirectedKContext>();
+        is_complete::<usize, DirectedKContext<Of<Pair>>>();
+    }
+
+    #[test]
+    fn data() {
+        let x: KContext = Construct::new();
+        // A cube has 12 edges, 12 face diagonals and 4 space diagonals.
+        assert_eq!(x.count(&(vec![2, 2, 2], 1)), 12);
+        assert_eq!(x.count(&(vec![2, 2, 2], 2)), 12);
+        assert_eq!(x.count(&(vec![2, 2, 2], 3)), 4);
+        assert_eq!(x.count(&(vec![2, 2, 2], 0)), 8);
+        let ref dim = (vec![2, 3, 4], 2);
+        assert_eq!(x.count(dim), 6 * 4 + 12 * 3 + 3 * 12 * 2);
+        assert_eq!(x.to_index(dim, &(vec![0, 0, 0], vec![0, 1], vec![1, 1])), 0);
+        // Swapping the end points gives the same edge.
+        assert_eq!(x.to_index(dim, &(vec![1, 1, 0], vec![0, 1], vec![0, 0])), 0);
+        assert_eq!(
+            x.to_index(dim, &(vec![0, 2, 3], vec![0, 2], vec![1, 1])),
+            x.to_index(dim, &(vec![1, 2, 1], vec![0, 2], vec![0, 3]))
+        );
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(pos.1.len(), 2);
+            assert!(pos.0[pos.1[0]] < pos.2[0]);
+            assert_ne!(pos.0[pos.1[1]], pos.2[1]);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+
+    #[test]
+    fn same_as_context() {
+        let x: KContext = Construct::new();
+        let context: Context = Construct::new();
+        let ref dim = vec![3, 2, 4];
+        let ref k_dim = (dim.clone(), 1);
+        assert_eq!(x.count(k_dim), context.count(dim));
+        let mut pos = x.zero(k_dim);
+        let mut context_pos = context.zero(dim);
+        for i in 0..x.count(k_dim) {
+            x.to_pos(k_dim, i, &mut pos);
+            context.to_pos(dim, i, &mut context_pos);
+            assert_eq!(pos, (context_pos.0.clone(), vec![context_pos.1], vec![context_pos.2]));
+        }
+    }
+
+    /// Lists the sets of changed axes in the order they appear.
+    fn axis_blocks<S>(dim: &(Vec<usize>, usize)) -> Vec<Vec<usize>>
+        where S: space::Space<usize, Dim = (Vec<usize>, usize),
+                               Pos = (Vec<usize>, Vec<usize>, Vec<usize>)>
+    {
+        let x: S = Construct::new();
+        let mut blocks: Vec<Vec<usize>> = vec![];
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            if blocks.last() != Some(&pos.1) {blocks.push(pos.1.clone())}
+        }
+        blocks
+    }
+
+    #[test]
+    fn combination_order() {
+        let combination: Combination = Construct::new();
+        let ref dim = (vec![2, 4, 3, 2, 3], 3);
+        let mut expected = vec![];
+        let mut axes = vec![];
+        for i in 0..combination.count(&(5, 3)) {
+            combination.to_pos(&(5, 3), i, &mut axes);
+            expected.push(axes.clone());
+        }
+        // Each set of changed axes is one block, in the order of `Combination`.
+        assert_eq!(axis_blocks::<KContext>(dim), expected);
+        assert_eq!(axis_blocks::<DirectedKContext>(dim), expected);
+    }
+
+    #[test]
+    fn data_big() {
+        use std::convert::TryInto;
+
+        let x: KContext = Construct::new();
+        let ref dim: (Vec<BigUint>, usize) = (vec![2usize.into(), 3usize.into(), 4usize.into()], 2);
+        assert_eq!(x.count(dim), 132usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+
+    #[test]
+    fn directed() {
+        let x: DirectedKContext = Construct::new();
+        assert_eq!(x.count(&(vec![2, 2, 2], 2)), 24);
+        let ref dim = (vec![3, 3, 2], 2);
+        assert_eq!(x.count(dim), 6 * 6 * 2 + 6 * 2 * 3 + 6 * 2 * 3);
+        assert_eq!(x.count(&(vec![3, 2], 1)), DirectedContext::<Data>::new().count(&vec![3, 2]));
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            for (j, &a) in pos.1.iter().enumerate() {
+                assert_ne!(pos.0[a], pos.2[j]);
+            }
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+
+    #[test]
+    fn directed_big() {
+        use std::convert::TryInto;
+
+        let x: DirectedKContext = Construct::new();
+        let ref dim: (Vec<BigUint>, usize) = (vec![3usize.into(), 3usize.into(), 2usize.into()], 2);
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+
+    #[test]
+    fn of() {
+        let x: KContext<Of<Pair>> = Construct::new();
+        let ref dim = (vec![3, 3, 4], 2);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
+}
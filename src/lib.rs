@@ -88,21 +88,50 @@ pub use zero::Zero;
 pub use to_index::ToIndex;
 pub use to_pos::ToPos;
 
-pub use power_set::PowerSet;
+pub use power_set::{PowerSet, Bits};
+pub use gray_power_set::GrayPowerSet;
 pub use dimension_n::DimensionN;
+pub use gray_dimension_n::GrayDimensionN;
 pub use dimension::Dimension;
 pub use pair::Pair;
 pub use eq_pair::EqPair;
 pub use neq_pair::NeqPair;
 pub use sq_pair::SqPair;
 pub use permutation::Permutation;
-pub use context::Context;
+pub use context::{Context, ContextTables};
 pub use directed_context::DirectedContext;
 pub use either::{Either, Select};
-pub use homotopy::{Homotopy, HPoint};
+pub use homotopy::{Homotopy, HPoint, Leaves, ParseHPointError};
+pub use homotopy_up_to::HomotopyUpTo;
+pub use directed_homotopy::{DirectedHomotopy, DirectedLoopFreeHomotopy};
+pub use seq_up_to::SeqUpTo;
+pub use incidence::Incidence;
+pub use unbounded::Unbounded;
+pub use natural::Natural;
+pub use cantor::Cantor;
+pub use szudzik::Szudzik;
+pub use finite_set::FiniteSet;
+pub use hereditarily_finite::{HereditarilyFinite, HFSet};
+pub use combination::Combination;
+pub use k_context::{KContext, DirectedKContext};
+pub use permutation_context::{PermutationContext, Swaps};
+pub use space_vec::SpaceVec;
+pub use sparse_space_map::SparseSpaceMap;
+pub use space_bit_set::SpaceBitSet;
+pub use prepared::{Prepare, Prepared};
+pub use scratch::Scratch;
+pub use order::{Lex, Colex, Reversed};
+pub use view::{Slice, Stride};
+pub use dyn_space::{DynSpace, DynDim, DynPos, ParseDynError};
 pub use num_bigint::BigUint;
 
 pub mod space;
+pub mod prepared;
+pub mod dyn_space;
+pub mod unbounded;
+pub mod search;
+#[cfg(feature = "rayon")]
+pub mod par;
 
 mod construct;
 mod count;
@@ -111,7 +140,9 @@ mod to_index;
 mod to_pos;
 
 mod power_set;
+mod gray_power_set;
 mod dimension_n;
+mod gray_dimension_n;
 mod dimension;
 mod pair;
 mod eq_pair;
@@ -123,6 +154,24 @@ mod directed_context;
 mod subspace;
 mod either;
 mod homotopy;
+mod directed_homotopy;
+mod homotopy_up_to;
+mod incidence;
+mod seq_up_to;
+mod natural;
+mod cantor;
+mod szudzik;
+mod finite_set;
+mod hereditarily_finite;
+mod combination;
+mod k_context;
+mod permutation_context;
+mod space_vec;
+mod sparse_space_map;
+mod space_bit_set;
+mod scratch;
+mod order;
+mod view;
 
 /// Used by the final subspace.
 #[derive(Copy, Clone)]
@@ -0,0 +1,44 @@
+use crate::{
+    BigUint,
+    Construct,
+    unbounded::Unbounded,
+};
+
+/// An unbounded space of all natural numbers, position is the same as index.
+pub struct Natural;
+
+impl Construct for Natural {
+    fn new() -> Self { Natural }
+}
+
+impl Unbounded<usize> for Natural {
+    type Pos = usize;
+    fn zero(&self) -> usize { 0 }
+    fn to_index(&self, pos: &usize) -> usize { *pos }
+    fn to_pos(&self, index: usize, pos: &mut usize) {
+        *pos = index;
+    }
+}
+
+impl Unbounded<BigUint> for Natural {
+    type Pos = BigUint;
+    fn zero(&self) -> BigUint { 0usize.into() }
+    fn to_index(&self, pos: &BigUint) -> BigUint { pos.clone() }
+    fn to_pos(&self, index: BigUint, pos: &mut BigUint) {
+        *pos = index;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn data() {
+        let x: Natural = Construct::new();
+        assert_eq!(Unbounded::<usize>::to_index(&x, &5), 5);
+        let mut pos: BigUint = 0usize.into();
+        x.to_pos(BigUint::from(7usize), &mut pos);
+        assert_eq!(pos, 7usize.into());
+    }
+}
@@ -5,6 +5,8 @@ use crate::{
     Construct,
     Data,
     Of,
+    Scratch,
+    prepared::Prepare,
     space::Space,
 };
 
@@ -19,7 +21,7 @@ impl<T> Construct for NeqPair<T> {
 impl Space<usize> for NeqPair<Data> {
     type Dim = usize;
     type Pos = (usize, usize);
-    fn count(&self, dim: &usize) -> usize { dim * (dim - 1) }
+    fn count(&self, dim: &usize) -> usize { dim * dim.saturating_sub(1) }
     fn zero(&self, _dim: &usize) -> (usize, usize) { (0, 0) }
     fn to_index(&self, dim: &usize, &(a, b): &(usize, usize)) -> usize {
         use crate::Pair;
@@ -50,6 +52,9 @@ impl Space<BigUint> for NeqPair<Data> {
     type Dim = BigUint;
     type Pos = (BigUint, BigUint);
     fn count(&self, dim: &BigUint) -> BigUint {
+        use num_traits::Zero;
+
+        if dim.is_zero() {return BigUint::zero()}
         dim * (dim - 1usize)
     }
     fn zero(&self, _dim: &BigUint) -> (BigUint, BigUint) { (0usize.into(), 0usize.into()) }
@@ -74,6 +79,43 @@ impl Space<BigUint> for NeqPair<Data> {
             std::mem::swap(&mut pos.0, &mut pos.1);
         }
     }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        (a, b): &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use crate::pair::biguint_pair_index_with;
+
+        if a < b {
+            biguint_pair_index_with(a, b, index);
+            *index <<= 1;
+        } else {
+            biguint_pair_index_with(b, a, index);
+            *index <<= 1;
+            *index += 1u32;
+        }
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        use crate::Pair;
+
+        let pair: Pair<Data> = Construct::new();
+        let mut half = scratch.take();
+        half.clone_from(index);
+        half >>= 1;
+        pair.to_pos_with(scratch, dim, &half, pos);
+        if index.bit(0) {
+            std::mem::swap(&mut pos.0, &mut pos.1);
+        }
+        scratch.put(half);
+    }
 }
 
 impl<N, T> Space<N> for NeqPair<Of<T>>
@@ -117,6 +159,93 @@ impl<N, T> Space<N> for NeqPair<Of<T>>
         of.to_pos(dim, pair_min, min);
         of.to_pos(dim, pair_max, max);
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        (min, max): &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: NeqPair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        of.to_index_with(scratch, dim, min, &mut pair.0);
+        of.to_index_with(scratch, dim, max, &mut pair.1);
+        data.to_index_with(scratch, &of.count(dim), &pair, index);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        (min, max): &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: NeqPair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        data.to_pos_with(scratch, &of.count(dim), index, &mut pair);
+        of.to_pos_with(scratch, dim, &pair.0, min);
+        of.to_pos_with(scratch, dim, &pair.1, max);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, (min, max): &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let data: NeqPair<Data> = Construct::new();
+        let min = of.try_to_index(dim, min)?;
+        let max = of.try_to_index(dim, max)?;
+        Some(data.to_index(&self.count(dim), &(min, max)))
+    }
+}
+
+impl<N, T> Prepare<N> for NeqPair<Of<T>>
+    where T: Prepare<N>,
+          NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+{
+    /// Tables of the inner space, followed by its count.
+    type Tables = (T::Tables, N);
+    fn tables(&self, dim: &Self::Dim) -> Self::Tables {
+        let of: T = Construct::new();
+        let tables = of.tables(dim);
+        let count = of.prepared_count(dim, &tables);
+        (tables, count)
+    }
+    fn prepared_count(&self, _dim: &Self::Dim, (_, count): &Self::Tables) -> N {
+        let data: NeqPair<Data> = Construct::new();
+        data.count(count)
+    }
+    fn prepared_to_index(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        (min, max): &Self::Pos,
+    ) -> N {
+        let of: T = Construct::new();
+        let data: NeqPair<Data> = Construct::new();
+        let min = of.prepared_to_index(dim, tables, min);
+        let max = of.prepared_to_index(dim, tables, max);
+        data.to_index(count, &(min, max))
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        index: N,
+        (min, max): &mut Self::Pos,
+    ) {
+        let of: T = Construct::new();
+        let data: NeqPair<Data> = Construct::new();
+        let mut pair = data.zero(count);
+        data.to_pos(count, index, &mut pair);
+        of.prepared_to_pos(dim, tables, pair.0, min);
+        of.prepared_to_pos(dim, tables, pair.1, max);
+    }
 }
 
 #[cfg(test)]
@@ -134,6 +263,7 @@ mod tests {
         let x: NeqPair = Construct::new();
         let ref dim = 4;
         assert_eq!(x.count(dim), 12);
+        assert_eq!(x.count(&0), 0);
         assert_eq!(x.to_index(dim, &(0, 1)), 0);
         assert_eq!(x.to_index(dim, &(1, 0)), 1);
         assert_eq!(x.to_index(dim, &(0, 2)), 2);
@@ -157,6 +287,7 @@ mod tests {
         let x: NeqPair = Construct::new();
         let ref dim: BigUint = 4usize.into();
         assert_eq!(x.count(dim), 12usize.into());
+        assert_eq!(x.count(&BigUint::from(0usize)), 0usize.into());
         assert_eq!(x.to_index(dim, &conv_pos((0, 1))), 0usize.into());
         assert_eq!(x.to_index(dim, &conv_pos((1, 0))), 1usize.into());
         assert_eq!(x.to_index(dim, &conv_pos((0, 2))), 2usize.into());
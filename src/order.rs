@@ -0,0 +1,463 @@
+//! Wrappers that select the ordering of a space.
+//!
+//! The wrappers have the same dimension and position types as the wrapped space,
+//! but convert between indices and positions using another ordering.
+//!
+//! | Space | Default order | `Lex` | `Colex` |
+//! |---|---|---|---|
+//! | `Pair`, `EqPair`, `SqPair` | colex | by first, then second | by second, then first |
+//! | `NeqPair` | interleaved | by first, then second | by second, then first |
+//! | `Permutation` | lex | from first item | from last item |
+//! | `PowerSet` | colex | sorted lists of numbers | by largest number |
+//!
+//! `Lex` and `Colex` only support the `Data` variants of these spaces,
+//! e.g. `Lex<Pair>` but not `Lex<Pair<Of<T>>>`.
+//! `Reversed` supports any space.
+
+use std::convert::TryInto;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+use crate::{
+    Bits,
+    Construct,
+    Data,
+    EqPair,
+    NeqPair,
+    Pair,
+    Permutation,
+    PowerSet,
+    SqPair,
+    space::Space,
+};
+
+/// Orders positions lexicographically, comparing the first component first.
+///
+/// Supports `Pair`, `EqPair`, `NeqPair`, `SqPair`, `Permutation` and `PowerSet`.
+///
+/// - Pairs are ordered by the first number, then by the second number,
+///   e.g. `(0, 1), (0, 2), (0, 3), (1, 2)` for `Lex<Pair>` with dimension 4.
+/// - Permutations are ordered by the first item, then the second item etc.
+///   This is the same order as `Permutation`.
+/// - Sets are ordered as sorted lists of numbers, where a list comes before its extensions,
+///   e.g. `[], [0], [0, 1], [1]` for `Lex<PowerSet>`.
+pub struct Lex<T>(PhantomData<T>);
+
+/// Orders positions colexicographically, comparing the last component first.
+///
+/// Supports `Pair`, `EqPair`, `NeqPair`, `SqPair`, `Permutation` and `PowerSet`.
+///
+/// - Pairs are ordered by the second number, then by the first number,
+///   e.g. `(0, 1), (0, 2), (1, 2), (0, 3)` for `Colex<Pair>` with dimension 4.
+///   This is the same order as `Pair`, `EqPair` and `SqPair`.
+/// - Permutations are ordered by the last item, then the second last item etc.
+/// - Sets are ordered by their largest number, which is the binary order of `PowerSet`,
+///   e.g. `[], [0], [1], [0, 1]` for `Colex<PowerSet>`.
+pub struct Colex<T>(PhantomData<T>);
+
+/// Reverses the order of any space, such that index `0` becomes the last position.
+pub struct Reversed<T>(PhantomData<T>);
+
+impl<T> Construct for Lex<T> {
+    fn new() -> Self {Lex(PhantomData)}
+}
+
+impl<T> Construct for Colex<T> {
+    fn new() -> Self {Colex(PhantomData)}
+}
+
+impl<T> Construct for Reversed<T> {
+    fn new() -> Self {Reversed(PhantomData)}
+}
+
+/// Returns `count - 1 - index`.
+fn reverse_index<N>(count: &N, index: &N) -> N
+    where N: From<usize>,
+          for<'a> &'a N: Sub<&'a N, Output = N>,
+{
+    let one: N = 1usize.into();
+    &(count - &one) - index
+}
+
+impl<N, T> Space<N> for Reversed<T>
+    where T: Space<N>,
+          N: From<usize>,
+          for<'a> &'a N: Sub<&'a N, Output = N>,
+{
+    type Dim = T::Dim;
+    type Pos = T::Pos;
+    fn count(&self, dim: &Self::Dim) -> N {
+        let of: T = Construct::new();
+        of.count(dim)
+    }
+    fn zero(&self, dim: &Self::Dim) -> Self::Pos {
+        let of: T = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> N {
+        let of: T = Construct::new();
+        reverse_index(&of.count(dim), &of.to_index(dim, pos))
+    }
+    fn to_pos(&self, dim: &Self::Dim, index: N, pos: &mut Self::Pos) {
+        let of: T = Construct::new();
+        of.to_pos(dim, reverse_index(&of.count(dim), &index), pos)
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        Some(reverse_index(&of.count(dim), &of.try_to_index(dim, pos)?))
+    }
+}
+
+/// Implements an ordering which is the same as the default ordering of a space.
+macro_rules! same_order {
+    ($order:ident, $space:ident) => {
+        impl<N> Space<N> for $order<$space<Data>>
+            where $space<Data>: Space<N>
+        {
+            type Dim = <$space<Data> as Space<N>>::Dim;
+            type Pos = <$space<Data> as Space<N>>::Pos;
+            fn count(&self, dim: &Self::Dim) -> N {
+                let of: $space<Data> = Construct::new();
+                of.count(dim)
+            }
+            fn zero(&self, dim: &Self::Dim) -> Self::Pos {
+                let of: $space<Data> = Construct::new();
+                of.zero(dim)
+            }
+            fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> N {
+                let of: $space<Data> = Construct::new();
+                of.to_index(dim, pos)
+            }
+            fn to_pos(&self, dim: &Self::Dim, index: N, pos: &mut Self::Pos) {
+                let of: $space<Data> = Construct::new();
+                of.to_pos(dim, index, pos)
+            }
+            fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+                let of: $space<Data> = Construct::new();
+                of.try_to_index(dim, pos)
+            }
+        }
+    };
+}
+
+same_order!{Colex, Pair}
+same_order!{Colex, EqPair}
+same_order!{Colex, SqPair}
+same_order!{Colex, PowerSet}
+same_order!{Lex, Permutation}
+
+/// Implements the lexicographic ordering of pairs `(min, max)`,
+/// by mirroring the numbers and reversing the colexicographic ordering.
+macro_rules! lex_pair {
+    ($space:ident) => {
+        impl<N> Space<N> for Lex<$space<Data>>
+            where $space<Data>: Space<N, Dim = N, Pos = (N, N)>,
+                  N: From<usize>,
+                  for<'a> &'a N: Sub<&'a N, Output = N>,
+        {
+            type Dim = N;
+            type Pos = (N, N);
+            fn count(&self, dim: &N) -> N {
+                let of: $space<Data> = Construct::new();
+                of.count(dim)
+            }
+            fn zero(&self, dim: &N) -> (N, N) {
+                let of: $space<Data> = Construct::new();
+                of.zero(dim)
+            }
+            fn to_index(&self, dim: &N, (min, max): &(N, N)) -> N {
+                let of: $space<Data> = Construct::new();
+                let mirrored = (reverse_index(dim, max), reverse_index(dim, min));
+                reverse_index(&of.count(dim), &of.to_index(dim, &mirrored))
+            }
+            fn to_pos(&self, dim: &N, index: N, pos: &mut (N, N)) {
+                let of: $space<Data> = Construct::new();
+                of.to_pos(dim, reverse_index(&of.count(dim), &index), pos);
+                let min = reverse_index(dim, &pos.1);
+                pos.1 = reverse_index(dim, &pos.0);
+                pos.0 = min;
+            }
+        }
+    };
+}
+
+lex_pair!{Pair}
+lex_pair!{EqPair}
+
+impl<N> Space<N> for Lex<SqPair<Data>>
+    where SqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: Clone,
+{
+    type Dim = N;
+    type Pos = (N, N);
+    fn count(&self, dim: &N) -> N {
+        let of: SqPair<Data> = Construct::new();
+        of.count(dim)
+    }
+    fn zero(&self, dim: &N) -> (N, N) {
+        let of: SqPair<Data> = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &N, (a, b): &(N, N)) -> N {
+        let of: SqPair<Data> = Construct::new();
+        of.to_index(dim, &(b.clone(), a.clone()))
+    }
+    fn to_pos(&self, dim: &N, index: N, pos: &mut (N, N)) {
+        let of: SqPair<Data> = Construct::new();
+        of.to_pos(dim, index, pos);
+        std::mem::swap(&mut pos.0, &mut pos.1);
+    }
+}
+
+/// Converts a directed pair `(a, b)` to index, ordered by `a` first.
+fn neq_pair_to_index<N>(dim: &N, a: &N, b: &N) -> N
+    where N: From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N>,
+{
+    let one: N = 1usize.into();
+    let index = &(a * &(dim - &one)) + b;
+    // Skip `b == a`, which is not part of the space.
+    if b > a {&index - &one} else {index}
+}
+
+/// Converts index to a directed pair `(a, b)`, ordered by `a` first.
+fn neq_pair_to_pos<N>(dim: &N, index: &N) -> (N, N)
+    where N: From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    let one: N = 1usize.into();
+    let size = dim - &one;
+    let a = index / &size;
+    let b = index % &size;
+    let b = if b >= a {&b + &one} else {b};
+    (a, b)
+}
+
+impl<N> Space<N> for Lex<NeqPair<Data>>
+    where NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    type Dim = N;
+    type Pos = (N, N);
+    fn count(&self, dim: &N) -> N {
+        let of: NeqPair<Data> = Construct::new();
+        of.count(dim)
+    }
+    fn zero(&self, dim: &N) -> (N, N) {
+        let of: NeqPair<Data> = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &N, (a, b): &(N, N)) -> N {
+        neq_pair_to_index(dim, a, b)
+    }
+    fn to_pos(&self, dim: &N, index: N, pos: &mut (N, N)) {
+        *pos = neq_pair_to_pos(dim, &index);
+    }
+}
+
+impl<N> Space<N> for Colex<NeqPair<Data>>
+    where NeqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+          N: From<usize> + PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    type Dim = N;
+    type Pos = (N, N);
+    fn count(&self, dim: &N) -> N {
+        let of: NeqPair<Data> = Construct::new();
+        of.count(dim)
+    }
+    fn zero(&self, dim: &N) -> (N, N) {
+        let of: NeqPair<Data> = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &N, (a, b): &(N, N)) -> N {
+        neq_pair_to_index(dim, b, a)
+    }
+    fn to_pos(&self, dim: &N, index: N, pos: &mut (N, N)) {
+        let (b, a) = neq_pair_to_pos(dim, &index);
+        *pos = (a, b);
+    }
+}
+
+impl<N> Space<N> for Colex<Permutation<Data>>
+    where Permutation<Data>: Space<N, Pos = Vec<N>>,
+          N: Clone,
+{
+    type Dim = <Permutation<Data> as Space<N>>::Dim;
+    type Pos = Vec<N>;
+    fn count(&self, dim: &Self::Dim) -> N {
+        let of: Permutation<Data> = Construct::new();
+        of.count(dim)
+    }
+    fn zero(&self, dim: &Self::Dim) -> Vec<N> {
+        let of: Permutation<Data> = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &Self::Dim, pos: &Vec<N>) -> N {
+        let of: Permutation<Data> = Construct::new();
+        let reversed: Vec<N> = pos.iter().rev().cloned().collect();
+        of.to_index(dim, &reversed)
+    }
+    fn to_pos(&self, dim: &Self::Dim, index: N, pos: &mut Vec<N>) {
+        let of: Permutation<Data> = Construct::new();
+        of.to_pos(dim, index, pos);
+        pos.reverse();
+    }
+}
+
+impl<N> Space<N> for Lex<PowerSet<Data>>
+    where PowerSet<Data>: Space<N, Dim = N, Pos = Vec<N>>,
+          N: Bits + Clone + From<usize> + TryInto<usize> + PartialOrd,
+          <N as TryInto<usize>>::Error: Debug,
+          for<'a> &'a N: Add<&'a N, Output = N> + Sub<&'a N, Output = N>,
+{
+    type Dim = N;
+    type Pos = Vec<N>;
+    fn count(&self, dim: &N) -> N {
+        let of: PowerSet<Data> = Construct::new();
+        of.count(dim)
+    }
+    fn zero(&self, _dim: &N) -> Vec<N> {
+        vec![]
+    }
+    fn to_index(&self, dim: &N, pos: &Vec<N>) -> N {
+        let n: usize = dim.clone().try_into().unwrap();
+        let mut items: Vec<usize> = pos.iter().map(|x| x.clone().try_into().unwrap()).collect();
+        items.sort_unstable();
+        // Each number skips the sets that start with the smaller numbers before it,
+        // plus the set that ends before it.
+        let mut index: N = 0usize.into();
+        let mut next = 0;
+        for x in items {
+            let skipped = &N::power_of_two(n - next) - &N::power_of_two(n - x);
+            index = &(&index + &skipped) + &N::from(1usize);
+            next = x + 1;
+        }
+        index
+    }
+    fn to_pos(&self, dim: &N, mut index: N, pos: &mut Vec<N>) {
+        let n: usize = dim.clone().try_into().unwrap();
+        let zero: N = 0usize.into();
+        let one: N = 1usize.into();
+        pos.clear();
+        let mut x = 0;
+        while index > zero {
+            index = &index - &one;
+            loop {
+                // The number of sets starting with `x` after the previous numbers.
+                let block = N::power_of_two(n - 1 - x);
+                if index < block {break}
+                index = &index - &block;
+                x += 1;
+            }
+            pos.push(x.into());
+            x += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// Checks that an ordering is a bijection which is sorted by a key.
+    fn check<S, K>(dim: S::Dim, key: impl Fn(&S::Pos) -> K)
+        where S: space::Space<usize>,
+              S::Pos: Clone + std::fmt::Debug,
+              K: Ord + std::fmt::Debug,
+    {
+        let x: S = Construct::new();
+        let count = x.count(&dim);
+        let mut pos = x.zero(&dim);
+        let mut keys = vec![];
+        for i in 0..count {
+            x.to_pos(&dim, i, &mut pos);
+            assert_eq!(x.to_index(&dim, &pos), i);
+            keys.push(key(&pos));
+        }
+        assert!(keys.windows(2).all(|w| w[0] < w[1]), "{:?}", keys);
+    }
+
+    #[test]
+    fn pairs() {
+        let lex = |&(a, b): &(usize, usize)| (a, b);
+        let colex = |&(a, b): &(usize, usize)| (b, a);
+        check::<Lex<Pair>, _>(6, lex);
+        check::<Colex<Pair>, _>(6, colex);
+        check::<Lex<EqPair>, _>(6, lex);
+        check::<Colex<EqPair>, _>(6, colex);
+        check::<Lex<NeqPair>, _>(6, lex);
+        check::<Colex<NeqPair>, _>(6, colex);
+        check::<Lex<SqPair>, _>(6, lex);
+        check::<Colex<SqPair>, _>(6, colex);
+        check::<Reversed<Lex<Pair>>, _>(6, |&(a, b)| std::cmp::Reverse((a, b)));
+
+        let x: Lex<Pair> = Construct::new();
+        let mut pos = (0, 0);
+        x.to_pos(&4, 2, &mut pos);
+        assert_eq!(pos, (0, 3));
+    }
+
+    #[test]
+    fn permutations() {
+        check::<Lex<Permutation>, _>(4, |p: &Vec<usize>| p.clone());
+        check::<Colex<Permutation>, _>(4, |p: &Vec<usize>| p.iter().rev().cloned().collect::<Vec<_>>());
+        check::<Reversed<Permutation>, _>(4, |p: &Vec<usize>| std::cmp::Reverse(p.clone()));
+    }
+
+    #[test]
+    fn sets() {
+        check::<Lex<PowerSet>, _>(5, |p: &Vec<usize>| p.clone());
+        check::<Colex<PowerSet>, _>(5, |p: &Vec<usize>| p.iter().rev().cloned().collect::<Vec<_>>());
+
+        let x: Lex<PowerSet> = Construct::new();
+        let mut pos = vec![];
+        x.to_pos(&3, 4, &mut pos);
+        assert_eq!(pos, vec![0, 2]);
+        assert_eq!(x.to_index(&3, &vec![2, 0]), 4);
+    }
+
+    #[test]
+    fn big() {
+        let n = |x: usize| -> BigUint {x.into()};
+
+        let x: Lex<Pair> = Construct::new();
+        let mut pos = (n(0), n(0));
+        x.to_pos(&n(4), n(2), &mut pos);
+        assert_eq!(pos, (n(0), n(3)));
+        assert_eq!(x.to_index(&n(4), &pos), n(2));
+
+        let x: Colex<NeqPair> = Construct::new();
+        x.to_pos(&n(4), n(5), &mut pos);
+        assert_eq!(pos, (n(3), n(1)));
+        assert_eq!(x.to_index(&n(4), &pos), n(5));
+
+        let x: Lex<PowerSet> = Construct::new();
+        let ref dim = n(100);
+        let count: BigUint = x.count(dim);
+        let mut pos = vec![];
+        x.to_pos(dim, &count - 1usize, &mut pos);
+        assert_eq!(pos, vec![n(99)]);
+        assert_eq!(x.to_index(dim, &pos), &count - 1usize);
+
+        let x: Reversed<Permutation> = Construct::new();
+        let mut pos = vec![];
+        x.to_pos(&n(3), n(0), &mut pos);
+        assert_eq!(pos, vec![n(2), n(1), n(0)]);
+    }
+}
@@ -5,6 +5,8 @@ use crate::{
     Construct,
     Data,
     Of,
+    Scratch,
+    prepared::Prepare,
     space::Space,
 };
 
@@ -15,23 +17,91 @@ impl<T> Construct for Pair<T> {
     fn new() -> Self { Pair(PhantomData) }
 }
 
+/// Computes `m * (m + 1) / 2`, or `None` on overflow.
+fn checked_triangular(m: usize) -> Option<usize> {
+    if m & 1 == 0 {(m / 2).checked_mul(m + 1)} else {m.checked_mul(m / 2 + 1)}
+}
+
+/// Computes `m * (m + 1) / 2` without overflowing in the middle.
+pub(crate) fn triangular(m: usize) -> usize {
+    checked_triangular(m).expect("Triangular number out of range")
+}
+
+/// Returns the largest `m` where `m * (m + 1) / 2 <= index`,
+/// together with that triangular number.
+///
+/// Estimates `m` with an integer square root, which is off by a few steps at most,
+/// and corrects it using exact arithmetic.
+pub(crate) fn triangular_root(index: usize) -> (usize, usize) {
+    use num_integer::Roots;
+
+    // Approximates `sqrt(2 * index)` without overflow.
+    let mut m = 2 * (index / 2).sqrt();
+    while checked_triangular(m).is_none_or(|t| t > index) {
+        m -= 1;
+    }
+    while checked_triangular(m + 1).is_some_and(|t| t <= index) {
+        m += 1;
+    }
+    (m, triangular(m))
+}
+
+/// Returns the largest `m` where `m * (m + 1) / 2 <= index`,
+/// together with that triangular number.
+pub(crate) fn biguint_triangular_root(index: &BigUint) -> (BigUint, BigUint) {
+    let mut m: BigUint = 0usize.into();
+    let mut t: BigUint = 0usize.into();
+    biguint_triangular_root_with(index, &mut m, &mut t);
+    (m, t)
+}
+
+/// Computes `biguint_triangular_root` into existing numbers.
+pub(crate) fn biguint_triangular_root_with(index: &BigUint, m: &mut BigUint, t: &mut BigUint) {
+    m.clone_from(index);
+    *m <<= 3;
+    *m += 1u32;
+    *m = m.sqrt();
+    *m -= 1u32;
+    *m >>= 1;
+    biguint_triangular_with(m, t);
+}
+
+/// Computes `m * (m + 1) / 2` into an existing number.
+pub(crate) fn biguint_triangular_with(m: &BigUint, t: &mut BigUint) {
+    t.clone_from(m);
+    *t += 1u32;
+    *t *= m;
+    *t >>= 1;
+}
+
+/// Computes the index of a pair `min < max` into an existing number.
+pub(crate) fn biguint_pair_index_with(min: &BigUint, max: &BigUint, index: &mut BigUint) {
+    use num_traits::Zero;
+
+    if max.is_zero() {
+        index.set_zero();
+    } else {
+        index.clone_from(max);
+        *index -= 1u32;
+        *index *= max;
+        *index >>= 1;
+        *index += min;
+    }
+}
+
 impl Space<usize> for Pair<Data> {
     type Dim = usize;
     type Pos = (usize, usize);
-    fn count(&self, dim: &usize) -> usize { dim * (dim - 1) / 2 }
+    fn count(&self, dim: &usize) -> usize { dim * dim.saturating_sub(1) / 2 }
     fn zero(&self, _dim: &usize) -> (usize, usize) { (0, 0) }
     fn to_index(&self, _dim: &usize, &(min, max): &(usize, usize)) -> usize {
         if max == 0 {0} else {
-            min + max * (max - 1) / 2
+            min + triangular(max - 1)
         }
     }
     fn to_pos(&self, _dim: &usize, index: usize, pos: &mut (usize, usize)) {
-        use num_integer::Roots;
-        let index = index as u128;
-        let max = (1 + (8 * index + 1).sqrt()) / 2;
-        let d = max * (max + 1) / 2;
-        let min = (index).wrapping_add(max).wrapping_sub(d);
-        *pos = (min as usize, max as usize)
+        let (m, t) = triangular_root(index);
+        *pos = (index - t, m + 1)
     }
 }
 
@@ -39,6 +109,9 @@ impl Space<BigUint> for Pair<Data> {
     type Dim = BigUint;
     type Pos = (BigUint, BigUint);
     fn count(&self, dim: &BigUint) -> BigUint {
+        use num_traits::Zero;
+
+        if dim.is_zero() {return BigUint::zero()}
         dim * (dim - 1usize) / 2usize
     }
     fn zero(&self, _dim: &BigUint) -> (BigUint, BigUint) { (0usize.into(), 0usize.into()) }
@@ -50,10 +123,31 @@ impl Space<BigUint> for Pair<Data> {
         }
     }
     fn to_pos(&self, _dim: &BigUint, index: BigUint, pos: &mut (BigUint, BigUint)) {
-        let max: BigUint = (1usize + (8usize * &index + 1usize).sqrt()) / 2usize;
-        let d = &max * (&max + 1usize) / 2usize;
-        let min = &index + &max - d;
-        *pos = (min, max)
+        let (m, t) = biguint_triangular_root(&index);
+        *pos = (index - t, m + 1usize)
+    }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        _dim: &BigUint,
+        (min, max): &(BigUint, BigUint),
+        index: &mut BigUint,
+    ) {
+        biguint_pair_index_with(min, max, index)
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        _dim: &BigUint,
+        index: &BigUint,
+        (min, max): &mut (BigUint, BigUint),
+    ) {
+        let mut t = scratch.take();
+        biguint_triangular_root_with(index, max, &mut t);
+        *max += 1u32;
+        min.clone_from(index);
+        *min -= &t;
+        scratch.put(t);
     }
 }
 
@@ -98,6 +192,93 @@ impl<N, T> Space<N> for Pair<Of<T>>
         of.to_pos(dim, pair_min, min);
         of.to_pos(dim, pair_max, max);
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        (min, max): &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Pair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        of.to_index_with(scratch, dim, min, &mut pair.0);
+        of.to_index_with(scratch, dim, max, &mut pair.1);
+        data.to_index_with(scratch, &of.count(dim), &pair, index);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        (min, max): &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Pair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        data.to_pos_with(scratch, &of.count(dim), index, &mut pair);
+        of.to_pos_with(scratch, dim, &pair.0, min);
+        of.to_pos_with(scratch, dim, &pair.1, max);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, (min, max): &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let data: Pair<Data> = Construct::new();
+        let min = of.try_to_index(dim, min)?;
+        let max = of.try_to_index(dim, max)?;
+        Some(data.to_index(&self.count(dim), &(min, max)))
+    }
+}
+
+impl<N, T> Prepare<N> for Pair<Of<T>>
+    where T: Prepare<N>,
+          Pair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+{
+    /// Tables of the inner space, followed by its count.
+    type Tables = (T::Tables, N);
+    fn tables(&self, dim: &Self::Dim) -> Self::Tables {
+        let of: T = Construct::new();
+        let tables = of.tables(dim);
+        let count = of.prepared_count(dim, &tables);
+        (tables, count)
+    }
+    fn prepared_count(&self, _dim: &Self::Dim, (_, count): &Self::Tables) -> N {
+        let data: Pair<Data> = Construct::new();
+        data.count(count)
+    }
+    fn prepared_to_index(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        (min, max): &Self::Pos,
+    ) -> N {
+        let of: T = Construct::new();
+        let data: Pair<Data> = Construct::new();
+        let min = of.prepared_to_index(dim, tables, min);
+        let max = of.prepared_to_index(dim, tables, max);
+        data.to_index(count, &(min, max))
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        index: N,
+        (min, max): &mut Self::Pos,
+    ) {
+        let of: T = Construct::new();
+        let data: Pair<Data> = Construct::new();
+        let mut pair = data.zero(count);
+        data.to_pos(count, index, &mut pair);
+        of.prepared_to_pos(dim, tables, pair.0, min);
+        of.prepared_to_pos(dim, tables, pair.1, max);
+    }
 }
 
 #[cfg(test)]
@@ -115,6 +296,7 @@ mod tests {
         let x: Pair = Construct::new();
         let ref dim = 4;
         assert_eq!(x.count(dim), 6);
+        assert_eq!(x.count(&0), 0);
         assert_eq!(x.to_index(dim, &(0, 1)), 0);
         assert_eq!(x.to_index(dim, &(0, 2)), 1);
         assert_eq!(x.to_index(dim, &(1, 2)), 2);
@@ -133,6 +315,7 @@ mod tests {
         let x: Pair = Construct::new();
         let ref dim: BigUint = 4usize.into();
         assert_eq!(x.count(dim), 6usize.into());
+        assert_eq!(x.count(&BigUint::from(0usize)), 0usize.into());
         assert_eq!(x.to_index(dim, &conv_pos((0, 1))), 0usize.into());
         assert_eq!(x.to_index(dim, &conv_pos((0, 2))), 1usize.into());
         assert_eq!(x.to_index(dim, &conv_pos((1, 2))), 2usize.into());
@@ -191,4 +374,56 @@ mod tests {
         x.to_pos(dim, 5usize.into(), &mut pos);
         assert_eq!(pos, conv_pos_of((vec![0, 1], vec![1, 1])));
     }
+
+    /// Indices next to triangular numbers at both ends of the `usize` range.
+    fn boundaries() -> Vec<usize> {
+        use super::triangular;
+
+        let (top, _) = super::triangular_root(usize::MAX);
+        let mut res = vec![usize::MAX - 1, usize::MAX];
+        for m in (1..20_000).chain(top - 20_000..top + 1) {
+            let t = triangular(m);
+            res.extend([t - 1, t, t.saturating_add(1)]);
+        }
+        res
+    }
+
+    #[test]
+    fn triangular_boundaries() {
+        use std::convert::TryInto;
+
+        let pair: Pair = Construct::new();
+        let eq_pair: EqPair = Construct::new();
+        let neq_pair: NeqPair = Construct::new();
+        let mut pos = (0, 0);
+        let mut big_pos: (BigUint, BigUint) = (0usize.into(), 0usize.into());
+        for (k, index) in boundaries().into_iter().enumerate() {
+            pair.to_pos(&0, index, &mut pos);
+            assert!(pos.0 < pos.1);
+            assert_eq!(pair.to_index(&0, &pos), index);
+
+            eq_pair.to_pos(&0, index, &mut pos);
+            assert!(pos.0 <= pos.1);
+            assert_eq!(eq_pair.to_index(&0, &pos), index);
+
+            if let Some(i) = index.checked_mul(2) {
+                neq_pair.to_pos(&0, i + 1, &mut pos);
+                assert!(pos.0 > pos.1);
+                assert_eq!(neq_pair.to_index(&0, &pos), i + 1);
+            }
+
+            // Compare with the big number impls for some of the indices.
+            if k % 7 == 0 {
+                let ref dim: BigUint = 0usize.into();
+                pair.to_pos(&0, index, &mut pos);
+                pair.to_pos(dim, index.into(), &mut big_pos);
+                assert_eq!(big_pos, conv_pos(pos));
+                eq_pair.to_pos(&0, index, &mut pos);
+                eq_pair.to_pos(dim, index.into(), &mut big_pos);
+                assert_eq!(big_pos, conv_pos(pos));
+                let i: usize = eq_pair.to_index(dim, &big_pos).try_into().unwrap();
+                assert_eq!(i, index);
+            }
+        }
+    }
 }
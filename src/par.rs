@@ -0,0 +1,165 @@
+//! Parallel enumeration of spaces, using Rayon.
+//!
+//! Since every index can be converted to a position independently,
+//! enumerating a space is embarrassingly parallel.
+//! The index range is split in halves on demand,
+//! such that the work stays balanced also for `BigUint` ranges.
+//!
+//! This module requires the `rayon` feature.
+
+use std::ops::{Add, Div, Mul, Sub};
+
+use rayon::iter::{self, ParallelIterator};
+
+use crate::space::Space;
+
+/// Splits an index range `start..end` in halves while Rayon asks for more work.
+fn split<N>(start: N, end: N) -> impl ParallelIterator<Item = (N, N)>
+    where N: Clone + Send + PartialOrd + From<usize>,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Div<&'a N, Output = N>,
+{
+    iter::split((start, end), |(start, end)| {
+        let one: N = 1usize.into();
+        let len = &end - &start;
+        if len > one {
+            let mid = &start + &(&len / &2usize.into());
+            ((start, mid.clone()), Some((mid, end)))
+        } else {
+            ((start, end), None)
+        }
+    })
+}
+
+/// Iterates over all positions of a space in parallel.
+///
+/// Each piece of work reuses one position buffer while converting indices,
+/// and yields a clone for each position.
+/// The order of positions is not preserved.
+pub fn par_iter<'a, N, S>(space: &'a S, dim: &'a S::Dim) -> impl ParallelIterator<Item = S::Pos> + 'a
+    where S: Space<N> + Sync,
+          S::Dim: Sync,
+          S::Pos: Clone + Send,
+          N: Clone + Send + PartialOrd + From<usize> + 'a,
+          for<'b> &'b N: Add<&'b N, Output = N> +
+                         Sub<&'b N, Output = N> +
+                         Div<&'b N, Output = N>,
+{
+    split(0usize.into(), space.count(dim)).flat_map_iter(move |(start, end)| {
+        let one: N = 1usize.into();
+        let mut pos = space.zero(dim);
+        let mut i = start;
+        std::iter::from_fn(move || {
+            if i >= end {return None}
+            space.to_pos(dim, i.clone(), &mut pos);
+            i = &i + &one;
+            Some(pos.clone())
+        })
+    })
+}
+
+/// Calls a closure in parallel for chunks of consecutive positions.
+///
+/// The closure gets the index of the first position in the chunk
+/// and the positions of the chunk, which has at most `chunk_size` positions.
+/// Position buffers are reused within each thread.
+pub fn par_for_each_chunk<N, S, F>(space: &S, dim: &S::Dim, chunk_size: usize, f: F)
+    where S: Space<N> + Sync,
+          S::Dim: Sync,
+          S::Pos: Send,
+          N: Clone + Send + Sync + PartialOrd + From<usize>,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N>,
+          F: Fn(&N, &[S::Pos]) + Sync,
+{
+    assert!(chunk_size > 0, "Chunk size must be positive");
+    let one: N = 1usize.into();
+    let size: N = chunk_size.into();
+    let count = space.count(dim);
+    let chunks = &(&(&count + &size) - &one) / &size;
+    split(0usize.into(), chunks).for_each_init(
+        || Vec::with_capacity(chunk_size),
+        |buf: &mut Vec<S::Pos>, (start, end)| {
+            let mut c = start;
+            while c < end {
+                let first = &c * &size;
+                let mut i = first.clone();
+                let mut len = 0;
+                while len < chunk_size && i < count {
+                    if buf.len() == len {buf.push(space.zero(dim))}
+                    space.to_pos(dim, i.clone(), &mut buf[len]);
+                    i = &i + &one;
+                    len += 1;
+                }
+                f(&first, &buf[..len]);
+                c = &c + &one;
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rayon::iter::ParallelIterator;
+
+    use crate::*;
+    use super::{par_iter, par_for_each_chunk};
+
+    #[test]
+    fn iter() {
+        let x: Pair = Construct::new();
+        let ref dim = 100;
+        let mut pairs: Vec<(usize, usize)> = par_iter::<usize, _>(&x, dim).collect();
+        pairs.sort();
+        assert_eq!(pairs.len(), 4950);
+        assert_eq!(pairs[0], (0, 1));
+        assert_eq!(pairs[4949], (98, 99));
+    }
+
+    #[test]
+    fn iter_big() {
+        let x: Permutation = Construct::new();
+        let ref dim: BigUint = 5usize.into();
+        let sum: BigUint = par_iter::<BigUint, _>(&x, dim)
+            .map(|p| p[0].clone())
+            .reduce(|| 0usize.into(), |a, b| a + b);
+        // Each item is first in 24 permutations.
+        assert_eq!(sum, (24 * 10usize).into());
+    }
+
+    #[test]
+    fn chunks() {
+        let x: DimensionN = Construct::new();
+        let ref dim = vec![7, 11];
+        let total = AtomicUsize::new(0);
+        let starts = Mutex::new(vec![]);
+        par_for_each_chunk(&x, dim, 10, |start: &usize, chunk: &[Vec<usize>]| {
+            assert!(chunk.len() <= 10);
+            assert_eq!(x.to_index(dim, &chunk[0]), *start);
+            total.fetch_add(chunk.len(), Ordering::Relaxed);
+            starts.lock().unwrap().push(*start);
+        });
+        assert_eq!(total.into_inner(), 77);
+        let mut starts = starts.into_inner().unwrap();
+        starts.sort();
+        assert_eq!(starts, (0..77).step_by(10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn chunks_big() {
+        let x: PowerSet = Construct::new();
+        let ref dim: BigUint = 10usize.into();
+        let total = AtomicUsize::new(0);
+        par_for_each_chunk(&x, dim, 100, |start: &BigUint, chunk: &[Vec<BigUint>]| {
+            assert_eq!(&x.to_index(dim, &chunk[0]), start);
+            total.fetch_add(chunk.len(), Ordering::Relaxed);
+        });
+        assert_eq!(total.into_inner(), 1024);
+    }
+}
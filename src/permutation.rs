@@ -2,31 +2,238 @@
 use std::marker::PhantomData;
 use std::ops::{
     AddAssign,
-    MulAssign,
-    Sub,
     Mul,
     SubAssign,
-    DivAssign,
     Div,
 };
 use std::convert::TryInto;
 use std::fmt::Debug;
 
+use num_integer::Integer;
+
 use crate::{
     BigUint,
     Construct,
     Data,
     Of,
+    Scratch,
+    prepared::Prepare,
+    scratch::{div_rem_small, set_small_list},
     space::Space,
 };
 
 /// Dimension is natural number, position is a list of numbers.
+///
+/// Positions are ranked by their Lehmer code,
+/// which takes `O(n log n)` time using a Fenwick tree.
 pub struct Permutation<T = Data>(PhantomData<T>);
 
 impl<T> Construct for Permutation<T> {
     fn new() -> Self { Permutation(PhantomData) }
 }
 
+/// Fenwick tree counting which of the items `0..n` are present.
+struct Fenwick {
+    tree: Vec<usize>,
+}
+
+impl Fenwick {
+    /// Creates a tree where no items are present.
+    fn empty(n: usize) -> Fenwick {
+        Fenwick {tree: vec![0; n + 1]}
+    }
+
+    /// Creates a tree where all items are present.
+    fn full(n: usize) -> Fenwick {
+        // Each node counts the items of its range, which has the size of its lowest bit.
+        Fenwick {tree: (0..n + 1).map(|i| i & i.wrapping_neg()).collect()}
+    }
+
+    fn insert(&mut self, item: usize) {
+        let mut i = item + 1;
+        while i < self.tree.len() {
+            self.tree[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn remove(&mut self, item: usize) {
+        let mut i = item + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Counts the present items less than `item`.
+    fn lower(&self, item: usize) -> usize {
+        let mut sum = 0;
+        let mut i = item;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+
+    /// Finds the present item which has `k` present items below it.
+    fn find(&self, mut k: usize) -> usize {
+        let n = self.tree.len() - 1;
+        let mut i = 0;
+        let mut step = if n == 0 {0} else {1 << (usize::BITS - 1 - n.leading_zeros())};
+        while step > 0 {
+            if i + step <= n && self.tree[i + step] <= k {
+                i += step;
+                k -= self.tree[i];
+            }
+            step >>= 1;
+        }
+        i
+    }
+}
+
+/// Computes the Lehmer code of a permutation of `0..n`.
+///
+/// Digit `i` counts the items less than `pos[i]` which come after it,
+/// and has radix `n - i`.
+fn lehmer(n: usize, pos: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut seen = Fenwick::empty(n);
+    pos.map(|x| {
+        let digit = x - seen.lower(x);
+        seen.insert(x);
+        digit
+    }).collect()
+}
+
+/// Converts a Lehmer code to a permutation of `0..n`.
+fn from_lehmer(digits: &[usize]) -> impl Iterator<Item = usize> + '_ {
+    let mut left = Fenwick::full(digits.len());
+    digits.iter().map(move |&d| {
+        let x = left.find(d);
+        left.remove(x);
+        x
+    })
+}
+
+/// The longest permutation which reuses temporaries digit by digit.
+///
+/// Converting one digit at a time is quadratic,
+/// so longer permutations use the balanced conversion instead.
+const SCRATCH_LEN: usize = 256;
+
+/// Returns the product of the radices `n - l` down to `n - r + 1`,
+/// if it fits in `usize`.
+fn small_product(n: usize, l: usize, r: usize) -> Option<usize> {
+    (l..r).try_fold(1usize, |prod, i| prod.checked_mul(n - i))
+}
+
+/// Returns the product of the radices `n - l` down to `n - r + 1`.
+fn product<N>(n: usize, l: usize, r: usize) -> N
+    where N: From<usize>,
+          for<'a> &'a N: Mul<&'a N, Output = N>,
+{
+    if let Some(prod) = small_product(n, l, r) {return prod.into()}
+    let m = (l + r) / 2;
+    &product::<N>(n, l, m) * &product::<N>(n, m, r)
+}
+
+/// Converts the digits `l..r` of a Lehmer code to a number.
+///
+/// Splits the digits in halves, such that the big multiplications
+/// are balanced instead of quadratic.
+fn digits_to_index<N>(n: usize, digits: &[usize], l: usize, r: usize) -> N
+    where N: From<usize> + for<'a> AddAssign<&'a N>,
+          for<'a> &'a N: Mul<&'a N, Output = N>,
+{
+    if small_product(n, l, r).is_some() {
+        return (l..r).fold(0, |acc, i| acc * (n - i) + digits[i]).into();
+    }
+    let m = (l + r) / 2;
+    let high: N = digits_to_index(n, digits, l, m);
+    let mut index = &high * &product::<N>(n, m, r);
+    index += &digits_to_index(n, digits, m, r);
+    index
+}
+
+/// Converts a number to the digits `l..r` of a Lehmer code.
+fn index_to_digits<N>(n: usize, index: N, digits: &mut [usize], l: usize, r: usize)
+    where N: From<usize> + TryInto<usize> + SubAssign,
+          <N as TryInto<usize>>::Error: Debug,
+          for<'a> &'a N: Mul<&'a N, Output = N> + Div<&'a N, Output = N>,
+{
+    if small_product(n, l, r).is_some() {
+        let mut index: usize = index.try_into().unwrap();
+        for i in (l..r).rev() {
+            digits[i] = index % (n - i);
+            index /= n - i;
+        }
+        return;
+    }
+    let m = (l + r) / 2;
+    let prod: N = product(n, m, r);
+    let high = &index / &prod;
+    let mut low = index;
+    low -= &high * &prod;
+    index_to_digits(n, high, digits, l, m);
+    index_to_digits(n, low, digits, m, r);
+}
+
+/// Computes the factorials `0!` up to `n!`.
+fn factorials<N>(n: usize) -> Vec<N>
+    where N: From<usize>,
+          for<'a> &'a N: Mul<&'a N, Output = N>,
+{
+    let mut fact: Vec<N> = Vec::with_capacity(n + 1);
+    fact.push(1usize.into());
+    for k in 1..n + 1 {
+        let x = &fact[k - 1] * &N::from(k);
+        fact.push(x);
+    }
+    fact
+}
+
+/// Converts a Lehmer code to a number, using precomputed factorials.
+fn prepared_digits_to_index<N>(fact: &[N], digits: &[usize]) -> N
+    where N: From<usize> + for<'a> AddAssign<&'a N>,
+          for<'a> &'a N: Mul<&'a N, Output = N>,
+{
+    let n = digits.len();
+    let mut index: N = 0usize.into();
+    for (i, &d) in digits.iter().enumerate() {
+        if d != 0 {index += &(&fact[n - 1 - i] * &N::from(d))}
+    }
+    index
+}
+
+/// Converts a number to a Lehmer code, using precomputed factorials.
+fn prepared_index_to_digits<N>(fact: &[N], mut index: N, digits: &mut [usize])
+    where N: Integer + TryInto<usize>,
+          <N as TryInto<usize>>::Error: Debug,
+{
+    let n = digits.len();
+    for i in 0..n {
+        let (d, r) = index.div_rem(&fact[n - 1 - i]);
+        digits[i] = d.try_into().unwrap();
+        index = r;
+    }
+}
+
+/// Converts index to position.
+fn to_pos(dim: usize, index: usize, pos: &mut Vec<usize>) {
+    let mut digits = vec![0; dim];
+    index_to_digits(dim, index, &mut digits, 0, dim);
+    pos.clear();
+    pos.extend(from_lehmer(&digits));
+}
+
+/// Converts index to position.
+fn biguint_to_pos(dim: usize, index: BigUint, pos: &mut Vec<BigUint>) {
+    let mut digits = vec![0; dim];
+    index_to_digits(dim, index, &mut digits, 0, dim);
+    pos.clear();
+    pos.extend(from_lehmer(&digits).map(BigUint::from));
+}
+
 impl Space<usize> for Permutation<Data> {
     type Dim = usize;
     type Pos = Vec<usize>;
@@ -41,32 +248,28 @@ impl Space<usize> for Permutation<Data> {
         vec![0; *dim]
     }
     fn to_index(&self, dim: &usize, pos: &Vec<usize>) -> usize {
-        let mut index = 0;
-        let mut count = 1;
-        for (i, &x) in pos.iter().enumerate().rev() {
-            let lower = pos[..i].iter().filter(|&&y| y < x).count();
-            index += count * (x - lower);
-            count *= dim - i;
-        }
-        index
+        let digits = lehmer(*dim, pos.iter().cloned());
+        digits_to_index(*dim, &digits, 0, *dim)
     }
-    fn to_pos(&self, dim: &usize, mut index: usize, pos: &mut Vec<usize>) {
-        unsafe { pos.set_len(0); }
-
-        let mut count = 1;
-        for (j, x) in (1..dim + 1).enumerate() {
-            count *= x;
-            pos.push(j);
-        }
+    fn to_pos(&self, dim: &usize, index: usize, pos: &mut Vec<usize>) {
+        to_pos(*dim, index, pos)
+    }
+}
 
-        for i in 0..*dim {
-            let block = count / (dim - i);
-            let ind = index / block;
-            let item = pos.remove(ind);
-            pos.push(item);
-            count /= dim - i;
-            index -= ind * block;
-        }
+impl Prepare<usize> for Permutation<Data> {
+    /// The factorials `0!` up to `dim!`.
+    type Tables = Vec<usize>;
+    fn tables(&self, dim: &usize) -> Vec<usize> {factorials(*dim)}
+    fn prepared_count(&self, dim: &usize, tables: &Vec<usize>) -> usize {tables[*dim]}
+    fn prepared_to_index(&self, dim: &usize, tables: &Vec<usize>, pos: &Vec<usize>) -> usize {
+        let digits = lehmer(*dim, pos.iter().cloned());
+        prepared_digits_to_index(tables, &digits)
+    }
+    fn prepared_to_pos(&self, dim: &usize, tables: &Vec<usize>, index: usize, pos: &mut Vec<usize>) {
+        let mut digits = vec![0; *dim];
+        prepared_index_to_digits(tables, index, &mut digits);
+        pos.clear();
+        pos.extend(from_lehmer(&digits));
     }
 }
 
@@ -74,86 +277,109 @@ impl Space<BigUint> for Permutation<Data> {
     type Dim = BigUint;
     type Pos = Vec<BigUint>;
     fn count(&self, dim: &BigUint) -> BigUint {
-        let _1: BigUint = 1usize.into();
-        let mut res: BigUint = _1.clone();
-        let mut x = _1.clone();
-        loop {
-            if &x > dim {break}
-            res *= &x;
-            x += &_1;
-        }
-        res
+        let dim: usize = dim.try_into().unwrap();
+        product(dim, 0, dim)
     }
     fn zero(&self, dim: &BigUint) -> Vec<BigUint> {
         let dim: usize = dim.try_into().unwrap();
         vec![0usize.into(); dim]
     }
     fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> BigUint {
-        let mut index: BigUint = 0usize.into();
-        let mut count: BigUint = 1usize.into();
-        for (i, x) in pos.iter().enumerate().rev() {
-            let lower = pos[..i].iter().filter(|&y| y < x).count();
-            index += &count * (x - lower);
-            count *= dim - i;
-        }
-        index
+        let dim: usize = dim.try_into().unwrap();
+        let digits = lehmer(dim, pos.iter().map(|x| x.try_into().unwrap()));
+        digits_to_index(dim, &digits, 0, dim)
     }
-    fn to_pos(&self, dim: &Self::Dim, mut index: BigUint, pos: &mut Self::Pos) {
-        pos.clear();
+    fn to_pos(&self, dim: &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        biguint_to_pos(dim.try_into().unwrap(), index, pos)
+    }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
 
-        let mut count: BigUint = 1usize.into();
-        let dim: usize = dim.try_into().unwrap();
-        for (j, x) in (1usize..dim + 1).enumerate() {
-            count *= x;
-            pos.push(j.into());
+        let n: usize = dim.try_into().unwrap();
+        if n > SCRATCH_LEN {
+            *index = self.to_index(dim, pos);
+            return;
         }
+        let digits = lehmer(n, pos.iter().map(|x| x.try_into().unwrap()));
+        index.set_zero();
+        for (i, &d) in digits.iter().enumerate() {
+            *index *= n - i;
+            *index += d;
+        }
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        let n: usize = dim.try_into().unwrap();
+        if n > SCRATCH_LEN {
+            self.to_pos(dim, index.clone(), pos);
+            return;
+        }
+        let mut rest = scratch.take();
+        rest.clone_from(index);
+        let mut digits = vec![0; n];
+        for i in (0..n).rev() {
+            digits[i] = div_rem_small(&mut rest, (n - i) as u32) as usize;
+        }
+        scratch.put(rest);
+        set_small_list(pos, from_lehmer(&digits));
+    }
+}
 
+impl Prepare<BigUint> for Permutation<Data> {
+    /// The factorials `0!` up to `dim!`.
+    type Tables = Vec<BigUint>;
+    fn tables(&self, dim: &BigUint) -> Vec<BigUint> {factorials(dim.try_into().unwrap())}
+    fn prepared_count(&self, _dim: &BigUint, tables: &Vec<BigUint>) -> BigUint {
+        tables[tables.len() - 1].clone()
+    }
+    fn prepared_to_index(&self, dim: &BigUint, tables: &Vec<BigUint>, pos: &Vec<BigUint>) -> BigUint {
         let dim: usize = dim.try_into().unwrap();
-        for i in 0..dim {
-            let block = &count / (dim - i);
-            let ind: BigUint = &index / &block;
-            let item = pos.remove((&ind).try_into().unwrap());
-            pos.push(item);
-            count /= dim - i;
-            index -= &ind * block;
-        }
+        let digits = lehmer(dim, pos.iter().map(|x| x.try_into().unwrap()));
+        prepared_digits_to_index(tables, &digits)
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &BigUint,
+        tables: &Vec<BigUint>,
+        index: BigUint,
+        pos: &mut Vec<BigUint>,
+    ) {
+        let mut digits = vec![0; dim.try_into().unwrap()];
+        prepared_index_to_digits(tables, index, &mut digits);
+        pos.clear();
+        pos.extend(from_lehmer(&digits).map(BigUint::from));
     }
 }
 
 impl<N, T> Space<N> for Permutation<Of<T>>
     where T: Space<N>,
           T::Pos: Clone,
-          N: Clone +
-             From<usize> +
+          Permutation<Data>: Space<N, Dim = N, Pos = Vec<N>>,
+          N: From<usize> +
              TryInto<usize> +
              for<'a> AddAssign<&'a N> +
-             for<'a> MulAssign<&'a N> +
-             Sub<usize, Output = N> +
-             SubAssign +
-             DivAssign<usize> +
-             MulAssign<usize> +
-             PartialOrd,
-          <N as TryInto<usize>>::Error: Debug,
-          for<'a> &'a N: Sub<usize, Output = N> +
-                         Mul<&'a N, Output = N> +
-                         Div<usize, Output = N> +
-                         Div<&'a N, Output = N> +,
+             SubAssign,
           <N as TryInto<usize>>::Error: Debug,
+          for<'a> &'a N: Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N>,
 {
     type Dim = T::Dim;
     type Pos = Vec<T::Pos>;
     fn count(&self, dim: &Self::Dim) -> N {
         let of: T = Construct::new();
-        let _1: N = 1usize.into();
-        let mut x = _1.clone();
-        let mut res = _1.clone();
-        let of_count = of.count(dim);
-        loop {
-            if &x > &of_count {break}
-            res *= &x;
-            x += &_1;
-        }
-        res
+        let of_count: usize = of.count(dim).try_into().unwrap();
+        product(of_count, 0, of_count)
     }
     fn zero(&self, dim: &Self::Dim) -> Self::Pos {
         let of: T = Construct::new();
@@ -165,42 +391,66 @@ impl<N, T> Space<N> for Permutation<Of<T>>
     }
     fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> N {
         let of: T = Construct::new();
-        let mut index: N = 0usize.into();
-        let dim_count = of.count(dim);
-        let mut count: N = 1usize.into();
-        for (i, x) in pos.iter()
-            .map(|x| of.to_index(dim, x))
-            .enumerate().rev() {
-            let lower = pos[..i].iter()
-                .map(|y| of.to_index(dim, y))
-                .filter(|y| y < &x).count();
-            index += &(&count * &(x - lower));
-            count *= &(&dim_count - i);
-        }
-        index
+        let of_count: usize = of.count(dim).try_into().unwrap();
+        let digits = lehmer(of_count, pos.iter().map(|x| of.to_index(dim, x).try_into().unwrap()));
+        digits_to_index(of_count, &digits, 0, of_count)
     }
-    fn to_pos(&self, dim: &Self::Dim, mut index: N, pos: &mut Self::Pos) {
+    fn to_pos(&self, dim: &Self::Dim, index: N, pos: &mut Self::Pos) {
         let of: T = Construct::new();
         let of_count: usize = of.count(dim).try_into().unwrap();
+        let mut digits = vec![0; of_count];
+        index_to_digits(of_count, index, &mut digits, 0, of_count);
         pos.clear();
-
-        let mut count: N = 1usize.into();
-        for (j, x) in (1..of_count + 1).enumerate() {
-            count *= x;
-            let mut new_pos: T::Pos = of.zero(&dim);
-            of.to_pos(dim, j.into(), &mut new_pos);
+        for x in from_lehmer(&digits) {
+            let mut new_pos: T::Pos = of.zero(dim);
+            of.to_pos(dim, x.into(), &mut new_pos);
             pos.push(new_pos);
         }
-
-        for i in 0..of_count {
-            let diff = of_count - i;
-            let block = &count / diff;
-            let ind = &index / &block;
-            index -= &ind * &block;
-            let item = pos.remove(ind.try_into().unwrap());
-            pos.push(item);
-            count /= diff;
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Permutation<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        for (x, i) in pos.iter().zip(&mut inner) {
+            of.to_index_with(scratch, dim, x, i);
+        }
+        data.to_index_with(scratch, &of.count(dim), &inner, index);
+        scratch.put_many(inner);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: Permutation<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        data.to_pos_with(scratch, &of.count(dim), index, &mut inner);
+        pos.truncate(inner.len());
+        while pos.len() < inner.len() {pos.push(of.zero(dim))}
+        for (i, x) in inner.iter().zip(pos) {
+            of.to_pos_with(scratch, dim, i, x);
+        }
+        scratch.put_many(inner);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos {
+            of.try_to_index(dim, x)?;
         }
+        Some(self.to_index(dim, pos))
     }
 }
 
@@ -290,4 +540,66 @@ mod test {
             assert_eq!(index, i.into());
         }
     }
+
+    #[test]
+    fn batch() {
+        let x: Permutation = Construct::new();
+        let ref dim = 4;
+        let indices: Vec<usize> = (0..24).rev().collect();
+        let mut pos = vec![vec![]; 24];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        assert_eq!(pos[0], vec![3, 2, 1, 0]);
+        let mut res = vec![0; 24];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        let ref dim: BigUint = 4usize.into();
+        let indices: Vec<BigUint> = (0usize..24).map(|i| i.into()).collect();
+        let mut pos = vec![vec![]; 24];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut res = vec![0usize.into(); 24];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+
+        // Tuples compute the count of the second space once.
+        let x: (Permutation, Pair) = Construct::new();
+        let ref dim = (3, 4);
+        let indices: Vec<usize> = (0..36).collect();
+        let mut pos = vec![x.zero(dim); 36];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        assert_eq!(pos[35], (vec![2, 1, 0], (2, 3)));
+        let mut res = vec![0; 36];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+    }
+
+    #[test]
+    fn large() {
+        let x: Permutation = Construct::new();
+
+        // Reverse order is the last permutation.
+        let ref dim = 20;
+        let pos: Vec<usize> = (0..20).rev().collect();
+        assert_eq!(x.to_index(dim, &pos), x.count(dim) - 1);
+
+        let ref dim: BigUint = 1000usize.into();
+        let count: BigUint = x.count(dim);
+        let mut pos = vec![];
+        x.to_pos(dim, &count - 1usize, &mut pos);
+        assert_eq!(pos, (0usize..1000).rev().map(BigUint::from).collect::<Vec<_>>());
+        assert_eq!(x.to_index(dim, &pos), &count - 1usize);
+
+        // Compare with counting smaller predecessors directly.
+        let index: BigUint = &count / 7usize;
+        x.to_pos(dim, index.clone(), &mut pos);
+        let mut expected: BigUint = 0usize.into();
+        let mut radix: BigUint = 1usize.into();
+        for (i, a) in pos.iter().enumerate().rev() {
+            let lower = pos[..i].iter().filter(|&b| b < a).count();
+            expected += &radix * (a - lower);
+            radix *= 1000 - i;
+        }
+        assert_eq!(expected, index);
+        assert_eq!(x.to_index(dim, &pos), index);
+    }
 }
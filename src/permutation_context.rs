@@ -0,0 +1,276 @@
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Rem};
+use std::convert::TryInto;
+use std::fmt::Debug;
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Pair,
+    space::Space,
+};
+
+/// The moves that connect permutations in `PermutationContext`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Swaps {
+    /// Any two items can be swapped.
+    All,
+    /// Only neighbouring items can be swapped.
+    Adjacent,
+}
+
+impl Swaps {
+    /// Gets the number of moves for permutations of `n` items.
+    pub fn count(&self, n: usize) -> usize {
+        if n < 2 {return 0}
+        match self {
+            Swaps::All => n * (n - 1) / 2,
+            Swaps::Adjacent => n - 1,
+        }
+    }
+
+    /// Returns `true` if swapping `i` and `j` is a move, where `i < j`.
+    pub fn allows(&self, n: usize, i: usize, j: usize) -> bool {
+        i < j && j < n && match self {
+            Swaps::All => true,
+            Swaps::Adjacent => j == i + 1,
+        }
+    }
+
+    fn to_index(self, n: usize, i: usize, j: usize) -> usize {
+        match self {
+            Swaps::All => {
+                let pair: Pair = Construct::new();
+                pair.to_index(&n, &(i, j))
+            }
+            Swaps::Adjacent => i,
+        }
+    }
+
+    fn to_pos(self, n: usize, index: usize) -> (usize, usize) {
+        match self {
+            Swaps::All => {
+                let pair: Pair = Construct::new();
+                let mut pos = (0, 0);
+                pair.to_pos(&n, index, &mut pos);
+                pos
+            }
+            Swaps::Adjacent => (index, index + 1),
+        }
+    }
+}
+
+/// A discrete space that models the moves between permutations,
+/// swapping two items at a time.
+///
+/// It can also be thought of as the edges in an undirected graph,
+/// where each node is a permutation (a Cayley graph of the symmetric group).
+///
+/// Dimension is `(n, Swaps)`, where `n` is the number of items
+/// and `Swaps` tells whether all transpositions or only adjacent swaps are moves.
+///
+/// The position is a tuple `(Vec<usize>, usize, usize)`,
+/// where the first component is the permutation,
+/// and the other components are the positions `i < j` of the swapped items.
+/// The canonical orientation of an edge is the permutation where the
+/// item at `i` is lower than the item at `j`.
+/// Both orientations have the same index.
+///
+/// Edges are ordered by move first, then by the permutation.
+pub struct PermutationContext<T = Data>(PhantomData<T>);
+
+impl<T> Construct for PermutationContext<T> {
+    fn new() -> Self { PermutationContext(PhantomData) }
+}
+
+impl<T> PermutationContext<T> {
+    /// Creates the canonical edge for swapping the items at `i` and `j` in `perm`.
+    ///
+    /// Returns `None` if this is not a move.
+    pub fn edge<U: Clone + Ord>(
+        &self,
+        swaps: Swaps,
+        perm: &[U],
+        i: usize,
+        j: usize,
+    ) -> Option<(Vec<U>, usize, usize)> {
+        let (i, j) = if i < j {(i, j)} else {(j, i)};
+        if !swaps.allows(perm.len(), i, j) {return None}
+        let mut perm = perm.to_vec();
+        if perm[i] > perm[j] {perm.swap(i, j)}
+        Some((perm, i, j))
+    }
+
+    /// Applies the move of an edge, returning the permutation at the other end.
+    pub fn apply<U: Clone>(&self, (perm, i, j): &(Vec<U>, usize, usize)) -> Vec<U> {
+        let mut perm = perm.clone();
+        perm.swap(*i, *j);
+        perm
+    }
+}
+
+fn count<N>(n: usize, swaps: Swaps) -> N
+    where N: From<usize>,
+          for<'a> &'a N: Mul<&'a N, Output = N>,
+{
+    // There are `n! / 2` permutations in each canonical orientation.
+    let mut res: N = swaps.count(n).into();
+    for x in 3..n + 1 {
+        res = &res * &x.into();
+    }
+    res
+}
+
+fn to_index<N>(n: usize, swaps: Swaps, perm: &[usize], i: usize, j: usize) -> N
+    where N: From<usize>,
+          for<'a> &'a N: Add<&'a N, Output = N> + Mul<&'a N, Output = N>,
+{
+    let (i, j) = if i < j {(i, j)} else {(j, i)};
+    // The other items determine the edge, since the swapped items
+    // are the two that are left over.
+    let rest: Vec<usize> = perm.iter().enumerate()
+        .filter(|&(k, _)| k != i && k != j)
+        .map(|(_, &x)| x)
+        .collect();
+    let mut index: N = swaps.to_index(n, i, j).into();
+    for (t, &x) in rest.iter().enumerate() {
+        let lower = rest[..t].iter().filter(|&&y| y < x).count();
+        index = &(&index * &(n - t).into()) + &(x - lower).into();
+    }
+    index
+}
+
+fn to_pos<N>(n: usize, swaps: Swaps, mut index: N, pos: &mut (Vec<usize>, usize, usize))
+    where N: From<usize> + TryInto<usize>,
+          <N as TryInto<usize>>::Error: Debug,
+          for<'a> &'a N: Div<&'a N, Output = N> + Rem<&'a N, Output = N>,
+{
+    let len = n.saturating_sub(2);
+    let mut digits = vec![0; len];
+    for t in (0..len).rev() {
+        let radix: N = (n - t).into();
+        digits[t] = (&index % &radix).try_into().unwrap();
+        index = &index / &radix;
+    }
+    let (i, j) = swaps.to_pos(n, index.try_into().unwrap());
+
+    let mut items: Vec<usize> = (0..n).collect();
+    let mut rest: Vec<usize> = digits.into_iter().map(|d| items.remove(d)).collect();
+    rest.reverse();
+    let (perm, pi, pj) = pos;
+    perm.clear();
+    for k in 0..n {
+        perm.push(if k == i {items[0]} else if k == j {items[1]} else {rest.pop().unwrap()});
+    }
+    *pi = i;
+    *pj = j;
+}
+
+impl Space<usize> for PermutationContext<Data> {
+    type Dim = (usize, Swaps);
+    type Pos = (Vec<usize>, usize, usize);
+    fn count(&self, &(n, swaps): &Self::Dim) -> usize {
+        count(n, swaps)
+    }
+    fn zero(&self, &(n, _): &Self::Dim) -> Self::Pos {
+        (vec![0; n], 0, 0)
+    }
+    fn to_index(&self, &(n, swaps): &Self::Dim, (perm, i, j): &Self::Pos) -> usize {
+        to_index(n, swaps, perm, *i, *j)
+    }
+    fn to_pos(&self, &(n, swaps): &Self::Dim, index: usize, pos: &mut Self::Pos) {
+        to_pos(n, swaps, index, pos)
+    }
+}
+
+impl Space<BigUint> for PermutationContext<Data> {
+    type Dim = (BigUint, Swaps);
+    type Pos = (Vec<BigUint>, usize, usize);
+    fn count(&self, (n, swaps): &Self::Dim) -> BigUint {
+        count(n.try_into().unwrap(), *swaps)
+    }
+    fn zero(&self, (n, _): &Self::Dim) -> Self::Pos {
+        let n: usize = n.try_into().unwrap();
+        (vec![0usize.into(); n], 0, 0)
+    }
+    fn to_index(&self, (n, swaps): &Self::Dim, (perm, i, j): &Self::Pos) -> BigUint {
+        let perm: Vec<usize> = perm.iter().map(|x| x.try_into().unwrap()).collect();
+        to_index(n.try_into().unwrap(), *swaps, &perm, *i, *j)
+    }
+    fn to_pos(&self, (n, swaps): &Self::Dim, index: BigUint, pos: &mut Self::Pos) {
+        let mut data_pos = (vec![], 0, 0);
+        to_pos(n.try_into().unwrap(), *swaps, index, &mut data_pos);
+        let (perm, i, j) = data_pos;
+        *pos = (perm.into_iter().map(|x| x.into()).collect(), i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, PermutationContext>();
+    }
+
+    #[test]
+    fn data() {
+        let x: PermutationContext = Construct::new();
+        assert_eq!(x.count(&(0, Swaps::All)), 0);
+        assert_eq!(x.count(&(1, Swaps::All)), 0);
+        assert_eq!(x.count(&(2, Swaps::All)), 1);
+        assert_eq!(x.count(&(3, Swaps::All)), 9);
+        assert_eq!(x.count(&(4, Swaps::All)), 72);
+        assert_eq!(x.count(&(4, Swaps::Adjacent)), 36);
+
+        let ref dim = (3, Swaps::All);
+        assert_eq!(x.to_index(dim, &(vec![1, 2, 0], 0, 1)), 0);
+        assert_eq!(x.to_index(dim, &(vec![2, 1, 0], 0, 1)), 0);
+        assert_eq!(x.to_index(dim, &(vec![0, 1, 2], 0, 1)), 2);
+
+        for &swaps in &[Swaps::All, Swaps::Adjacent] {
+            let ref dim = (5, swaps);
+            let mut pos = x.zero(dim);
+            for i in 0..x.count(dim) {
+                x.to_pos(dim, i, &mut pos);
+                let (ref perm, a, b) = pos;
+                assert!(swaps.allows(5, a, b));
+                assert!(perm[a] < perm[b]);
+                assert_eq!(x.to_index(dim, &pos), i);
+                // The other orientation is the same edge.
+                assert_eq!(x.to_index(dim, &(x.apply(&pos), a, b)), i);
+            }
+        }
+    }
+
+    #[test]
+    fn data_big() {
+        use std::convert::TryInto;
+
+        let x: PermutationContext = Construct::new();
+        let ref dim: (BigUint, Swaps) = (4usize.into(), Swaps::All);
+        assert_eq!(x.count(dim), 72usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+
+    #[test]
+    fn moves() {
+        let x: PermutationContext = Construct::new();
+        let ref dim = (4, Swaps::Adjacent);
+        assert_eq!(x.edge(Swaps::Adjacent, &[3, 1, 2, 0], 0, 2), None);
+        let edge = x.edge(Swaps::Adjacent, &[3, 1, 2, 0], 1, 0).unwrap();
+        assert_eq!(edge, (vec![1, 3, 2, 0], 0, 1));
+        assert_eq!(x.apply(&edge), vec![3, 1, 2, 0]);
+        let index = x.to_index(dim, &edge);
+        let mut pos = x.zero(dim);
+        x.to_pos(dim, index, &mut pos);
+        assert_eq!(pos, edge);
+    }
+}
@@ -2,19 +2,77 @@ use std::marker::PhantomData;
 use std::convert::TryInto;
 use std::fmt::Debug;
 
-use std::ops::{BitOrAssign, Rem, Shr};
-
-use num_traits::pow::Pow;
-
 use crate::{
     BigUint,
     Construct,
     Of,
     Data,
+    Scratch,
+    scratch::set_small_list,
     space::Space,
 };
 
+/// Implemented by numbers that can be used as sets of bits.
+///
+/// Bit `i` has the value `2^i`.
+pub trait Bits {
+    /// Returns `2^exp`.
+    fn power_of_two(exp: usize) -> Self;
+    /// Returns `true` if bit `i` is set.
+    fn bit(&self, i: usize) -> bool;
+    /// Sets or clears bit `i`.
+    fn set_bit(&mut self, i: usize, value: bool);
+    /// Returns the bits as 64 bit words, least significant first.
+    fn words(&self) -> impl Iterator<Item = u64> + '_;
+    /// Iterates over the set bits in increasing order.
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words().enumerate().flat_map(|(k, mut word)| {
+            std::iter::from_fn(move || {
+                if word == 0 {return None}
+                let i = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(k * 64 + i)
+            })
+        })
+    }
+}
+
+impl Bits for usize {
+    fn power_of_two(exp: usize) -> usize {
+        u32::try_from(exp).ok().and_then(|exp| 1usize.checked_shl(exp)).expect("Out of range")
+    }
+    fn bit(&self, i: usize) -> bool {
+        i < usize::BITS as usize && (self >> i) & 1 == 1
+    }
+    fn set_bit(&mut self, i: usize, value: bool) {
+        let b = usize::power_of_two(i);
+        if value {*self |= b} else {*self &= !b}
+    }
+    fn words(&self) -> impl Iterator<Item = u64> + '_ {
+        std::iter::once(*self as u64)
+    }
+}
+
+impl Bits for BigUint {
+    fn power_of_two(exp: usize) -> BigUint {
+        let mut res: BigUint = 0usize.into();
+        res.set_bit(exp as u64, true);
+        res
+    }
+    fn bit(&self, i: usize) -> bool {
+        BigUint::bit(self, i as u64)
+    }
+    fn set_bit(&mut self, i: usize, value: bool) {
+        BigUint::set_bit(self, i as u64, value)
+    }
+    fn words(&self) -> impl Iterator<Item = u64> + '_ {
+        self.iter_u64_digits()
+    }
+}
+
 /// Dimension is natural number, position is a list of numbers.
+///
+/// Each number in the position sets a bit of the index.
 pub struct PowerSet<T = Data>(PhantomData<T>);
 
 impl<T> Construct for PowerSet<T> {
@@ -47,12 +105,8 @@ impl Space<usize> for PowerSet<Data> {
         index: usize,
         pos: &mut Vec<usize>
     ) {
-        unsafe { pos.set_len(0); }
-        for i in 0..*dim {
-            if ((index >> i) & 1) == 1 {
-                pos.push(i);
-            }
-        }
+        pos.clear();
+        pos.extend(index.ones().take_while(|i| i < dim));
     }
 }
 
@@ -60,9 +114,7 @@ impl Space<BigUint> for PowerSet<Data> {
     type Dim = BigUint;
     type Pos = Vec<BigUint>;
     fn count(&self, dim: &BigUint) -> BigUint {
-        let _two: BigUint = 2usize.into();
-        let dim: u32 = dim.try_into().unwrap();
-        _two.pow(dim)
+        BigUint::power_of_two(dim.try_into().unwrap())
     }
     fn zero(&self, _dim: &BigUint) -> Vec<BigUint> {
         vec![]
@@ -73,9 +125,8 @@ impl Space<BigUint> for PowerSet<Data> {
         pos: &Self::Pos,
     ) -> BigUint {
         let mut index: BigUint = 0usize.into();
-        let ref _2: BigUint = 2usize.into();
         for i in pos {
-            index |= _2.pow(i.try_into().unwrap());
+            Bits::set_bit(&mut index, i.try_into().unwrap(), true);
         }
         index
     }
@@ -85,37 +136,49 @@ impl Space<BigUint> for PowerSet<Data> {
         index: BigUint,
         pos: &mut Self::Pos,
     ) {
+        let dim: usize = dim.try_into().unwrap();
         pos.clear();
-        let dim: u32 = dim.try_into().unwrap();
-        let ref _2: BigUint = 2u32.into();
-        let ref _1: BigUint = 1u32.into();
-        for i in 0u32..dim {
-            if &((&index >> i) % _2) == _1 {
-                pos.push(i.into());
-            }
+        pos.extend(index.ones().take_while(|&i| i < dim).map(BigUint::from));
+    }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        _dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use num_traits::Zero;
+
+        index.set_zero();
+        for i in pos {
+            Bits::set_bit(index, i.try_into().unwrap(), true);
         }
     }
+    fn to_pos_with(
+        &self,
+        _scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        let dim: usize = dim.try_into().unwrap();
+        set_small_list(pos, index.ones().take_while(|&i| i < dim));
+    }
 }
 
 impl<N, T> Space<N> for PowerSet<Of<T>>
     where T: Space<N>,
-          N: Clone +
-             From<usize> +
-             PartialEq +
-             TryInto<u32> +
-             BitOrAssign<N> +
-             Rem<usize, Output = N> +
-             Pow<u32, Output = N> +
-             Shr<u32, Output = N>,
-          <N as TryInto<u32>>::Error: Debug,
+          PowerSet<Data>: Space<N, Dim = N, Pos = Vec<N>>,
+          N: From<usize> +
+             TryInto<usize> +
+             Bits,
+          <N as TryInto<usize>>::Error: Debug,
 {
     type Dim = T::Dim;
     type Pos = Vec<T::Pos>;
     fn count(&self, dim: &Self::Dim) -> N {
-        let _two: N = 2usize.into();
         let of: T = Construct::new();
-        let count = of.count(dim);
-        _two.pow(count.try_into().unwrap())
+        N::power_of_two(of.count(dim).try_into().unwrap())
     }
     fn zero(&self, _dim: &Self::Dim) -> Self::Pos {
         vec![]
@@ -127,10 +190,8 @@ impl<N, T> Space<N> for PowerSet<Of<T>>
     ) -> N {
         let of: T = Construct::new();
         let mut index: N = 0usize.into();
-        let _2: N = 2usize.into();
         for i in pos {
-            let i: N = of.to_index(dim, i);
-            index |= _2.clone().pow(i.try_into().unwrap());
+            index.set_bit(of.to_index(dim, i).try_into().unwrap(), true);
         }
         index
     }
@@ -141,19 +202,72 @@ impl<N, T> Space<N> for PowerSet<Of<T>>
         pos: &mut Self::Pos,
     ) {
         let of: T = Construct::new();
-        let count = of.count(dim);
+        let count: usize = of.count(dim).try_into().unwrap();
         pos.clear();
-        let count: u32 = count.try_into().unwrap();
-        pos.reserve_exact(count as usize);
-        let ref _1: N = 1usize.into();
-        for j in 0u32..count {
-            if &((index.clone() >> j) % 2) == _1 {
-                let mut p = of.zero(dim);
-                of.to_pos(dim, (j as usize).into(), &mut p);
-                pos.push(p);
+        for j in index.ones().take_while(|&j| j < count) {
+            let mut p = of.zero(dim);
+            of.to_pos(dim, j.into(), &mut p);
+            pos.push(p);
+        }
+    }
+    fn to_pos_batch(&self, dim: &Self::Dim, index: &[N], pos: &mut [Self::Pos]) {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let of: T = Construct::new();
+        let count: usize = of.count(dim).try_into().unwrap();
+        for (i, p) in index.iter().zip(pos) {
+            p.clear();
+            for j in i.ones().take_while(|&j| j < count) {
+                let mut x = of.zero(dim);
+                of.to_pos(dim, j.into(), &mut x);
+                p.push(x);
             }
         }
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: PowerSet<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        for (x, i) in pos.iter().zip(&mut inner) {
+            of.to_index_with(scratch, dim, x, i);
+        }
+        data.to_index_with(scratch, &of.count(dim), &inner, index);
+        scratch.put_many(inner);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: PowerSet<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        data.to_pos_with(scratch, &of.count(dim), index, &mut inner);
+        pos.truncate(inner.len());
+        while pos.len() < inner.len() {pos.push(of.zero(dim))}
+        for (i, x) in inner.iter().zip(pos) {
+            of.to_pos_with(scratch, dim, i, x);
+        }
+        scratch.put_many(inner);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos {
+            of.try_to_index(dim, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
 }
 
 #[cfg(test)]
@@ -237,4 +351,54 @@ mod tests {
         x.to_pos(dim, 7usize.into(), &mut a);
         assert_eq!(a[0], (0usize.into(), 1usize.into()));
     }
+
+    #[test]
+    fn bits() {
+        let mut x: usize = 0;
+        x.set_bit(3, true);
+        x.set_bit(63, true);
+        assert!(x.bit(3) && !x.bit(4) && !x.bit(100));
+        assert_eq!(x.ones().collect::<Vec<_>>(), vec![3, 63]);
+        x.set_bit(3, false);
+        assert_eq!(x, usize::power_of_two(63));
+
+        let mut x: BigUint = 0usize.into();
+        x.set_bit(1000, true);
+        x.set_bit(64, true);
+        assert!(x.bit(64) && !x.bit(65));
+        assert_eq!(x.ones().collect::<Vec<_>>(), vec![64, 1000]);
+    }
+
+    #[test]
+    fn of_sparse() {
+        // The inner space has 2^24 positions, so the index has that many bits.
+        let x: PowerSet<Of<DimensionN>> = Construct::new();
+        let ref dim: Vec<BigUint> = conv(vec![1 << 12, 1 << 12]);
+        let count: BigUint = x.count(dim);
+        assert_eq!(count.bits(), (1 << 24) + 1);
+        let pos = vec![conv(vec![5, 0]), conv(vec![4095, 4095])];
+        let index = x.to_index(dim, &pos);
+        assert_eq!(index.ones().collect::<Vec<_>>(), vec![5, (1 << 24) - 1]);
+        let mut a = vec![];
+        x.to_pos(dim, index, &mut a);
+        assert_eq!(a, pos);
+    }
+
+    #[test]
+    fn batch() {
+        let x: PowerSet<Of<Pair>> = Construct::new();
+        let ref dim = 4;
+        let count = x.count(dim);
+        let indices: Vec<usize> = (0..count).collect();
+        let mut pos = vec![vec![]; count];
+        x.to_pos_batch(dim, &indices, &mut pos);
+        let mut single = vec![];
+        for (i, p) in pos.iter().enumerate() {
+            x.to_pos(dim, i, &mut single);
+            assert_eq!(*p, single);
+        }
+        let mut res = vec![0; count];
+        x.to_index_batch(dim, &pos, &mut res);
+        assert_eq!(res, indices);
+    }
 }
@@ -0,0 +1,170 @@
+//! Prepared spaces, which cache tables that depend only on the dimension.
+//!
+//! Many spaces compute constants of the dimension on every call,
+//! such as offsets of subspaces, factorials or counts of inner spaces.
+//! When converting many indices for the same dimension,
+//! these can be computed once by preparing the space:
+//!
+//! ~~~
+//! use discrete::*;
+//!
+//! let prepared: Prepared<Context> = Prepared::new(vec![2, 3]);
+//! assert_eq!(prepared.count(), 9);
+//! ~~~
+
+use crate::space::Space;
+
+/// Implemented by spaces that can precompute tables for a dimension.
+pub trait Prepare<N>: Space<N> {
+    /// The precomputed tables of a dimension.
+    type Tables;
+
+    /// Computes the tables of a dimension.
+    fn tables(&self, dim: &Self::Dim) -> Self::Tables;
+    /// Counts the size of space using precomputed tables.
+    fn prepared_count(&self, dim: &Self::Dim, tables: &Self::Tables) -> N;
+    /// Converts position to index using precomputed tables.
+    fn prepared_to_index(&self, dim: &Self::Dim, tables: &Self::Tables, pos: &Self::Pos) -> N;
+    /// Converts index to position using precomputed tables.
+    fn prepared_to_pos(
+        &self,
+        dim: &Self::Dim,
+        tables: &Self::Tables,
+        index: N,
+        pos: &mut Self::Pos,
+    );
+
+    /// Prepares a space for a dimension.
+    fn prepare(self, dim: Self::Dim) -> Prepared<Self, N> {
+        Prepared::from_space(self, dim)
+    }
+}
+
+/// A space with a fixed dimension and precomputed tables.
+pub struct Prepared<S: Prepare<N>, N = usize> {
+    space: S,
+    dim: S::Dim,
+    tables: S::Tables,
+}
+
+impl<N, S: Prepare<N>> Prepared<S, N> {
+    /// Prepares a space for a dimension.
+    pub fn new(dim: S::Dim) -> Prepared<S, N> {
+        Prepared::from_space(S::new(), dim)
+    }
+
+    fn from_space(space: S, dim: S::Dim) -> Prepared<S, N> {
+        let tables = space.tables(&dim);
+        Prepared {space, dim, tables}
+    }
+
+    /// Returns the dimension.
+    pub fn dim(&self) -> &S::Dim {&self.dim}
+
+    /// Returns the dimension, dropping the tables.
+    pub fn into_dim(self) -> S::Dim {self.dim}
+
+    /// Counts the size of space.
+    pub fn count(&self) -> N {
+        self.space.prepared_count(&self.dim, &self.tables)
+    }
+
+    /// Creates a default element.
+    pub fn zero(&self) -> S::Pos {
+        self.space.zero(&self.dim)
+    }
+
+    /// Converts position to index.
+    pub fn to_index(&self, pos: &S::Pos) -> N {
+        self.space.prepared_to_index(&self.dim, &self.tables, pos)
+    }
+
+    /// Converts index to position.
+    pub fn to_pos(&self, index: N, pos: &mut S::Pos) {
+        self.space.prepared_to_pos(&self.dim, &self.tables, index, pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use super::{Prepare, Prepared};
+
+    /// Checks that the prepared space agrees with the unprepared one.
+    fn check<S>(dim: S::Dim)
+        where S: Prepare<usize>,
+              S::Dim: Clone,
+              S::Pos: std::fmt::Debug + PartialEq,
+    {
+        let x: S = Construct::new();
+        let prepared: Prepared<S> = Prepared::new(dim.clone());
+        let count = x.count(&dim);
+        assert_eq!(prepared.count(), count);
+        let mut a = x.zero(&dim);
+        let mut b = prepared.zero();
+        for i in 0..count {
+            x.to_pos(&dim, i, &mut a);
+            prepared.to_pos(i, &mut b);
+            assert_eq!(a, b);
+            assert_eq!(prepared.to_index(&b), i);
+        }
+    }
+
+    /// Checks that the prepared space agrees with the unprepared one.
+    fn check_big<S>(dim: S::Dim, count: usize)
+        where S: Prepare<BigUint>,
+              S::Dim: Clone,
+              S::Pos: std::fmt::Debug + PartialEq,
+    {
+        let x: S = Construct::new();
+        let prepared: Prepared<S, BigUint> = Prepared::new(dim.clone());
+        assert_eq!(prepared.count(), count.into());
+        assert_eq!(x.count(&dim), count.into());
+        let mut a = x.zero(&dim);
+        let mut b = prepared.zero();
+        for i in 0..count {
+            x.to_pos(&dim, i.into(), &mut a);
+            prepared.to_pos(i.into(), &mut b);
+            assert_eq!(a, b);
+            assert_eq!(prepared.to_index(&b), i.into());
+        }
+    }
+
+    #[test]
+    fn data() {
+        check::<Context>(vec![2, 3, 4]);
+        check::<Permutation>(5);
+        check::<DimensionN>(vec![3, 1, 4]);
+        check::<Homotopy>((2, 3));
+        check::<(Permutation, DimensionN)>((3, vec![2, 3]));
+        check::<(Homotopy, (Context, Permutation))>(((1, 2), (vec![2, 2], 3)));
+        check::<Pair<Of<Permutation>>>(3);
+        check::<EqPair<Of<DimensionN>>>(vec![2, 3]);
+        check::<NeqPair<Of<Context>>>(vec![2, 2]);
+        check::<SqPair<Of<Permutation>>>(3);
+    }
+
+    #[test]
+    fn data_big() {
+        let dim = |v: &[usize]| v.iter().map(|&x| x.into()).collect::<Vec<BigUint>>();
+        check_big::<Context>(dim(&[2, 3, 4]), 72);
+        check_big::<Permutation>(5usize.into(), 120);
+        check_big::<DimensionN>(dim(&[3, 1, 4]), 12);
+        check_big::<Homotopy>((2, 3usize.into()), 21);
+        check_big::<(Permutation, DimensionN)>((3usize.into(), dim(&[2, 3])), 36);
+        check_big::<Pair<Of<Permutation>>>(3usize.into(), 15);
+        check_big::<SqPair<Of<DimensionN>>>(dim(&[2, 3]), 36);
+    }
+
+    #[test]
+    fn prepare() {
+        let x: Context = Construct::new();
+        let prepared = Prepare::<usize>::prepare(x, vec![2, 3]);
+        assert_eq!(prepared.count(), 9);
+        assert_eq!(prepared.dim(), &vec![2, 3]);
+        let mut pos = prepared.zero();
+        prepared.to_pos(8, &mut pos);
+        assert_eq!(pos, (vec![1, 1], 1, 2));
+        assert_eq!(prepared.into_dim(), vec![2, 3]);
+    }
+}
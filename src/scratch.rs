@@ -0,0 +1,192 @@
+//! Reusable buffers for converting between indices and positions.
+//!
+//! Big numbers allocate memory for every temporary value.
+//! When converting many positions, the same temporaries can be reused
+//! by passing a `Scratch` to `to_index_with` and `to_pos_with`:
+//!
+//! ~~~
+//! use discrete::*;
+//!
+//! let x: Pair = Construct::new();
+//! let ref dim: BigUint = 100usize.into();
+//! let mut scratch = Scratch::new();
+//! let mut pos = x.zero(dim);
+//! for i in 0..10usize {
+//!     x.to_pos_with(&mut scratch, dim, &i.into(), &mut pos);
+//! }
+//! assert_eq!(pos, (3usize.into(), 4usize.into()));
+//! ~~~
+//!
+//! The spaces which reuse temporaries are the dimension, pair, power set,
+//! permutation, combination and context spaces, `SeqUpTo`, `Homotopy`,
+//! `GrayPowerSet` and `GrayDimensionN`, the `Of` variants of these,
+//! tuples and `Either`.
+//! The `Of` variants convert through the inner indices,
+//! and reuse the inner positions when converting to position.
+//! Other spaces fall back to `to_index` and `to_pos`,
+//! which give the same results but allocate as usual.
+
+use crate::BigUint;
+
+/// Stores numbers that can be reused as temporary values.
+pub struct Scratch<N = BigUint> {
+    values: Vec<N>,
+}
+
+impl<N> Default for Scratch<N> {
+    fn default() -> Self {Scratch::new()}
+}
+
+impl<N> Scratch<N> {
+    /// Creates an empty scratch context.
+    pub fn new() -> Scratch<N> {
+        Scratch {values: vec![]}
+    }
+
+    /// Takes a number, which is zero unless it was used before.
+    ///
+    /// The previous value is left over, so callers must overwrite it.
+    pub fn take(&mut self) -> N where N: From<usize> {
+        self.values.pop().unwrap_or_else(|| 0usize.into())
+    }
+
+    /// Gives back a number, such that its memory can be reused.
+    pub fn put(&mut self, value: N) {
+        self.values.push(value);
+    }
+
+    /// Takes `n` numbers, which are zero unless they were used before.
+    pub(crate) fn take_many(&mut self, n: usize) -> Vec<N> where N: From<usize> {
+        (0..n).map(|_| self.take()).collect()
+    }
+
+    /// Gives back a list of numbers.
+    pub(crate) fn put_many(&mut self, values: Vec<N>) {
+        self.values.extend(values);
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {self.values.len()}
+
+    /// Returns `true` if there are no stored values.
+    pub fn is_empty(&self) -> bool {self.values.is_empty()}
+}
+
+/// Sets a number to a small value without allocating.
+pub(crate) fn set_small(x: &mut BigUint, value: usize) {
+    use num_traits::Zero;
+
+    x.set_zero();
+    *x += value;
+}
+
+/// Sets a list of numbers to small values, reusing the numbers it already has.
+pub(crate) fn set_small_list(list: &mut Vec<BigUint>, values: impl Iterator<Item = usize>) {
+    let mut len = 0;
+    for x in values {
+        if len < list.len() {
+            set_small(&mut list[len], x);
+        } else {
+            list.push(x.into());
+        }
+        len += 1;
+    }
+    list.truncate(len);
+}
+
+/// Divides a number in place by a small divisor and returns the remainder.
+pub(crate) fn div_rem_small(x: &mut BigUint, divisor: u32) -> u32 {
+    let d = u64::from(divisor);
+    let rem = x.iter_u32_digits().rev().fold(0, |r, w| ((r << 32) | u64::from(w)) % d);
+    *x = std::mem::take(x) / divisor;
+    rem as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use crate::*;
+    use crate::space::Space;
+
+    /// Checks that reusing a scratch context gives the same results.
+    fn check<S>(dim: S::Dim)
+        where S: Space<BigUint>,
+              S::Pos: Debug + PartialEq,
+    {
+        use std::convert::TryInto;
+
+        let x: S = Construct::new();
+        let mut scratch = Scratch::new();
+        let count: usize = x.count(&dim).try_into().unwrap();
+        let mut a = x.zero(&dim);
+        let mut b = x.zero(&dim);
+        let mut index: BigUint = 0usize.into();
+        for i in 0..count {
+            let i: BigUint = i.into();
+            x.to_pos(&dim, i.clone(), &mut a);
+            x.to_pos_with(&mut scratch, &dim, &i, &mut b);
+            assert_eq!(a, b);
+            x.to_index_with(&mut scratch, &dim, &b, &mut index);
+            assert_eq!(index, i);
+        }
+        // Temporary values are given back, but do not pile up.
+        assert!(scratch.len() < 16);
+    }
+
+    #[test]
+    fn spaces() {
+        let n = |x: usize| -> BigUint {x.into()};
+        check::<Pair>(n(7));
+        check::<EqPair>(n(7));
+        check::<NeqPair>(n(7));
+        check::<DimensionN>(vec![n(3), n(1), n(4)]);
+        check::<PowerSet>(n(5));
+        check::<Permutation>(n(4));
+        check::<Homotopy>((2, n(3)));
+        check::<Homotopy>((3, n(2)));
+        check::<(Pair, DimensionN)>((n(4), vec![n(2), n(3)]));
+        check::<Pair<Of<DimensionN>>>(vec![n(2), n(3)]);
+        check::<EqPair<Of<EqPair>>>(n(3));
+        check::<NeqPair<Of<Pair>>>(n(4));
+        check::<Dimension>(n(5));
+        check::<Dimension<Of<Pair>>>(n(4));
+        check::<SqPair>(n(4));
+        check::<SqPair<Of<Pair>>>(n(3));
+        check::<Permutation<Of<Pair>>>(n(4));
+        check::<Context>(vec![n(2), n(3), n(2)]);
+        check::<Context<Of<Pair>>>(vec![n(3), n(4)]);
+        check::<DirectedContext>(vec![n(3), n(2)]);
+        check::<DirectedContext<Of<Pair>>>(vec![n(3), n(4)]);
+        check::<PowerSet<Of<Pair>>>(n(4));
+        check::<DimensionN<Of<Pair>>>(vec![n(3), n(4)]);
+        check::<Homotopy<Of<Pair>>>((2, n(3)));
+        check::<Homotopy<Of<Pair>>>((0, n(4)));
+        check::<Combination>((n(6), 3));
+        check::<Combination>((n(4), 0));
+        check::<Combination<Of<Pair>>>((n(4), 2));
+        check::<SeqUpTo>((3, n(3)));
+        check::<SeqUpTo<Of<Pair>>>((2, n(3)));
+        check::<KContext>((vec![n(2), n(3), n(2)], 2));
+        check::<KContext<Of<Pair>>>((vec![n(3), n(4)], 1));
+        check::<DirectedKContext>((vec![n(3), n(2), n(2)], 2));
+        check::<DirectedKContext<Of<Pair>>>((vec![n(3), n(3)], 2));
+        check::<GrayPowerSet>(n(6));
+        check::<GrayDimensionN>(vec![n(3), n(2), n(4)]);
+        check::<Either<Pair, DimensionN>>((n(3), vec![n(2), n(3)]));
+        check::<(Either<Pair, SqPair>, Pair)>(((n(3), n(2)), n(3)));
+    }
+
+    #[test]
+    fn reuse() {
+        let mut scratch: Scratch = Scratch::new();
+        assert!(scratch.is_empty());
+        let mut x = scratch.take();
+        assert_eq!(x, 0usize.into());
+        x += 5usize;
+        scratch.put(x);
+        assert_eq!(scratch.len(), 1);
+        // Values are not cleared when taken again.
+        assert_eq!(scratch.take(), 5usize.into());
+    }
+}
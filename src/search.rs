@@ -0,0 +1,256 @@
+//! Graph search over context spaces.
+//!
+//! The nodes of a context space are the positions of `DimensionN`
+//! with the same dimension, referred to by their index.
+//! The edges are the positions of the context space itself.
+//! This makes it possible to search state spaces without writing
+//! any encoding by hand, e.g. a planner over `DirectedContext`.
+//!
+//! Every search takes a predicate `allow` that tells whether an edge
+//! can be used, which is how forbidden transitions are modeled.
+//! Visited nodes are stored as bits indexed by `DimensionN` rank.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::ops::Add;
+
+use crate::{Dimension, Incidence, SpaceBitSet};
+
+/// Stores a set of visited nodes as bits, indexed by `DimensionN` rank.
+pub type Visited = SpaceBitSet<Dimension>;
+
+/// Gets the node at the other end of an edge.
+fn other<S: Incidence<usize>>(space: &S, dim: &S::Dim, node: usize, edge: &S::Pos) -> usize {
+    let (from, to) = space.nodes(dim, edge);
+    if from == node {to} else {from}
+}
+
+/// Visits the nodes reachable from `start` in breadth-first order.
+///
+/// The closure `visit` is called with each node and its distance from `start`.
+/// Returns the set of visited nodes.
+pub fn bfs<S, A, F>(
+    space: &S,
+    dim: &S::Dim,
+    start: usize,
+    mut allow: A,
+    mut visit: F,
+) -> Visited
+    where S: Incidence<usize>,
+          A: FnMut(&S::Pos) -> bool,
+          F: FnMut(usize, usize),
+{
+    let mut visited = Visited::new(space.node_count(dim));
+    let mut queue = VecDeque::new();
+    visited.insert(&start);
+    queue.push_back((start, 0));
+    while let Some((node, depth)) = queue.pop_front() {
+        visit(node, depth);
+        for edge in space.out_edges(dim, &node) {
+            if !allow(&edge) {continue}
+            let next = other(space, dim, node, &edge);
+            if visited.insert(&next) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+    visited
+}
+
+/// Visits the nodes reachable from `start` in depth-first order.
+///
+/// The closure `visit` is called with each node before its successors.
+/// Returns the set of visited nodes.
+pub fn dfs<S, A, F>(
+    space: &S,
+    dim: &S::Dim,
+    start: usize,
+    mut allow: A,
+    mut visit: F,
+) -> Visited
+    where S: Incidence<usize>,
+          A: FnMut(&S::Pos) -> bool,
+          F: FnMut(usize),
+{
+    let mut visited = Visited::new(space.node_count(dim));
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if !visited.insert(&node) {continue}
+        visit(node);
+        let edges = space.out_edges(dim, &node);
+        for edge in edges.iter().rev() {
+            if !allow(edge) {continue}
+            let next = other(space, dim, node, edge);
+            if !visited.contains(&next) {
+                stack.push(next);
+            }
+        }
+    }
+    visited
+}
+
+/// Computes the set of nodes reachable from `start` using allowed edges.
+pub fn reachable<S, A>(space: &S, dim: &S::Dim, start: usize, allow: A) -> Visited
+    where S: Incidence<usize>,
+          A: FnMut(&S::Pos) -> bool,
+{
+    bfs(space, dim, start, allow, |_, _| {})
+}
+
+/// Finds a path from `start` to `goal` with the fewest edges.
+///
+/// Returns the edges along the path, or `None` if `goal` is not reachable.
+pub fn shortest_path<S, A>(
+    space: &S,
+    dim: &S::Dim,
+    start: usize,
+    goal: usize,
+    mut allow: A,
+) -> Option<Vec<S::Pos>>
+    where S: Incidence<usize>,
+          A: FnMut(&S::Pos) -> bool,
+{
+    let mut visited = Visited::new(space.node_count(dim));
+    let mut parents: HashMap<usize, (usize, S::Pos)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    visited.insert(&start);
+    queue.push_back(start);
+    while let Some(node) = queue.pop_front() {
+        if node == goal {
+            return Some(unwind(parents, start, goal));
+        }
+        for edge in space.out_edges(dim, &node) {
+            if !allow(&edge) {continue}
+            let next = other(space, dim, node, &edge);
+            if visited.insert(&next) {
+                parents.insert(next, (node, edge));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Finds a path from `start` to `goal` with the least total weight,
+/// using Dijkstra's algorithm.
+///
+/// The closure `weight` returns the weight of an edge,
+/// or `None` if the edge can not be used.
+/// Returns the total weight and the edges along the path,
+/// or `None` if `goal` is not reachable.
+pub fn dijkstra<S, W, F>(
+    space: &S,
+    dim: &S::Dim,
+    start: usize,
+    goal: usize,
+    mut weight: F,
+) -> Option<(W, Vec<S::Pos>)>
+    where S: Incidence<usize>,
+          W: Copy + Ord + Default + Add<Output = W>,
+          F: FnMut(&S::Pos) -> Option<W>,
+{
+    let mut settled = Visited::new(space.node_count(dim));
+    let mut dist: HashMap<usize, W> = HashMap::new();
+    let mut parents: HashMap<usize, (usize, S::Pos)> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    dist.insert(start, W::default());
+    heap.push(Reverse((W::default(), start)));
+    while let Some(Reverse((d, node))) = heap.pop() {
+        if !settled.insert(&node) {continue}
+        if node == goal {
+            return Some((d, unwind(parents, start, goal)));
+        }
+        for edge in space.out_edges(dim, &node) {
+            let w = match weight(&edge) {
+                Some(w) => w,
+                None => continue,
+            };
+            let next = other(space, dim, node, &edge);
+            if settled.contains(&next) {continue}
+            let nd = d + w;
+            if dist.get(&next).is_none_or(|&old| nd < old) {
+                dist.insert(next, nd);
+                parents.insert(next, (node, edge));
+                heap.push(Reverse((nd, next)));
+            }
+        }
+    }
+    None
+}
+
+/// Follows parent edges from `goal` back to `start`.
+fn unwind<P>(mut parents: HashMap<usize, (usize, P)>, start: usize, goal: usize) -> Vec<P> {
+    let mut path = vec![];
+    let mut node = goal;
+    while node != start {
+        let (parent, edge) = parents.remove(&node).unwrap();
+        path.push(edge);
+        node = parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::search::*;
+
+    #[test]
+    fn bfs_cube() {
+        let x: Context = Construct::new();
+        let ref dim = vec![2, 2, 2];
+        let mut depths = vec![];
+        let visited = bfs(&x, dim, 0, |_| true, |node, depth| depths.push((node, depth)));
+        assert_eq!(visited.count(), 8);
+        assert_eq!(depths[0], (0, 0));
+        assert_eq!(depths[7], (7, 3));
+    }
+
+    #[test]
+    fn dfs_grid() {
+        let x: Context = Construct::new();
+        let ref dim = vec![3, 3];
+        let mut order = vec![];
+        let visited = dfs(&x, dim, 0, |_| true, |node| order.push(node));
+        assert_eq!(visited.count(), 9);
+        assert_eq!(order.len(), 9);
+        assert_eq!(order[0], 0);
+    }
+
+    #[test]
+    fn forbidden() {
+        let x: DirectedContext = Construct::new();
+        let ref dim = vec![4];
+        // Only allow moving one step up.
+        let visited = reachable(&x, dim, 1, |(p, _, b)| *b == p[0] + 1);
+        assert_eq!(visited.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(!visited.contains(&0));
+    }
+
+    #[test]
+    fn path() {
+        let x: Context = Construct::new();
+        let ref dim = vec![3, 3];
+        // Node index is `x + 3 * y`, go from (0, 0) to (2, 2).
+        let path = shortest_path(&x, dim, 0, 8, |_| true).unwrap();
+        assert_eq!(path.len(), 2);
+        let path = shortest_path(&x, dim, 0, 8, |(_, ind, _)| *ind == 0);
+        assert_eq!(path, None);
+        assert_eq!(shortest_path(&x, dim, 4, 4, |_| true), Some(vec![]));
+    }
+
+    #[test]
+    fn weighted() {
+        let x: DirectedContext = Construct::new();
+        let ref dim = vec![4];
+        // Jumping costs the square of the distance, so single steps are cheaper.
+        let (cost, path) = dijkstra(&x, dim, 0, 3, |(p, _, b)| {
+            let d = if *b > p[0] {b - p[0]} else {p[0] - b};
+            Some(d * d)
+        }).unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(path, vec![(vec![0], 0, 1), (vec![1], 0, 2), (vec![2], 0, 3)]);
+        assert_eq!(dijkstra(&x, dim, 0, 3, |_| None::<usize>), None);
+    }
+}
@@ -0,0 +1,378 @@
+use std::marker::PhantomData;
+use std::ops::{
+    AddAssign,
+    Div,
+    MulAssign,
+    Rem,
+    SubAssign,
+};
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Of,
+    Scratch,
+    space::Space,
+};
+
+/// A discrete space that models all sequences up to a maximum length.
+///
+/// Dimension is `(<max length>, <inner dimension>)`,
+/// position is a list of inner positions.
+///
+/// Sequences are ordered by length first and then lexicographically,
+/// where the first element is the most significant (shortlex order).
+/// The count is the sum of powers `1 + n + n^2 + ... + n^max_len`.
+pub struct SeqUpTo<T = Data>(PhantomData<T>);
+
+impl<T> Construct for SeqUpTo<T> {
+    fn new() -> Self { SeqUpTo(PhantomData) }
+}
+
+impl Space<usize> for SeqUpTo<Data> {
+    type Dim = (usize, usize);
+    type Pos = Vec<usize>;
+    fn count(&self, &(len, n): &(usize, usize)) -> usize {
+        let mut sum = 0;
+        let mut pow = 1;
+        for l in 0..=len {
+            sum += pow;
+            if l < len { pow *= n; }
+        }
+        sum
+    }
+    fn zero(&self, _dim: &(usize, usize)) -> Vec<usize> {
+        vec![]
+    }
+    fn to_index(&self, &(_, n): &(usize, usize), pos: &Vec<usize>) -> usize {
+        let mut offset = 0;
+        let mut pow = 1;
+        for _ in 0..pos.len() {
+            offset += pow;
+            pow *= n;
+        }
+        let mut index = 0;
+        for &x in pos {
+            index = index * n + x;
+        }
+        offset + index
+    }
+    fn to_pos(&self, &(len, n): &(usize, usize), mut index: usize, pos: &mut Vec<usize>) {
+        let mut l = 0;
+        let mut pow = 1;
+        while l < len && index >= pow {
+            index -= pow;
+            pow *= n;
+            l += 1;
+        }
+        pos.clear();
+        pos.resize(l, 0);
+        for x in pos.iter_mut().rev() {
+            *x = index % n;
+            index /= n;
+        }
+    }
+}
+
+impl Space<BigUint> for SeqUpTo<Data> {
+    type Dim = (usize, BigUint);
+    type Pos = Vec<BigUint>;
+    fn count(&self, (len, n): &Self::Dim) -> BigUint {
+        let mut sum: BigUint = 0usize.into();
+        let mut pow: BigUint = 1usize.into();
+        for l in 0..=*len {
+            sum += &pow;
+            if l < *len { pow *= n; }
+        }
+        sum
+    }
+    fn zero(&self, _dim: &Self::Dim) -> Self::Pos {
+        vec![]
+    }
+    fn to_index(&self, (_, n): &Self::Dim, pos: &Self::Pos) -> BigUint {
+        let mut offset: BigUint = 0usize.into();
+        let mut pow: BigUint = 1usize.into();
+        for _ in 0..pos.len() {
+            offset += &pow;
+            pow *= n;
+        }
+        let mut index: BigUint = 0usize.into();
+        for x in pos {
+            index *= n;
+            index += x;
+        }
+        offset + index
+    }
+    fn to_pos(&self, (len, n): &Self::Dim, mut index: BigUint, pos: &mut Self::Pos) {
+        let mut l = 0;
+        let mut pow: BigUint = 1usize.into();
+        while l < *len && index >= pow {
+            index -= &pow;
+            pow *= n;
+            l += 1;
+        }
+        pos.clear();
+        pos.resize(l, 0usize.into());
+        for x in pos.iter_mut().rev() {
+            *x = &index % n;
+            index /= n;
+        }
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch,
+        (_, n): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        use num_traits::{One, Zero};
+
+        let mut pow = scratch.take();
+        index.set_zero();
+        for x in pos {
+            *index *= n;
+            *index += x;
+        }
+        // Add the offset of the sequences which are shorter.
+        pow.set_one();
+        for _ in 0..pos.len() {
+            *index += &pow;
+            pow *= n;
+        }
+        scratch.put(pow);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch,
+        (len, n): &Self::Dim,
+        index: &BigUint,
+        pos: &mut Self::Pos,
+    ) {
+        use num_traits::One;
+
+        let mut rest = scratch.take();
+        let mut pow = scratch.take();
+        rest.clone_from(index);
+        pow.set_one();
+        let mut l = 0;
+        while l < *len && rest >= pow {
+            rest -= &pow;
+            pow *= n;
+            l += 1;
+        }
+        pos.resize(l, 0usize.into());
+        for x in pos.iter_mut().rev() {
+            x.clone_from(&rest);
+            *x %= n;
+            rest /= n;
+        }
+        scratch.put(rest);
+        scratch.put(pow);
+    }
+}
+
+impl<N, T> Space<N> for SeqUpTo<Of<T>>
+    where T: Space<N>,
+          SeqUpTo<Data>: Space<N, Dim = (usize, N), Pos = Vec<N>>,
+          N: Clone +
+             From<usize> +
+             PartialOrd +
+             for<'a> AddAssign<&'a N> +
+             for<'a> SubAssign<&'a N> +
+             for<'a> MulAssign<&'a N>,
+          for<'a> &'a N: Div<&'a N, Output = N> + Rem<&'a N, Output = N>,
+{
+    type Dim = (usize, T::Dim);
+    type Pos = Vec<T::Pos>;
+    fn count(&self, (len, dim): &Self::Dim) -> N {
+        let of: T = Construct::new();
+        let n = of.count(dim);
+        let mut sum: N = 0usize.into();
+        let mut pow: N = 1usize.into();
+        for l in 0..=*len {
+            sum += &pow;
+            if l < *len { pow *= &n; }
+        }
+        sum
+    }
+    fn zero(&self, _dim: &Self::Dim) -> Self::Pos {
+        vec![]
+    }
+    fn to_index(&self, (_, dim): &Self::Dim, pos: &Self::Pos) -> N {
+        let of: T = Construct::new();
+        let n = of.count(dim);
+        let mut offset: N = 0usize.into();
+        let mut pow: N = 1usize.into();
+        for _ in 0..pos.len() {
+            offset += &pow;
+            pow *= &n;
+        }
+        let mut index: N = 0usize.into();
+        for x in pos {
+            index *= &n;
+            index += &of.to_index(dim, x);
+        }
+        offset += &index;
+        offset
+    }
+    fn to_pos(&self, (len, dim): &Self::Dim, mut index: N, pos: &mut Self::Pos) {
+        let of: T = Construct::new();
+        let n = of.count(dim);
+        let mut l = 0;
+        let mut pow: N = 1usize.into();
+        while l < *len && index >= pow {
+            index -= &pow;
+            pow *= &n;
+            l += 1;
+        }
+        pos.clear();
+        for _ in 0..l {
+            pos.push(of.zero(dim));
+        }
+        for x in pos.iter_mut().rev() {
+            let q = &index / &n;
+            of.to_pos(dim, &index % &n, x);
+            index = q;
+        }
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (len, dim): &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: SeqUpTo<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        for (x, i) in pos.iter().zip(&mut inner) {
+            of.to_index_with(scratch, dim, x, i);
+        }
+        data.to_index_with(scratch, &(*len, of.count(dim)), &inner, index);
+        scratch.put_many(inner);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (len, dim): &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: SeqUpTo<Data> = Construct::new();
+        let mut inner = scratch.take_many(pos.len());
+        data.to_pos_with(scratch, &(*len, of.count(dim)), index, &mut inner);
+        pos.truncate(inner.len());
+        while pos.len() < inner.len() {pos.push(of.zero(dim))}
+        for (i, x) in inner.iter().zip(pos) {
+            of.to_pos_with(scratch, dim, i, x);
+        }
+        scratch.put_many(inner);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        for x in pos {
+            of.try_to_index(&dim.1, x)?;
+        }
+        Some(self.to_index(dim, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn features() {
+        is_complete::<usize, SeqUpTo>();
+        is_complete::<usize, SeqUpTo<Of<Pair>>>();
+    }
+
+    #[test]
+    fn data() {
+        let x: SeqUpTo = Construct::new();
+        let ref dim = (2, 2);
+        assert_eq!(x.count(dim), 7);
+        assert_eq!(x.to_index(dim, &vec![]), 0);
+        assert_eq!(x.to_index(dim, &vec![0]), 1);
+        assert_eq!(x.to_index(dim, &vec![1]), 2);
+        assert_eq!(x.to_index(dim, &vec![0, 0]), 3);
+        assert_eq!(x.to_index(dim, &vec![0, 1]), 4);
+        assert_eq!(x.to_index(dim, &vec![1, 0]), 5);
+        assert_eq!(x.to_index(dim, &vec![1, 1]), 6);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+        x.to_pos(dim, 5, &mut pos);
+        assert_eq!(pos, vec![1, 0]);
+
+        assert_eq!(x.count(&(3, 0)), 1);
+        assert_eq!(x.count(&(3, 1)), 4);
+        x.to_pos(&(3, 1), 3, &mut pos);
+        assert_eq!(pos, vec![0, 0, 0]);
+    }
+
+    fn conv(v: Vec<usize>) -> Vec<BigUint> {
+        v.into_iter().map(|n| n.into()).collect()
+    }
+
+    #[test]
+    fn data_big() {
+        use std::convert::TryInto;
+
+        let x: SeqUpTo = Construct::new();
+        let ref dim: (usize, BigUint) = (3, 3usize.into());
+        assert_eq!(x.count(dim), 40usize.into());
+        assert_eq!(x.to_index(dim, &vec![]), 0usize.into());
+        assert_eq!(x.to_index(dim, &conv(vec![2])), 3usize.into());
+        assert_eq!(x.to_index(dim, &conv(vec![0, 0])), 4usize.into());
+        assert_eq!(x.to_index(dim, &conv(vec![0, 0, 0])), 13usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+        x.to_pos(dim, 39usize.into(), &mut pos);
+        assert_eq!(pos, conv(vec![2, 2, 2]));
+    }
+
+    #[test]
+    fn of() {
+        let x: SeqUpTo<Of<Pair>> = Construct::new();
+        let ref dim = (2, 3);
+        assert_eq!(x.count(dim), 13);
+        assert_eq!(x.to_index(dim, &vec![(0, 1)]), 1);
+        assert_eq!(x.to_index(dim, &vec![(0, 1), (0, 2)]), 5);
+        assert_eq!(x.to_index(dim, &vec![(1, 2), (1, 2)]), 12);
+        let mut pos = x.zero(dim);
+        for i in 0..x.count(dim) {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+        x.to_pos(dim, 5, &mut pos);
+        assert_eq!(pos, vec![(0, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn of_big() {
+        use std::convert::TryInto;
+
+        let x: SeqUpTo<Of<Pair>> = Construct::new();
+        let ref dim: (usize, BigUint) = (2, 3usize.into());
+        assert_eq!(x.count(dim), 13usize.into());
+        let count: usize = x.count(dim).try_into().unwrap();
+        let mut pos = x.zero(dim);
+        for i in 0..count {
+            x.to_pos(dim, i.into(), &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i.into());
+        }
+    }
+}
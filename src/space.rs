@@ -3,6 +3,7 @@
 use crate::{
     BigUint,
     Construct,
+    Scratch,
     Count,
     Zero,
     ToIndex,
@@ -24,6 +25,62 @@ pub trait Space<N>: Construct + Sized {
     fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> N;
     /// Converts index to position.
     fn to_pos(&self, dim: &Self::Dim, index: N, pos: &mut Self::Pos);
+    /// Converts position to index, or returns `None` if the position is not in the space.
+    ///
+    /// Views that contain only part of another space override this.
+    fn try_to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        Some(self.to_index(dim, pos))
+    }
+    /// Converts many positions to indices.
+    ///
+    /// Spaces can override this to compute constants of the dimension once.
+    /// Panics if the slices have different lengths.
+    fn to_index_batch(&self, dim: &Self::Dim, pos: &[Self::Pos], index: &mut [N]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        for (p, i) in pos.iter().zip(index) {
+            *i = self.to_index(dim, p);
+        }
+    }
+    /// Converts many indices to positions.
+    ///
+    /// Spaces can override this to compute constants of the dimension once.
+    /// Panics if the slices have different lengths.
+    fn to_pos_batch(&self, dim: &Self::Dim, index: &[N], pos: &mut [Self::Pos])
+        where N: Clone
+    {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        for (i, p) in index.iter().zip(pos) {
+            self.to_pos(dim, i.clone(), p);
+        }
+    }
+    /// Converts position to index, reusing temporary values of a scratch context.
+    ///
+    /// Spaces can override this to avoid allocating memory for big numbers.
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        pos: &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        *index = self.to_index(dim, pos);
+    }
+    /// Converts index to position, reusing temporary values of a scratch context.
+    ///
+    /// Spaces can override this to avoid allocating memory for big numbers.
+    fn to_pos_with(
+        &self,
+        _scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        pos: &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        self.to_pos(dim, index.clone(), pos);
+    }
 }
 
 impl<D, T: Space<usize, Dim = D>> Count<D, usize> for T {
@@ -44,16 +101,46 @@ impl<D, P, T: Space<BigUint, Dim = D, Pos = P>> Zero<D, P, BigUint> for T {
 
 impl<D, P, T: Space<usize, Dim = D, Pos = P>> ToIndex<D, P, usize> for T {
     fn to_index(&self, dim: &D, pos: &P) -> usize {Space::<usize>::to_index(self, dim, pos)}
+    fn try_to_index(&self, dim: &D, pos: &P) -> Option<usize> {
+        Space::<usize>::try_to_index(self, dim, pos)
+    }
+    fn to_index_batch(&self, dim: &D, pos: &[P], index: &mut [usize]) {
+        Space::<usize>::to_index_batch(self, dim, pos, index)
+    }
+    fn to_index_with(&self, scratch: &mut Scratch<usize>, dim: &D, pos: &P, index: &mut usize) {
+        Space::<usize>::to_index_with(self, scratch, dim, pos, index)
+    }
 }
 
 impl<D, P, T: Space<BigUint, Dim = D, Pos = P>> ToIndex<D, P, BigUint> for T {
     fn to_index(&self, dim: &D, pos: &P) -> BigUint {Space::<BigUint>::to_index(self, dim, pos)}
+    fn try_to_index(&self, dim: &D, pos: &P) -> Option<BigUint> {
+        Space::<BigUint>::try_to_index(self, dim, pos)
+    }
+    fn to_index_batch(&self, dim: &D, pos: &[P], index: &mut [BigUint]) {
+        Space::<BigUint>::to_index_batch(self, dim, pos, index)
+    }
+    fn to_index_with(&self, scratch: &mut Scratch<BigUint>, dim: &D, pos: &P, index: &mut BigUint) {
+        Space::<BigUint>::to_index_with(self, scratch, dim, pos, index)
+    }
 }
 
 impl<D, P, T: Space<usize, Dim = D, Pos = P>> ToPos<D, P, usize> for T {
     fn to_pos(&self, dim: &D, ind: usize, pos: &mut P) {Space::<usize>::to_pos(self, dim, ind, pos)}
+    fn to_pos_batch(&self, dim: &D, index: &[usize], pos: &mut [P]) {
+        Space::<usize>::to_pos_batch(self, dim, index, pos)
+    }
+    fn to_pos_with(&self, scratch: &mut Scratch<usize>, dim: &D, index: &usize, pos: &mut P) {
+        Space::<usize>::to_pos_with(self, scratch, dim, index, pos)
+    }
 }
 
 impl<D, P, T: Space<BigUint, Dim = D, Pos = P>> ToPos<D, P, BigUint> for T {
     fn to_pos(&self, dim: &D, ind: BigUint, pos: &mut P) {Space::<BigUint>::to_pos(self, dim, ind, pos)}
+    fn to_pos_batch(&self, dim: &D, index: &[BigUint], pos: &mut [P]) {
+        Space::<BigUint>::to_pos_batch(self, dim, index, pos)
+    }
+    fn to_pos_with(&self, scratch: &mut Scratch<BigUint>, dim: &D, index: &BigUint, pos: &mut P) {
+        Space::<BigUint>::to_pos_with(self, scratch, dim, index, pos)
+    }
 }
@@ -0,0 +1,276 @@
+#[cfg(feature = "mmap")]
+use std::fs::File;
+#[cfg(feature = "mmap")]
+use std::io;
+
+use crate::{
+    Construct,
+    space::Space,
+};
+
+/// The storage of bits.
+enum Words {
+    Heap(Vec<u64>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::MmapMut),
+}
+
+impl Words {
+    fn as_slice(&self) -> &[u64] {
+        match self {
+            Words::Heap(words) => words,
+            // Memory maps are page aligned, so the bytes line up with words.
+            #[cfg(feature = "mmap")]
+            Words::Mapped(map) => unsafe { map.align_to::<u64>().1 },
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u64] {
+        match self {
+            Words::Heap(words) => words,
+            #[cfg(feature = "mmap")]
+            Words::Mapped(map) => unsafe { map.align_to_mut::<u64>().1 },
+        }
+    }
+}
+
+/// Stores a set of positions in a space as bits.
+///
+/// Uses one bit per position, indexed by `to_index`,
+/// which is compact for dense sets such as visited nodes in a search.
+///
+/// With the `mmap` feature, the bits can be stored in a memory mapped file
+/// using `SpaceBitSet::from_file`, which handles spaces that do not fit in memory.
+pub struct SpaceBitSet<S: Space<usize>> {
+    space: S,
+    dim: S::Dim,
+    len: usize,
+    words: Words,
+}
+
+impl<S: Space<usize>> SpaceBitSet<S> {
+    /// Creates a new empty set.
+    pub fn new(dim: S::Dim) -> SpaceBitSet<S> {
+        let space: S = Construct::new();
+        let len = space.count(&dim);
+        let words = Words::Heap(vec![0; len.div_ceil(64)]);
+        SpaceBitSet {space, dim, len, words}
+    }
+
+    /// Creates a new set stored in a memory mapped file.
+    ///
+    /// The file is resized to fit the bits of the space.
+    /// Bits already in the file are kept, so a set can be reopened later.
+    #[cfg(feature = "mmap")]
+    pub fn from_file(dim: S::Dim, file: &File) -> io::Result<SpaceBitSet<S>> {
+        let space: S = Construct::new();
+        let len = space.count(&dim);
+        let bytes = len.div_ceil(64) * 8;
+        file.set_len(bytes as u64)?;
+        let words = if bytes == 0 {
+            Words::Heap(vec![])
+        } else {
+            Words::Mapped(unsafe { memmap2::MmapMut::map_mut(file)? })
+        };
+        Ok(SpaceBitSet {space, dim, len, words})
+    }
+
+    /// Writes changes to the backing file, if any.
+    #[cfg(feature = "mmap")]
+    pub fn flush(&self) -> io::Result<()> {
+        match &self.words {
+            Words::Heap(_) => Ok(()),
+            Words::Mapped(map) => map.flush(),
+        }
+    }
+
+    /// Gets the dimension.
+    pub fn dim(&self) -> &S::Dim {&self.dim}
+
+    /// Gets the number of positions in the space.
+    pub fn capacity(&self) -> usize {self.len}
+
+    /// Counts the positions in the set.
+    pub fn count(&self) -> usize {
+        self.words.as_slice().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.words.as_slice().iter().all(|&w| w == 0)
+    }
+
+    /// Removes all positions.
+    pub fn clear(&mut self) {
+        for w in self.words.as_mut_slice() {*w = 0}
+    }
+
+    /// Inserts a position, returning `true` if it was not in the set.
+    pub fn insert(&mut self, pos: &S::Pos) -> bool {
+        let index = self.space.to_index(&self.dim, pos);
+        self.insert_index(index)
+    }
+
+    /// Inserts an index, returning `true` if it was not in the set.
+    pub fn insert_index(&mut self, index: usize) -> bool {
+        assert!(index < self.len, "Index out of range");
+        let (i, mask) = (index / 64, 1 << (index % 64));
+        let w = &mut self.words.as_mut_slice()[i];
+        let new = *w & mask == 0;
+        *w |= mask;
+        new
+    }
+
+    /// Removes a position, returning `true` if it was in the set.
+    pub fn remove(&mut self, pos: &S::Pos) -> bool {
+        let index = self.space.to_index(&self.dim, pos);
+        self.remove_index(index)
+    }
+
+    /// Removes an index, returning `true` if it was in the set.
+    pub fn remove_index(&mut self, index: usize) -> bool {
+        if index >= self.len {return false}
+        let (i, mask) = (index / 64, 1 << (index % 64));
+        let w = &mut self.words.as_mut_slice()[i];
+        let old = *w & mask != 0;
+        *w &= !mask;
+        old
+    }
+
+    /// Returns `true` if the position is in the set.
+    pub fn contains(&self, pos: &S::Pos) -> bool {
+        self.contains_index(self.space.to_index(&self.dim, pos))
+    }
+
+    /// Returns `true` if the index is in the set.
+    pub fn contains_index(&self, index: usize) -> bool {
+        index < self.len && self.words.as_slice()[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Adds all positions of another set with the same dimension.
+    pub fn union_with(&mut self, other: &SpaceBitSet<S>) {
+        assert_eq!(self.len, other.len, "Different capacity");
+        let words = self.words.as_mut_slice();
+        for (a, b) in words.iter_mut().zip(other.words.as_slice()) {*a |= b}
+    }
+
+    /// Keeps only the positions that are in another set with the same dimension.
+    pub fn intersect_with(&mut self, other: &SpaceBitSet<S>) {
+        assert_eq!(self.len, other.len, "Different capacity");
+        let words = self.words.as_mut_slice();
+        for (a, b) in words.iter_mut().zip(other.words.as_slice()) {*a &= b}
+    }
+
+    /// Removes the positions that are in another set with the same dimension.
+    pub fn difference_with(&mut self, other: &SpaceBitSet<S>) {
+        assert_eq!(self.len, other.len, "Different capacity");
+        let words = self.words.as_mut_slice();
+        for (a, b) in words.iter_mut().zip(other.words.as_slice()) {*a &= !b}
+    }
+
+    /// Iterates over the indices in the set in increasing order.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.as_slice().iter().enumerate().flat_map(|(i, &w)| {
+            let mut w = w;
+            std::iter::from_fn(move || {
+                if w == 0 {return None}
+                let bit = w.trailing_zeros() as usize;
+                w &= w - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    /// Iterates over the positions in the set in index order.
+    pub fn iter(&self) -> impl Iterator<Item = S::Pos> + '_ {
+        self.indices().map(move |i| {
+            let mut pos = self.space.zero(&self.dim);
+            self.space.to_pos(&self.dim, i, &mut pos);
+            pos
+        })
+    }
+}
+
+impl<S> Clone for SpaceBitSet<S>
+    where S: Space<usize>,
+          S::Dim: Clone,
+{
+    /// Clones the bits into memory, also when the set is stored in a file.
+    fn clone(&self) -> Self {
+        SpaceBitSet {
+            space: Construct::new(),
+            dim: self.dim.clone(),
+            len: self.len,
+            words: Words::Heap(self.words.as_slice().to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn dimension_n() {
+        let mut a: SpaceBitSet<DimensionN> = SpaceBitSet::new(vec![10, 10]);
+        assert_eq!(a.capacity(), 100);
+        assert!(a.is_empty());
+        assert!(a.insert(&vec![3, 4]));
+        assert!(!a.insert(&vec![3, 4]));
+        assert!(a.insert(&vec![9, 9]));
+        assert!(a.contains(&vec![3, 4]));
+        assert!(!a.contains(&vec![4, 3]));
+        assert_eq!(a.count(), 2);
+
+        let mut b = a.clone();
+        assert!(b.remove(&vec![9, 9]));
+        assert!(!b.remove(&vec![9, 9]));
+        b.insert(&vec![0, 0]);
+
+        let mut c = a.clone();
+        c.union_with(&b);
+        assert_eq!(c.iter().collect::<Vec<_>>(), vec![vec![0, 0], vec![3, 4], vec![9, 9]]);
+        c.intersect_with(&a);
+        assert_eq!(c.iter().collect::<Vec<_>>(), vec![vec![3, 4], vec![9, 9]]);
+        c.difference_with(&b);
+        assert_eq!(c.iter().collect::<Vec<_>>(), vec![vec![9, 9]]);
+        c.clear();
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn indices() {
+        let mut a: SpaceBitSet<Permutation> = SpaceBitSet::new(5);
+        for i in (0..120).step_by(7) {a.insert_index(i);}
+        assert_eq!(a.indices().collect::<Vec<_>>(), (0..120).step_by(7).collect::<Vec<_>>());
+        assert!(a.contains(&vec![0, 1, 2, 3, 4]));
+        assert!(!a.contains_index(120));
+        assert!(!a.remove_index(200));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn file() {
+        /// Removes the file when the test ends, also on failure.
+        struct Remove(std::path::PathBuf);
+
+        impl Drop for Remove {
+            fn drop(&mut self) {let _ = std::fs::remove_file(&self.0);}
+        }
+
+        let name = format!("discrete_space_bit_set_test_{}", std::process::id());
+        let path = Remove(std::env::temp_dir().join(name));
+        let file = std::fs::OpenOptions::new()
+            .read(true).write(true).create_new(true)
+            .open(&path.0).unwrap();
+        let mut a: SpaceBitSet<Pair> = SpaceBitSet::from_file(100, &file).unwrap();
+        assert_eq!(a.capacity(), 4950);
+        a.insert(&(2, 3));
+        a.insert_index(4949);
+        a.flush().unwrap();
+        drop(a);
+
+        let a: SpaceBitSet<Pair> = SpaceBitSet::from_file(100, &file).unwrap();
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![(2, 3), (98, 99)]);
+    }
+}
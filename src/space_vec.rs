@@ -0,0 +1,179 @@
+use crate::{
+    Construct,
+    space::Space,
+};
+
+/// Stores one value for every position in a space.
+///
+/// The values are stored in a `Vec<V>` of length `count(dim)`,
+/// such that the value of a position is at its index.
+pub struct SpaceVec<S: Space<usize>, V> {
+    space: S,
+    dim: S::Dim,
+    data: Vec<V>,
+}
+
+impl<S: Space<usize>, V> SpaceVec<S, V> {
+    /// Creates a new vector with the same value at every position.
+    pub fn new(dim: S::Dim, value: V) -> SpaceVec<S, V> where V: Clone {
+        let space: S = Construct::new();
+        let data = vec![value; space.count(&dim)];
+        SpaceVec {space, dim, data}
+    }
+
+    /// Creates a new vector with a value for each position.
+    pub fn from_fn<F: FnMut(&S::Pos) -> V>(dim: S::Dim, f: F) -> SpaceVec<S, V> {
+        let space: S = Construct::new();
+        let data = Vec::with_capacity(space.count(&dim));
+        let mut res = SpaceVec {space, dim, data};
+        res.extend_with(f);
+        res
+    }
+
+    /// Gets the dimension.
+    pub fn dim(&self) -> &S::Dim {&self.dim}
+
+    /// Gets the number of values.
+    pub fn len(&self) -> usize {self.data.len()}
+
+    /// Returns `true` if there are no values.
+    pub fn is_empty(&self) -> bool {self.data.is_empty()}
+
+    /// Gets the values in index order.
+    pub fn as_slice(&self) -> &[V] {&self.data}
+
+    /// Gets the mutable values in index order.
+    pub fn as_mut_slice(&mut self) -> &mut [V] {&mut self.data}
+
+    /// Gets the value at a position.
+    ///
+    /// Returns `None` if the position is not in the space.
+    pub fn get(&self, pos: &S::Pos) -> Option<&V> where S::Pos: PartialEq {
+        let index = self.index_of(pos)?;
+        self.data.get(index)
+    }
+
+    /// Gets the mutable value at a position.
+    ///
+    /// Returns `None` if the position is not in the space.
+    pub fn get_mut(&mut self, pos: &S::Pos) -> Option<&mut V> where S::Pos: PartialEq {
+        let index = self.index_of(pos)?;
+        self.data.get_mut(index)
+    }
+
+    /// Iterates over positions and values in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (S::Pos, &V)> + '_ {
+        self.data.iter().enumerate().map(move |(i, v)| {
+            let mut pos = self.space.zero(&self.dim);
+            self.space.to_pos(&self.dim, i, &mut pos);
+            (pos, v)
+        })
+    }
+
+    /// Iterates over positions and mutable values in index order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (S::Pos, &mut V)> + '_ {
+        let space = &self.space;
+        let dim = &self.dim;
+        self.data.iter_mut().enumerate().map(move |(i, v)| {
+            let mut pos = space.zero(dim);
+            space.to_pos(dim, i, &mut pos);
+            (pos, v)
+        })
+    }
+
+    /// Sets the value of every position.
+    pub fn fill_with<F: FnMut(&S::Pos) -> V>(&mut self, f: F) {
+        self.data.clear();
+        self.extend_with(f);
+    }
+
+    /// Changes the dimension, keeping the values by index.
+    ///
+    /// New indices get `value`.
+    /// Positions keep their values when the space order is stable under
+    /// change of dimension, e.g. for `Dimension`, `Pair` or `EqPair`.
+    pub fn resize_dim(&mut self, dim: S::Dim, value: V) where V: Clone {
+        let count = self.space.count(&dim);
+        self.dim = dim;
+        self.data.truncate(count);
+        self.data.resize(count, value);
+    }
+
+    /// Converts into the values in index order.
+    pub fn into_vec(self) -> Vec<V> {self.data}
+
+    /// Gets the index of a position, checking that it converts back to the same position,
+    /// since an invalid position, e.g. `(1, 0)` of `Pair`, may have the index of another.
+    fn index_of(&self, pos: &S::Pos) -> Option<usize> where S::Pos: PartialEq {
+        let index = self.space.try_to_index(&self.dim, pos)?;
+        if index >= self.data.len() {return None}
+        let mut p = self.space.zero(&self.dim);
+        self.space.to_pos(&self.dim, index, &mut p);
+        if p == *pos {Some(index)} else {None}
+    }
+
+    fn extend_with<F: FnMut(&S::Pos) -> V>(&mut self, mut f: F) {
+        let count = self.space.count(&self.dim);
+        let mut pos = self.space.zero(&self.dim);
+        for i in self.data.len()..count {
+            self.space.to_pos(&self.dim, i, &mut pos);
+            self.data.push(f(&pos));
+        }
+    }
+}
+
+impl<S, V> Clone for SpaceVec<S, V>
+    where S: Space<usize>,
+          S::Dim: Clone,
+          V: Clone,
+{
+    fn clone(&self) -> Self {
+        SpaceVec {
+            space: Construct::new(),
+            dim: self.dim.clone(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn pair() {
+        let mut weights: SpaceVec<Pair, f64> = SpaceVec::new(4, 0.0);
+        assert_eq!(weights.len(), 6);
+        *weights.get_mut(&(1, 3)).unwrap() = 2.5;
+        assert_eq!(weights.get(&(1, 3)), Some(&2.5));
+        assert_eq!(weights.get(&(0, 1)), Some(&0.0));
+        assert_eq!(weights.get(&(4, 5)), None);
+        // Has the index of `(0, 1)`.
+        assert_eq!(weights.get(&(1, 0)), None);
+        assert!(weights.get_mut(&(1, 0)).is_none());
+
+        weights.fill_with(|&(a, b)| (a + b) as f64);
+        let sums: Vec<_> = weights.iter().map(|(pos, &v)| (pos, v)).collect();
+        assert_eq!(sums[0], ((0, 1), 1.0));
+        assert_eq!(sums[5], ((2, 3), 5.0));
+
+        // Pairs of the lower dimension keep their values.
+        weights.resize_dim(5, -1.0);
+        assert_eq!(weights.len(), 10);
+        assert_eq!(weights.get(&(1, 3)), Some(&4.0));
+        assert_eq!(weights.get(&(1, 4)), Some(&-1.0));
+    }
+
+    #[test]
+    fn context() {
+        let mut values: SpaceVec<Context, usize> =
+            SpaceVec::from_fn(vec![2, 3], |(p, ind, b): &(Vec<usize>, usize, usize)| {
+                p[*ind] + b
+            });
+        assert_eq!(values.len(), 9);
+        assert_eq!(values.get(&(vec![0, 1], 1, 2)), Some(&3));
+        for (_, v) in values.iter_mut() {*v *= 2}
+        assert_eq!(values.get(&(vec![0, 1], 1, 2)), Some(&6));
+        assert_eq!(values.clone().into_vec(), values.as_slice());
+    }
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{
+    BigUint,
+    Construct,
+    space::Space,
+};
+
+/// The number of limbs stored without heap allocation.
+const INLINE: usize = 3;
+
+/// An index stored as little-endian 64 bit limbs without leading zeros.
+///
+/// Indices up to 192 bits are stored inline.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Key {
+    Inline(u8, [u64; INLINE]),
+    Heap(Box<[u64]>),
+}
+
+impl Key {
+    fn limbs(&self) -> &[u64] {
+        match self {
+            Key::Inline(len, limbs) => &limbs[..*len as usize],
+            Key::Heap(limbs) => limbs,
+        }
+    }
+
+    fn to_biguint(&self) -> BigUint {
+        let mut res = BigUint::from(0usize);
+        for &limb in self.limbs().iter().rev() {
+            res = (res << 64) | BigUint::from(limb);
+        }
+        res
+    }
+}
+
+impl From<&BigUint> for Key {
+    fn from(index: &BigUint) -> Key {
+        let len = index.iter_u64_digits().len();
+        if len <= INLINE {
+            let mut limbs = [0; INLINE];
+            for (limb, digit) in limbs.iter_mut().zip(index.iter_u64_digits()) {
+                *limb = digit;
+            }
+            Key::Inline(len as u8, limbs)
+        } else {
+            Key::Heap(index.iter_u64_digits().collect())
+        }
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Key) -> Option<Ordering> {Some(self.cmp(other))}
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Key) -> Ordering {
+        let (a, b) = (self.limbs(), other.limbs());
+        a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+}
+
+fn to_key(bound: Bound<&BigUint>) -> Bound<Key> {
+    match bound {
+        Bound::Included(x) => Bound::Included(x.into()),
+        Bound::Excluded(x) => Bound::Excluded(x.into()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Stores values for a few positions in a huge space.
+///
+/// Values are keyed by index and kept in index order.
+/// Indices are stored as packed 64 bit limbs instead of `BigUint`,
+/// such that indices up to 192 bits do not allocate.
+pub struct SparseSpaceMap<S: Space<BigUint>, V> {
+    space: S,
+    dim: S::Dim,
+    map: BTreeMap<Key, V>,
+}
+
+impl<S: Space<BigUint>, V> SparseSpaceMap<S, V> {
+    /// Creates a new empty map.
+    pub fn new(dim: S::Dim) -> SparseSpaceMap<S, V> {
+        SparseSpaceMap {space: Construct::new(), dim, map: BTreeMap::new()}
+    }
+
+    /// Gets the dimension.
+    pub fn dim(&self) -> &S::Dim {&self.dim}
+
+    /// Gets the number of stored values.
+    pub fn len(&self) -> usize {self.map.len()}
+
+    /// Returns `true` if there are no stored values.
+    pub fn is_empty(&self) -> bool {self.map.is_empty()}
+
+    /// Removes all values.
+    pub fn clear(&mut self) {self.map.clear()}
+
+    /// Inserts a value at a position, returning the old value.
+    pub fn insert(&mut self, pos: &S::Pos, value: V) -> Option<V> {
+        let index = self.space.to_index(&self.dim, pos);
+        self.insert_index(&index, value)
+    }
+
+    /// Inserts a value at an index, returning the old value.
+    pub fn insert_index(&mut self, index: &BigUint, value: V) -> Option<V> {
+        self.map.insert(index.into(), value)
+    }
+
+    /// Gets the value at a position.
+    pub fn get(&self, pos: &S::Pos) -> Option<&V> {
+        self.get_index(&self.space.to_index(&self.dim, pos))
+    }
+
+    /// Gets the value at an index.
+    pub fn get_index(&self, index: &BigUint) -> Option<&V> {
+        self.map.get(&index.into())
+    }
+
+    /// Gets the mutable value at a position.
+    pub fn get_mut(&mut self, pos: &S::Pos) -> Option<&mut V> {
+        let index = self.space.to_index(&self.dim, pos);
+        self.map.get_mut(&(&index).into())
+    }
+
+    /// Returns `true` if there is a value at a position.
+    pub fn contains(&self, pos: &S::Pos) -> bool {
+        self.get(pos).is_some()
+    }
+
+    /// Removes the value at a position.
+    pub fn remove(&mut self, pos: &S::Pos) -> Option<V> {
+        let index = self.space.to_index(&self.dim, pos);
+        self.remove_index(&index)
+    }
+
+    /// Removes the value at an index.
+    pub fn remove_index(&mut self, index: &BigUint) -> Option<V> {
+        self.map.remove(&index.into())
+    }
+
+    /// Iterates over indices and values in index order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (BigUint, &V)> + '_ {
+        self.map.iter().map(|(key, v)| (key.to_biguint(), v))
+    }
+
+    /// Iterates over positions and values in index order.
+    pub fn iter_pos(&self) -> impl DoubleEndedIterator<Item = (S::Pos, &V)> + '_ {
+        self.iter().map(move |(index, v)| (self.pos(index), v))
+    }
+
+    /// Iterates over indices and values within a range of indices.
+    pub fn range<R>(&self, range: R) -> impl DoubleEndedIterator<Item = (BigUint, &V)> + '_
+        where R: RangeBounds<BigUint>
+    {
+        let bounds = (to_key(range.start_bound()), to_key(range.end_bound()));
+        self.map.range(bounds).map(|(key, v)| (key.to_biguint(), v))
+    }
+
+    /// Gets the first index and value.
+    pub fn first(&self) -> Option<(BigUint, &V)> {self.iter().next()}
+
+    /// Gets the last index and value.
+    pub fn last(&self) -> Option<(BigUint, &V)> {self.iter().next_back()}
+
+    fn pos(&self, index: BigUint) -> S::Pos {
+        let mut pos = self.space.zero(&self.dim);
+        self.space.to_pos(&self.dim, index, &mut pos);
+        pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn keys() {
+        use super::Key;
+
+        let small = BigUint::from(5usize);
+        let big: BigUint = BigUint::from(1usize) << 200usize;
+        assert!(matches!(Key::from(&small), Key::Inline(1, _)));
+        assert!(matches!(Key::from(&big), Key::Heap(_)));
+        assert!(Key::from(&small) < Key::from(&big));
+        assert!(Key::from(&(BigUint::from(1usize) << 64usize)) > Key::from(&BigUint::from(u64::MAX)));
+        assert!(Key::from(&BigUint::from(0usize)) < Key::from(&small));
+        assert_eq!(Key::from(&big).to_biguint(), big);
+        assert_eq!(Key::from(&BigUint::from(0usize)).to_biguint(), 0usize.into());
+    }
+
+    #[test]
+    fn power_set() {
+        let mut map: SparseSpaceMap<PowerSet<Of<Pair>>, &str> = SparseSpaceMap::new(20usize.into());
+        let ref a: Vec<(BigUint, BigUint)> = vec![(0usize.into(), 1usize.into())];
+        let ref b: Vec<(BigUint, BigUint)> = vec![(18usize.into(), 19usize.into())];
+        let ref c: Vec<(BigUint, BigUint)> = vec![];
+        assert_eq!(map.insert(a, "a"), None);
+        assert_eq!(map.insert(b, "b"), None);
+        assert_eq!(map.insert(c, "c"), None);
+        assert_eq!(map.insert(a, "A"), Some("a"));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(a), Some(&"A"));
+        assert!(map.contains(b));
+
+        let values: Vec<_> = map.iter_pos().map(|(pos, &v)| (pos, v)).collect();
+        assert_eq!(values, vec![(c.clone(), "c"), (a.clone(), "A"), (b.clone(), "b")]);
+        let last: BigUint = BigUint::from(1usize) << 189usize;
+        assert_eq!(map.last(), Some((last.clone(), &"b")));
+
+        let values: Vec<_> = map.range(BigUint::from(1usize)..last.clone()).collect();
+        assert_eq!(values, vec![(1usize.into(), &"A")]);
+        let values: Vec<_> = map.range(BigUint::from(1usize)..).collect();
+        assert_eq!(values.len(), 2);
+
+        *map.get_mut(c).unwrap() = "C";
+        assert_eq!(map.get_index(&0usize.into()), Some(&"C"));
+        assert_eq!(map.remove(b), Some("b"));
+        assert_eq!(map.remove_index(&last), None);
+        assert_eq!(map.len(), 2);
+    }
+}
@@ -5,6 +5,8 @@ use crate::{
     Construct,
     Data,
     Of,
+    Scratch,
+    prepared::Prepare,
     space::Space,
 };
 
@@ -43,6 +45,29 @@ impl Space<BigUint> for SqPair<Data> {
         pos.0 = &index % dim;
         pos.1 = &index / dim;
     }
+    fn to_index_with(
+        &self,
+        _scratch: &mut Scratch,
+        dim: &Self::Dim,
+        (a, b): &Self::Pos,
+        index: &mut BigUint,
+    ) {
+        index.clone_from(b);
+        *index *= dim;
+        *index += a;
+    }
+    fn to_pos_with(
+        &self,
+        _scratch: &mut Scratch,
+        dim: &Self::Dim,
+        index: &BigUint,
+        (a, b): &mut Self::Pos,
+    ) {
+        a.clone_from(index);
+        *a %= dim;
+        b.clone_from(index);
+        *b /= dim;
+    }
 }
 
 impl<T, N> Space<N> for SqPair<Of<T>>
@@ -70,7 +95,7 @@ impl<T, N> Space<N> for SqPair<Of<T>>
         let data: SqPair<Data> = Construct::new();
         let a = of.to_index(dim, a);
         let b = of.to_index(dim, b);
-        data.to_index(&self.count(dim), &(a, b))
+        data.to_index(&of.count(dim), &(a, b))
     }
     fn to_pos(
         &self,
@@ -87,4 +112,111 @@ impl<T, N> Space<N> for SqPair<Of<T>>
         of.to_pos(dim, pair_a, a);
         of.to_pos(dim, pair_b, b);
     }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        (a, b): &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: SqPair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        of.to_index_with(scratch, dim, a, &mut pair.0);
+        of.to_index_with(scratch, dim, b, &mut pair.1);
+        data.to_index_with(scratch, &of.count(dim), &pair, index);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        dim: &Self::Dim,
+        index: &N,
+        (a, b): &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let of: T = Construct::new();
+        let data: SqPair<Data> = Construct::new();
+        let mut pair = (scratch.take(), scratch.take());
+        data.to_pos_with(scratch, &of.count(dim), index, &mut pair);
+        of.to_pos_with(scratch, dim, &pair.0, a);
+        of.to_pos_with(scratch, dim, &pair.1, b);
+        scratch.put(pair.0);
+        scratch.put(pair.1);
+    }
+    fn try_to_index(&self, dim: &Self::Dim, (min, max): &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let data: SqPair<Data> = Construct::new();
+        let min = of.try_to_index(dim, min)?;
+        let max = of.try_to_index(dim, max)?;
+        Some(data.to_index(&of.count(dim), &(min, max)))
+    }
+}
+
+impl<N, T> Prepare<N> for SqPair<Of<T>>
+    where T: Prepare<N>,
+          N: From<usize>,
+          SqPair<Data>: Space<N, Dim = N, Pos = (N, N)>,
+{
+    /// Tables of the inner space, followed by its count.
+    type Tables = (T::Tables, N);
+    fn tables(&self, dim: &Self::Dim) -> Self::Tables {
+        let of: T = Construct::new();
+        let tables = of.tables(dim);
+        let count = of.prepared_count(dim, &tables);
+        (tables, count)
+    }
+    fn prepared_count(&self, _dim: &Self::Dim, (_, count): &Self::Tables) -> N {
+        let data: SqPair<Data> = Construct::new();
+        data.count(count)
+    }
+    fn prepared_to_index(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        (a, b): &Self::Pos,
+    ) -> N {
+        let of: T = Construct::new();
+        let data: SqPair<Data> = Construct::new();
+        let a = of.prepared_to_index(dim, tables, a);
+        let b = of.prepared_to_index(dim, tables, b);
+        data.to_index(count, &(a, b))
+    }
+    fn prepared_to_pos(
+        &self,
+        dim: &Self::Dim,
+        (tables, count): &Self::Tables,
+        index: N,
+        (a, b): &mut Self::Pos,
+    ) {
+        let of: T = Construct::new();
+        let data: SqPair<Data> = Construct::new();
+        let mut pair = data.zero(count);
+        data.to_pos(count, index, &mut pair);
+        of.prepared_to_pos(dim, tables, pair.0, a);
+        of.prepared_to_pos(dim, tables, pair.1, b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn of() {
+        let x: SqPair<Of<Pair>> = Construct::new();
+        let ref dim = 3;
+        assert_eq!(x.count(dim), 9);
+        assert_eq!(x.to_index(dim, &((0, 1), (0, 2))), 3);
+        assert_eq!(x.to_index(dim, &((0, 2), (0, 1))), 1);
+        let mut pos = x.zero(dim);
+        for i in 0..9 {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+    }
 }
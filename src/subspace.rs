@@ -1,8 +1,9 @@
 //! Implements traits for tuples such that subspaces can be constructed.
 
-use std::ops::{Mul, Div, Sub, Add};
+use std::ops::{Mul, Div, Sub, Add, MulAssign, AddAssign, DivAssign, RemAssign};
 
-use crate::Construct;
+use crate::{Construct, Scratch};
+use crate::prepared::Prepare;
 use crate::space::Space;
 
 impl<T, U> Construct for (T, U)
@@ -15,6 +16,10 @@ impl<N, T, U> Space<N> for (T, U)
     where T: Space<N>,
           U: Space<N>,
           N: Add<N, Output = N>,
+          for<'a> N: MulAssign<&'a N> +
+                     AddAssign<&'a N> +
+                     DivAssign<&'a N> +
+                     RemAssign<&'a N>,
           for<'a> &'a N: Mul<&'a N, Output = N> +
                          Div<&'a N, Output = N> +
                          Sub<&'a N, Output = N>,
@@ -54,4 +59,138 @@ impl<N, T, U> Space<N> for (T, U)
         u.to_pos(dim_u, &ind - &(&x * &count), pu);
         t.to_pos(dim_t, x, pt);
     }
+    fn try_to_index(&self, (dim_t, dim_u): &Self::Dim, (pt, pu): &Self::Pos) -> Option<N> {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let count = u.count(dim_u);
+        Some(&t.try_to_index(dim_t, pt)? * &count + u.try_to_index(dim_u, pu)?)
+    }
+    fn to_index_batch(
+        &self,
+        (dim_t, dim_u): &Self::Dim,
+        pos: &[Self::Pos],
+        index: &mut [N],
+    ) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let count = u.count(dim_u);
+        for ((pt, pu), i) in pos.iter().zip(index) {
+            *i = &t.to_index(dim_t, pt) * &count + u.to_index(dim_u, pu);
+        }
+    }
+    fn to_pos_batch(
+        &self,
+        (dim_t, dim_u): &Self::Dim,
+        index: &[N],
+        pos: &mut [Self::Pos],
+    )
+        where N: Clone
+    {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let count = u.count(dim_u);
+        for (ind, (pt, pu)) in index.iter().zip(pos) {
+            let x = ind / &count;
+            u.to_pos(dim_u, ind - &(&x * &count), pu);
+            t.to_pos(dim_t, x, pt);
+        }
+    }
+    fn to_index_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim_t, dim_u): &Self::Dim,
+        (pt, pu): &Self::Pos,
+        index: &mut N,
+    )
+        where N: Clone + From<usize>
+    {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let mut low = scratch.take();
+        t.to_index_with(scratch, dim_t, pt, index);
+        u.to_index_with(scratch, dim_u, pu, &mut low);
+        *index *= &u.count(dim_u);
+        *index += &low;
+        scratch.put(low);
+    }
+    fn to_pos_with(
+        &self,
+        scratch: &mut Scratch<N>,
+        (dim_t, dim_u): &Self::Dim,
+        ind: &N,
+        (pt, pu): &mut Self::Pos,
+    )
+        where N: Clone + From<usize>
+    {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let count = u.count(dim_u);
+        let mut x = scratch.take();
+        let mut low = scratch.take();
+        x.clone_from(ind);
+        x /= &count;
+        low.clone_from(ind);
+        low %= &count;
+        u.to_pos_with(scratch, dim_u, &low, pu);
+        t.to_pos_with(scratch, dim_t, &x, pt);
+        scratch.put(x);
+        scratch.put(low);
+    }
+}
+
+impl<N, T, U> Prepare<N> for (T, U)
+    where T: Prepare<N>,
+          U: Prepare<N>,
+          N: Add<N, Output = N>,
+          for<'a> N: MulAssign<&'a N> +
+                     AddAssign<&'a N> +
+                     DivAssign<&'a N> +
+                     RemAssign<&'a N>,
+          for<'a> &'a N: Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Sub<&'a N, Output = N>,
+{
+    /// Tables of both spaces, followed by the count of the second space.
+    type Tables = (T::Tables, U::Tables, N);
+    fn tables(&self, (dim_t, dim_u): &Self::Dim) -> Self::Tables {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let tables_u = u.tables(dim_u);
+        let count = u.prepared_count(dim_u, &tables_u);
+        (t.tables(dim_t), tables_u, count)
+    }
+    fn prepared_count(
+        &self,
+        (dim_t, _): &Self::Dim,
+        (tables_t, _, count): &Self::Tables,
+    ) -> N {
+        let t: T = Construct::new();
+        &t.prepared_count(dim_t, tables_t) * count
+    }
+    fn prepared_to_index(
+        &self,
+        (dim_t, dim_u): &Self::Dim,
+        (tables_t, tables_u, count): &Self::Tables,
+        (pt, pu): &Self::Pos,
+    ) -> N {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        &t.prepared_to_index(dim_t, tables_t, pt) * count +
+            u.prepared_to_index(dim_u, tables_u, pu)
+    }
+    fn prepared_to_pos(
+        &self,
+        (dim_t, dim_u): &Self::Dim,
+        (tables_t, tables_u, count): &Self::Tables,
+        ind: N,
+        (pt, pu): &mut Self::Pos,
+    ) {
+        let t: T = Construct::new();
+        let u: U = Construct::new();
+        let x = &ind / count;
+        u.prepared_to_pos(dim_u, tables_u, &ind - &(&x * count), pu);
+        t.prepared_to_pos(dim_t, tables_t, x, pt);
+    }
 }
@@ -0,0 +1,122 @@
+use std::marker::PhantomData;
+
+use crate::{
+    BigUint,
+    Construct,
+    Data,
+    Of,
+    unbounded::Unbounded,
+};
+
+/// An unbounded space of all pairs of natural numbers,
+/// using the Szudzik pairing function.
+///
+/// Position is `(x, y)`, index is `y * y + x` when `x < y`,
+/// otherwise `x * x + x + y`.
+/// Pairs are enumerated along the shells where `max(x, y)` is constant,
+/// so all pairs below `n` come before the index `n * n`.
+pub struct Szudzik<T = Data>(PhantomData<T>);
+
+impl<T> Construct for Szudzik<T> {
+    fn new() -> Self { Szudzik(PhantomData) }
+}
+
+impl Unbounded<usize> for Szudzik<Data> {
+    type Pos = (usize, usize);
+    fn zero(&self) -> (usize, usize) { (0, 0) }
+    fn to_index(&self, &(x, y): &(usize, usize)) -> usize {
+        if x < y { y * y + x } else { x * x + x + y }
+    }
+    fn to_pos(&self, index: usize, pos: &mut (usize, usize)) {
+        use num_integer::Roots;
+
+        let s = index.sqrt();
+        let r = index - s * s;
+        *pos = if r < s { (r, s) } else { (s, r - s) };
+    }
+}
+
+impl Unbounded<BigUint> for Szudzik<Data> {
+    type Pos = (BigUint, BigUint);
+    fn zero(&self) -> Self::Pos { (0usize.into(), 0usize.into()) }
+    fn to_index(&self, (x, y): &Self::Pos) -> BigUint {
+        if x < y { y * y + x } else { x * x + x + y }
+    }
+    fn to_pos(&self, index: BigUint, pos: &mut Self::Pos) {
+        let s = index.sqrt();
+        let r = index - &s * &s;
+        *pos = if r < s { (r, s) } else { let r = r - &s; (s, r) };
+    }
+}
+
+impl<N, T> Unbounded<N> for Szudzik<Of<T>>
+    where T: Unbounded<N>,
+          Szudzik<Data>: Unbounded<N, Pos = (N, N)>,
+{
+    type Pos = (T::Pos, T::Pos);
+    fn zero(&self) -> Self::Pos {
+        let of: T = Construct::new();
+        (of.zero(), of.zero())
+    }
+    fn to_index(&self, (x, y): &Self::Pos) -> N {
+        let of: T = Construct::new();
+        let data: Szudzik<Data> = Construct::new();
+        data.to_index(&(of.to_index(x), of.to_index(y)))
+    }
+    fn to_pos(&self, index: N, (x, y): &mut Self::Pos) {
+        let of: T = Construct::new();
+        let data: Szudzik<Data> = Construct::new();
+        let mut pair = data.zero();
+        data.to_pos(index, &mut pair);
+        of.to_pos(pair.0, x);
+        of.to_pos(pair.1, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn data() {
+        let x: Szudzik = Construct::new();
+        let index = |pos: &(usize, usize)| -> usize { x.to_index(pos) };
+        assert_eq!(index(&(0, 0)), 0);
+        assert_eq!(index(&(0, 1)), 1);
+        assert_eq!(index(&(1, 0)), 2);
+        assert_eq!(index(&(1, 1)), 3);
+        assert_eq!(index(&(0, 2)), 4);
+        let mut pos = (0, 0);
+        for i in 0..1000 {
+            x.to_pos(i, &mut pos);
+            assert_eq!(index(&pos), i);
+        }
+        x.to_pos(3usize, &mut pos);
+        assert_eq!(pos, (1, 1));
+    }
+
+    #[test]
+    fn data_big() {
+        let x: Szudzik = Construct::new();
+        let index = |pos: &(BigUint, BigUint)| -> BigUint { x.to_index(pos) };
+        let mut pos = (0usize.into(), 0usize.into());
+        for i in 0usize..1000 {
+            x.to_pos(BigUint::from(i), &mut pos);
+            assert_eq!(index(&pos), i.into());
+        }
+        let big = BigUint::from(3usize).pow(100u32);
+        x.to_pos(big.clone(), &mut pos);
+        assert_eq!(index(&pos), big);
+    }
+
+    #[test]
+    fn of() {
+        let x: Szudzik<Of<Cantor>> = Construct::new();
+        let index = |pos: &((usize, usize), (usize, usize))| -> usize { x.to_index(pos) };
+        let mut pos = ((0, 0), (0, 0));
+        for i in 0usize..1000 {
+            x.to_pos(i, &mut pos);
+            assert_eq!(index(&pos), i);
+        }
+    }
+}
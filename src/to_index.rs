@@ -1,5 +1,24 @@
+use crate::Scratch;
+
 /// Implemented by spaces that can convert position to index.
 pub trait ToIndex<T, U, N> {
     /// Converts position to index.
     fn to_index(&self, dim: &T, pos: &U) -> N;
+    /// Converts position to index, or returns `None` if the position is not in the space.
+    fn try_to_index(&self, dim: &T, pos: &U) -> Option<N> {
+        Some(self.to_index(dim, pos))
+    }
+    /// Converts many positions to indices.
+    ///
+    /// Panics if the slices have different lengths.
+    fn to_index_batch(&self, dim: &T, pos: &[U], index: &mut [N]) {
+        assert_eq!(pos.len(), index.len(), "Different lengths");
+        for (p, i) in pos.iter().zip(index) {
+            *i = self.to_index(dim, p);
+        }
+    }
+    /// Converts position to index, reusing temporary values of a scratch context.
+    fn to_index_with(&self, _scratch: &mut Scratch<N>, dim: &T, pos: &U, index: &mut N) {
+        *index = self.to_index(dim, pos);
+    }
 }
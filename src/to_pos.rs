@@ -1,5 +1,24 @@
+use crate::Scratch;
+
 /// Implemented for spaces which can convert an index to position type.
 pub trait ToPos<T, U, N> {
     /// Converts index to position.
     fn to_pos(&self, dim: &T, index: N, pos: &mut U);
+    /// Converts many indices to positions.
+    ///
+    /// Panics if the slices have different lengths.
+    fn to_pos_batch(&self, dim: &T, index: &[N], pos: &mut [U])
+        where N: Clone
+    {
+        assert_eq!(index.len(), pos.len(), "Different lengths");
+        for (i, p) in index.iter().zip(pos) {
+            self.to_pos(dim, i.clone(), p);
+        }
+    }
+    /// Converts index to position, reusing temporary values of a scratch context.
+    fn to_pos_with(&self, _scratch: &mut Scratch<N>, dim: &T, index: &N, pos: &mut U)
+        where N: Clone
+    {
+        self.to_pos(dim, index.clone(), pos);
+    }
 }
@@ -0,0 +1,20 @@
+//! Helper trait for implementing unbounded discrete spaces.
+
+use crate::Construct;
+
+/// Implemented by discrete spaces that have no dimension.
+///
+/// An unbounded space is a one-to-one map with all natural numbers.
+/// Since every index is valid, there is no count,
+/// and the space can be enumerated lazily by counting from zero.
+pub trait Unbounded<N>: Construct + Sized {
+    /// The position type of the space.
+    type Pos;
+
+    /// Creates a default element.
+    fn zero(&self) -> Self::Pos;
+    /// Converts position to index.
+    fn to_index(&self, pos: &Self::Pos) -> N;
+    /// Converts index to position.
+    fn to_pos(&self, index: N, pos: &mut Self::Pos);
+}
@@ -0,0 +1,211 @@
+//! Views that select part of a space by transforming indices.
+//!
+//! Views can be combined with each other and with `Reversed`,
+//! e.g. `Slice<Stride<Pair>>` is every few pairs within a range.
+//! Since a view does not contain every position of the wrapped space,
+//! `to_index` panics for positions outside the view.
+//! Use `try_to_index` to check whether a position is inside the view.
+
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Range, Rem, Sub};
+
+use crate::{
+    Construct,
+    space::Space,
+};
+
+/// A consecutive range of indices of a space.
+///
+/// Dimension is `(<dimension of space>, <range of indices>)`,
+/// where the range must be within the space.
+/// Index `0` of the slice is the start of the range.
+/// Panics if the range ends before it starts or after the space ends.
+pub struct Slice<T>(PhantomData<T>);
+
+/// Every `step`th index of a space, starting at index `0`.
+///
+/// Dimension is `(<dimension of space>, <step>)`.
+/// Panics if the step is zero.
+pub struct Stride<T>(PhantomData<T>);
+
+impl<T> Construct for Slice<T> {
+    fn new() -> Self {Slice(PhantomData)}
+}
+
+impl<T> Construct for Stride<T> {
+    fn new() -> Self {Stride(PhantomData)}
+}
+
+impl<N, T> Space<N> for Slice<T>
+    where T: Space<N>,
+          N: PartialOrd,
+          for<'a> &'a N: Add<&'a N, Output = N> + Sub<&'a N, Output = N>,
+{
+    type Dim = (T::Dim, Range<N>);
+    type Pos = T::Pos;
+    fn count(&self, (dim, range): &Self::Dim) -> N {
+        let of: T = Construct::new();
+        assert!(range.start <= range.end, "Slice range ends before it starts");
+        assert!(range.end <= of.count(dim), "Slice range ends after the space");
+        &range.end - &range.start
+    }
+    fn zero(&self, (dim, _): &Self::Dim) -> Self::Pos {
+        let of: T = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> N {
+        self.try_to_index(dim, pos).expect("Position outside of slice")
+    }
+    fn to_pos(&self, (dim, range): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        let of: T = Construct::new();
+        of.to_pos(dim, &range.start + &index, pos)
+    }
+    fn try_to_index(&self, (dim, range): &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let index = of.try_to_index(dim, pos)?;
+        if range.contains(&index) {Some(&index - &range.start)} else {None}
+    }
+}
+
+impl<N, T> Space<N> for Stride<T>
+    where T: Space<N>,
+          N: PartialEq + From<usize>,
+          for<'a> &'a N: Add<&'a N, Output = N> +
+                         Sub<&'a N, Output = N> +
+                         Mul<&'a N, Output = N> +
+                         Div<&'a N, Output = N> +
+                         Rem<&'a N, Output = N>,
+{
+    type Dim = (T::Dim, N);
+    type Pos = T::Pos;
+    fn count(&self, (dim, step): &Self::Dim) -> N {
+        let of: T = Construct::new();
+        assert!(*step != 0usize.into(), "Stride step is zero");
+        // Rounds up, since index `0` is always included.
+        let one: N = 1usize.into();
+        &(&(&of.count(dim) + step) - &one) / step
+    }
+    fn zero(&self, (dim, _): &Self::Dim) -> Self::Pos {
+        let of: T = Construct::new();
+        of.zero(dim)
+    }
+    fn to_index(&self, dim: &Self::Dim, pos: &Self::Pos) -> N {
+        self.try_to_index(dim, pos).expect("Position outside of stride")
+    }
+    fn to_pos(&self, (dim, step): &Self::Dim, index: N, pos: &mut Self::Pos) {
+        let of: T = Construct::new();
+        of.to_pos(dim, &index * step, pos)
+    }
+    fn try_to_index(&self, (dim, step): &Self::Dim, pos: &Self::Pos) -> Option<N> {
+        let of: T = Construct::new();
+        let index = of.try_to_index(dim, pos)?;
+        if &index % step == 0usize.into() {Some(&index / step)} else {None}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn slice() {
+        let x: Slice<Pair> = Construct::new();
+        let ref dim = (4, 2..5);
+        assert_eq!(x.count(dim), 3);
+        let mut pos = (0, 0);
+        x.to_pos(dim, 0, &mut pos);
+        assert_eq!(pos, (1, 2));
+        assert_eq!(x.to_index(dim, &pos), 0);
+        assert_eq!(x.try_to_index(dim, &(1, 3)), Some(2));
+        assert_eq!(x.try_to_index(dim, &(0, 1)), None);
+        assert_eq!(x.try_to_index(dim, &(2, 3)), None);
+    }
+
+    #[test]
+    fn stride() {
+        let x: Stride<Permutation> = Construct::new();
+        let ref dim = (3, 2);
+        assert_eq!(x.count(dim), 3);
+        let ref dim = (4, 5);
+        assert_eq!(x.count(dim), 5);
+        let mut pos = vec![];
+        for i in 0..5 {
+            x.to_pos(dim, i, &mut pos);
+            assert_eq!(x.to_index(dim, &pos), i);
+        }
+        assert_eq!(x.try_to_index(dim, &vec![0, 1, 3, 2]), None);
+    }
+
+    #[test]
+    fn compose() {
+        // The last 3 of every second pair, in reverse.
+        let x: Reversed<Slice<Stride<Pair>>> = Construct::new();
+        let ref dim = ((5, 2), 2..5);
+        assert_eq!(x.count(dim), 3);
+        let mut pos = (0, 0);
+        x.to_pos(dim, 0, &mut pos);
+        // Index 8 of the pairs.
+        assert_eq!(pos, (2, 4));
+        assert_eq!(x.to_index(dim, &pos), 0);
+
+        let x: Slice<Stride<Pair>> = Construct::new();
+        let ref dim = ((5, 2), 2..5);
+        assert_eq!(x.try_to_index(dim, &(0, 2)), None);
+        assert_eq!(x.try_to_index(dim, &(1, 3)), Some(0));
+    }
+
+    #[test]
+    fn compose_outside() {
+        // Views inside tuples and `Of` spaces are checked, not panicking.
+        let x: (Slice<Pair>, Pair) = Construct::new();
+        let ref dim = ((4, 2..5), 3);
+        assert_eq!(x.try_to_index(dim, &((0, 1), (0, 1))), None);
+        assert_eq!(x.try_to_index(dim, &((1, 3), (0, 2))), Some(7));
+        assert_eq!(x.try_to_index(dim, &((1, 3), (0, 2))), Some(x.to_index(dim, &((1, 3), (0, 2)))));
+
+        let x: Pair<Of<Slice<Pair>>> = Construct::new();
+        let ref dim = (4, 2..5);
+        assert_eq!(x.try_to_index(dim, &((0, 1), (1, 2))), None);
+        assert_eq!(x.try_to_index(dim, &((1, 2), (0, 3))), Some(0));
+
+        let x: Either<Pair, Stride<Pair>> = Construct::new();
+        let ref dim = (3, (4, 2));
+        assert_eq!(x.try_to_index(dim, &Select::Snd((0, 2))), None);
+        assert_eq!(x.try_to_index(dim, &Select::Snd((1, 2))), Some(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Stride step is zero")]
+    fn zero_step() {
+        let x: Stride<Pair> = Construct::new();
+        x.count(&(4, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Slice range ends before it starts")]
+    fn reversed_range() {
+        let x: Slice<Pair> = Construct::new();
+        #[allow(clippy::reversed_empty_ranges)]
+        x.count(&(4, 3..2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Slice range ends after the space")]
+    fn range_past_end() {
+        let x: Slice<Pair> = Construct::new();
+        x.count(&(4, 2..7));
+    }
+
+    #[test]
+    fn big() {
+        let x: Slice<Stride<Pair>> = Construct::new();
+        let n = |x: usize| -> BigUint {x.into()};
+        let ref dim = ((n(5), n(2)), n(2)..n(5));
+        assert_eq!(x.count(dim), n(3));
+        let mut pos = (n(0), n(0));
+        x.to_pos(dim, n(2), &mut pos);
+        assert_eq!(pos, (n(2), n(4)));
+        assert_eq!(x.to_index(dim, &pos), n(2));
+        assert_eq!(x.try_to_index(dim, &(n(0), n(2))), None);
+    }
+}